@@ -421,4 +421,42 @@ CLOSED: [2026-06-24 Wed]`;
     assert.deepEqual(doneHeading.planning, [{ keyword: 'CLOSED', text: '[2026-06-24 Wed]' }]);
   });
 
+  it('uses the fence language from the bridge without touching the org source', () => {
+    const nodes: LexicalNode[] = [
+      {
+        type: 'code_block',
+        language: 'emacs-lisp',
+        fence_language: 'lisp',
+        text: '(message "hi")',
+        raw: '#+begin_src emacs-lisp\n(message "hi")\n#+end_src',
+        line_start: 0,
+        line_end: 2
+      }
+    ];
+
+    const [block] = lexicalNodesToProjection(nodes, '', { outlineOnly: false, readerMode: false });
+    assert.equal(block.type, 'code_block');
+    if (block.type !== 'code_block') {
+      assert.fail('expected code block projection');
+    }
+    assert.equal(block.language, 'emacs-lisp');
+    assert.equal(block.fenceLanguage, 'lisp');
+  });
+
+  it('keeps every header row above the table rule', () => {
+    const nodes: LexicalNode[] = [
+      {
+        type: 'table',
+        header: [['Name', 'Qty'], ['(unit)', '']],
+        rows: [['apple', '3']],
+        raw: '| Name | Qty |\n| (unit) |\n|---+---|\n| apple | 3 |',
+        line_start: 0,
+        line_end: 3
+      }
+    ];
+
+    const [table] = lexicalNodesToProjection(nodes, '', { outlineOnly: false, readerMode: false });
+    assert.equal(table.type, 'table');
+    assert.equal(orgProjectionPlainText(table), '| Name | Qty |\n| (unit) |  |\n|---+---|\n| apple | 3 |');
+  });
 });
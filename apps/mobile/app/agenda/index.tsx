@@ -79,7 +79,7 @@ function agendaStatusKeyword(item: AgendaItem) {
 }
 
 function agendaItemDoneLike(item: AgendaItem) {
-  return isDoneLikeStatus(agendaStatusKeyword(item));
+  return item.done ?? isDoneLikeStatus(agendaStatusKeyword(item));
 }
 
 function orderAgendaItems(items: AgendaItem[], preserveOrderKeys?: string[] | null) {
@@ -23,8 +23,10 @@ import { useSafeAreaInsets } from "react-native-safe-area-context";
 import { useQuery, useQueryClient } from "@tanstack/react-query";
 
 import {
+  tableRows,
   type DocumentPayload,
   type DocumentRef,
+  type DocumentSort,
   type LexicalNode,
 } from "@postep/bridge";
 import { LexicalDocument } from "../../components/LexicalDocument";
@@ -259,6 +261,18 @@ function GraphicalActionIcon({
 const PREVIEW_LOAD_CONCURRENCY = 4;
 const PREVIEW_LOAD_TIMEOUT_MS = 5000;
 const ANDROID_STATUS_BAR_FALLBACK = 58;
+const DOCUMENT_SORT_LABELS: Record<DocumentSort, string> = {
+  path: "By date",
+  modified_desc: "Recently edited",
+  modified_asc: "Least recently edited",
+  title_asc: "By title",
+};
+const NEXT_DOCUMENT_SORT: Record<DocumentSort, DocumentSort> = {
+  path: "modified_desc",
+  modified_desc: "modified_asc",
+  modified_asc: "title_asc",
+  title_asc: "path",
+};
 
 function routePathParam(value?: string | string[]): string | null {
   const param = Array.isArray(value) ? value[0] : value;
@@ -488,7 +502,10 @@ function textForNode(node: LexicalNode) {
     return cleanOrgText(node.text);
   }
   if (node.type === "table") {
-    return node.rows[0]?.join(" · ") ?? "Table";
+    return tableRows(node)[0]?.join(" · ") ?? "Table";
+  }
+  if (node.type === "verse") {
+    return cleanOrgText(node.lines.join(" "));
   }
   return cleanOrgText(node.raw);
 }
@@ -525,16 +542,19 @@ function listItemPreviewText(
 }
 
 function titleFromDocument(doc: DocumentRef, nodes: LexicalNode[]) {
-  const titleDirective = nodes.find(
-    (node) =>
-      node.type === "directive" && node.keyword.toUpperCase() === "TITLE",
-  );
-  if (
-    titleDirective &&
-    "text" in titleDirective &&
-    titleDirective.text.trim()
-  ) {
-    return titleDirective.text.trim();
+  // A title split across several #+TITLE: lines reads as one, space-joined.
+  const title = nodes
+    .flatMap((node) =>
+      node.type === "directive" &&
+      node.keyword.toUpperCase() === "TITLE" &&
+      "text" in node &&
+      node.text.trim()
+        ? [node.text.trim()]
+        : [],
+    )
+    .join(" ");
+  if (title) {
+    return title;
   }
   const heading = nodes.find((node) => node.type === "heading");
   if (heading && "text" in heading && heading.text.trim()) {
@@ -687,7 +707,7 @@ function renderOrgNode(
   if (node.type === "table") {
     return (
       <View style={styles.metadataCard}>
-        {node.rows.map((row, index) => (
+        {tableRows(node).map((row, index) => (
           <Text key={`${row.join(":")}:${index}`} style={styles.monoText}>
             {row.join("   ")}
           </Text>
@@ -696,6 +716,14 @@ function renderOrgNode(
     );
   }
 
+  if (node.type === "verse") {
+    return (
+      <Text style={styles.paragraphText}>
+        {node.lines.map((line) => cleanOrgText(line)).join("\n")}
+      </Text>
+    );
+  }
+
   if (node.type === "code_block") {
     return (
       <View style={styles.codeCard}>
@@ -707,6 +735,15 @@ function renderOrgNode(
     );
   }
 
+  if (node.type === "result") {
+    return (
+      <View style={styles.codeCard}>
+        <Text style={styles.metadataLabel}>{node.name ?? "Results"}</Text>
+        <Text style={styles.codeText}>{node.text}</Text>
+      </View>
+    );
+  }
+
   if (node.type === "directive") {
     if (["TITLE", "CATEGORY"].includes(node.keyword.toUpperCase())) {
       return (
@@ -827,6 +864,7 @@ export default function LibraryScreen() {
   const [newChecklistText, setNewChecklistText] = useState("");
   const [showCheckedItems, setShowCheckedItems] = useState(true);
   const [searchQuery, setSearchQuery] = useState("");
+  const [documentSort, setDocumentSort] = useState<DocumentSort>("path");
   const [isAppMenuOpen, setIsAppMenuOpen] = useState(false);
   const [isSyncingOrgFiles, setIsSyncingOrgFiles] = useState(false);
   const [interactionStatus, setInteractionStatus] = useState<string | null>(
@@ -873,11 +911,11 @@ export default function LibraryScreen() {
     [],
   );
 
-  const documentsKey = documentsQueryKey(bridgeConfig);
+  const documentsKey = documentsQueryKey(bridgeConfig, documentSort);
   const cachedDocuments = queryClient.getQueryData<DocumentRef[]>(documentsKey);
   const documentsQuery = useQuery({
     queryKey: documentsKey,
-    queryFn: () => listDocumentsForConfig(bridgeConfig),
+    queryFn: () => listDocumentsForConfig(bridgeConfig, documentSort),
     enabled: hasConfiguredRoots,
     initialData: cachedDocuments,
   });
@@ -933,6 +971,7 @@ export default function LibraryScreen() {
   const previewKey = documentPreviewsQueryKey(
     bridgeConfig,
     documentsQuery.data?.map((doc) => doc.path),
+    documentSort,
   );
   const cachedPreviews = queryClient.getQueryData<MeasuredNotePreviews>(previewKey);
   const previewsQuery = useQuery({
@@ -947,13 +986,16 @@ export default function LibraryScreen() {
           PREVIEW_LOAD_CONCURRENCY,
           (doc) => loadPreviewOrSkip(bridgeConfig, doc),
         );
-        return previews
-          .filter((preview): preview is NotePreview => preview !== null)
-          .sort((left, right) =>
-            (left.primaryDate ?? "9999-12-31").localeCompare(
-              right.primaryDate ?? "9999-12-31",
-            ),
-          );
+        const loaded = previews.filter(
+          (preview): preview is NotePreview => preview !== null,
+        );
+        return documentSort === "path"
+          ? loaded.sort((left, right) =>
+              (left.primaryDate ?? "9999-12-31").localeCompare(
+                right.primaryDate ?? "9999-12-31",
+              ),
+            )
+          : loaded;
       }),
   });
 
@@ -2087,6 +2129,17 @@ export default function LibraryScreen() {
               <Text style={styles.noteCountText}>
                 {documentsQuery.data?.length ?? 0} notes
               </Text>
+              <TouchableOpacity
+                testID="document-sort-toggle"
+                onPress={() =>
+                  setDocumentSort((current) => NEXT_DOCUMENT_SORT[current])
+                }
+                accessibilityLabel="Change note order"
+              >
+                <Text style={styles.latencyText}>
+                  {DOCUMENT_SORT_LABELS[documentSort]}
+                </Text>
+              </TouchableOpacity>
               {interactionStatus && (
                 <Text style={styles.latencyText}>{interactionStatus}</Text>
               )}
@@ -1,5 +1,6 @@
 import {
   loadAgendaSnapshotAsync,
+  tableRows,
   type AgendaItem,
   type AgendaSnapshot,
   type Habit,
@@ -366,7 +367,7 @@ function contextFromNodes(nodes: LexicalNode[]): string {
         return [node.text];
       }
       if (node.type === "table") {
-        return [node.rows.map((row) => row.join(" · ")).join("\n")];
+        return [tableRows(node).map((row) => row.join(" · ")).join("\n")];
       }
       return [];
     })
@@ -407,9 +408,13 @@ function logEntriesFromNodes(nodes: LexicalNode[]): Habit["log_entries"] {
       }
       return [
         ...node.text.matchAll(
-          /State "([^"]+)" from "[^"]*" \[(\d{4}-\d{2}-\d{2})/g,
+          /State "([^"]+)" from "([^"]*)" \[(\d{4}-\d{2}-\d{2})/g,
         ),
-      ].map((match) => ({ state: match[1], date: match[2] }));
+      ].map((match) => ({
+        state: match[1],
+        from_state: match[2] || null,
+        date: match[3],
+      }));
     })
     .filter((entry) => entry.state && entry.date);
 }
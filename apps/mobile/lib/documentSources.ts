@@ -5,6 +5,7 @@ import {
   updateDocumentAsync,
   type DocumentPayload,
   type DocumentRef,
+  type DocumentSort,
   type OrgBridgeConfig,
   type UpdateDocumentRequest,
 } from "@postep/bridge";
@@ -215,13 +216,14 @@ function splitConfig(config: OrgBridgeConfig): {
 
 export async function listDocumentsForConfig(
   config: OrgBridgeConfig,
+  order: DocumentSort = "path",
 ): Promise<DocumentRef[]> {
   const startedAt = Date.now();
   if (config.roots.length === 0 && (config.roamRoots?.length ?? 0) === 0) {
     return [];
   }
 
-  const cacheKey = configCacheKey(config);
+  const cacheKey = order === "path" ? configCacheKey(config) : `${configCacheKey(config)}|${order}`;
   const cached = documentListCache.get(cacheKey);
   if (cached) {
     documentSourceStats.listCacheHits += 1;
@@ -233,7 +235,7 @@ export async function listDocumentsForConfig(
   const documents: DocumentRef[] = [];
 
   if (nativeConfig.roots.length > 0 || (nativeConfig.roamRoots?.length ?? 0) > 0) {
-    documents.push(...(await listDocumentsAsync(nativeConfig)));
+    documents.push(...(await listDocumentsAsync(nativeConfig, order)));
   }
 
   const safDocumentRoots = dedupeSourceList([...safRoots, ...safRoamRoots]);
@@ -260,7 +262,11 @@ export async function listDocumentsForConfig(
     }
   }
 
-  const sorted = dedupeDocuments(documents).sort((left, right) => left.name.localeCompare(right.name));
+  // Other orders keep the native listing's order; SAF documents carry no cached metadata
+  // and follow it as listed.
+  const deduped = dedupeDocuments(documents);
+  const sorted =
+    order === "path" ? deduped.sort((left, right) => left.name.localeCompare(right.name)) : deduped;
   documentListCache.set(cacheKey, sorted);
   console.log("Postep document listing", {
     files: sorted.length,
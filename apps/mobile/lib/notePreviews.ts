@@ -1,3 +1,4 @@
+import { tableRows } from "@postep/bridge";
 import type { DocumentPayload, DocumentRef, LexicalNode } from "@postep/bridge";
 
 export type NoteLine = {
@@ -72,7 +73,10 @@ function textForNode(node: LexicalNode) {
     return cleanOrgText(node.text);
   }
   if (node.type === "table") {
-    return node.rows[0]?.join(" · ") ?? "Table";
+    return tableRows(node)[0]?.join(" · ") ?? "Table";
+  }
+  if (node.type === "verse") {
+    return cleanOrgText(node.lines.join(" "));
   }
   return cleanOrgText(node.raw);
 }
@@ -109,16 +113,19 @@ function listItemPreviewText(
 }
 
 function titleFromDocument(doc: DocumentRef, nodes: LexicalNode[]) {
-  const titleDirective = nodes.find(
-    (node) =>
-      node.type === "directive" && node.keyword.toUpperCase() === "TITLE",
-  );
-  if (
-    titleDirective &&
-    "text" in titleDirective &&
-    titleDirective.text.trim()
-  ) {
-    return titleDirective.text.trim();
+  // A title split across several #+TITLE: lines reads as one, space-joined.
+  const title = nodes
+    .flatMap((node) =>
+      node.type === "directive" &&
+      node.keyword.toUpperCase() === "TITLE" &&
+      "text" in node &&
+      node.text.trim()
+        ? [node.text.trim()]
+        : [],
+    )
+    .join(" ");
+  if (title) {
+    return title;
   }
   const heading = nodes.find((node) => node.type === "heading");
   if (heading && "text" in heading && heading.text.trim()) {
@@ -6,6 +6,7 @@ import {
   createEditor
 } from 'lexical';
 
+import { tableRows } from '@postep/bridge';
 import type { LexicalNode } from '@postep/bridge';
 
 type ProjectionMetadata = { id?: string; lineStart?: number; lineEnd?: number; sourceRaw?: string };
@@ -17,8 +18,8 @@ export type LexicalProjectionNode =
   | (ProjectionMetadata & { type: 'planning'; keyword?: string; children: Array<{ text: string }> })
   | (ProjectionMetadata & { type: 'property_drawer'; children: Array<{ text: string }>; properties?: Record<string, string> })
   | (ProjectionMetadata & { type: 'drawer'; name?: string; collapsed?: boolean; children: Array<{ text: string }> })
-  | (ProjectionMetadata & { type: 'code_block'; language?: string | null; children: Array<{ text: string }> })
-  | (ProjectionMetadata & { type: 'table'; rows: string[][]; children: Array<{ text: string }> })
+  | (ProjectionMetadata & { type: 'code_block'; language?: string | null; fenceLanguage?: string | null; children: Array<{ text: string }> })
+  | (ProjectionMetadata & { type: 'table'; header?: string[][]; rows: string[][]; formula?: string | null; children: Array<{ text: string }> })
   | (ProjectionMetadata & { type: 'directive'; keyword?: string; children: Array<{ text: string }> })
   | (ProjectionMetadata & { type: 'horizontal_rule'; children: Array<{ text: string }> })
   | (ProjectionMetadata & { type: 'paragraph'; children: Array<{ text: string }> });
@@ -116,7 +117,14 @@ export function orgProjectionPlainText(node: LexicalProjectionNode): string {
     return `#+BEGIN_SRC${node.language ? ` ${node.language}` : ''}\n${text}\n#+END_SRC`;
   }
   if (node.type === 'table') {
-    return node.rows.map((row) => `| ${row.join(' | ')} |`).join('\n');
+    const body = node.rows.map((row) => `| ${row.join(' | ')} |`);
+    const formulas = node.formula ? node.formula.split('\n').map((line) => `#+TBLFM: ${line}`) : [];
+    if (!node.header || node.header.length === 0) {
+      return [...body, ...formulas].join('\n');
+    }
+    const header = node.header.map((row) => `| ${row.join(' | ')} |`);
+    const separator = `|${node.header[0].map(() => '---').join('+')}|`;
+    return [...header, separator, ...body, ...formulas].join('\n');
   }
   if (node.type === 'horizontal_rule') {
     return '-----';
@@ -442,10 +450,13 @@ function lexicalNodeToProjection(node: LexicalNode, options: ConversionOptions):
     return [{ ...metadata, type: 'drawer', name: node.name, collapsed: node.collapsed, sourceRaw: node.raw, children: [{ text: node.text }] } as LexicalProjectionNode];
   }
   if (node.type === 'code_block') {
-    return [{ ...metadata, type: 'code_block', language: node.language ?? null, sourceRaw: node.raw, children: [{ text: node.text }] } as LexicalProjectionNode];
+    return [{ ...metadata, type: 'code_block', language: node.language ?? null, fenceLanguage: node.fence_language ?? node.language ?? null, sourceRaw: node.raw, children: [{ text: node.text }] } as LexicalProjectionNode];
+  }
+  if (node.type === 'verse') {
+    return [{ ...metadata, type: 'paragraph', sourceRaw: node.raw, children: [{ text: node.lines.join('\n') }] } as LexicalProjectionNode];
   }
   if (node.type === 'table') {
-    return [{ ...metadata, type: 'table', rows: node.rows, sourceRaw: node.raw, children: [{ text: node.rows.map((row) => row.join(' | ')).join('\n') }] } as LexicalProjectionNode];
+    return [{ ...metadata, type: 'table', header: node.header ?? [], rows: node.rows, formula: node.formula ?? null, sourceRaw: node.raw, children: [{ text: tableRows(node).map((row) => row.join(' | ')).join('\n') }] } as LexicalProjectionNode];
   }
   if (node.type === 'directive') {
     return [{ ...metadata, type: 'directive', keyword: node.keyword, sourceRaw: node.raw, children: [{ text: node.text }] } as LexicalProjectionNode];
@@ -484,11 +495,14 @@ function getDisplayText(node: LexicalNode, options: ConversionOptions): string {
     return `${node.name}: ${node.text}`;
   }
   if (node.type === 'table') {
-    return node.rows.map((row) => row.join(' | ')).join('\n');
+    return tableRows(node).map((row) => row.join(' | ')).join('\n');
   }
   if (node.type === 'code_block') {
     return node.text;
   }
+  if (node.type === 'verse') {
+    return node.lines.join('\n');
+  }
   if (node.type === 'directive') {
     return `#+${node.keyword}: ${node.text}`;
   }
@@ -1,4 +1,4 @@
-import type { OrgBridgeConfig } from "@postep/bridge";
+import type { DocumentSort, OrgBridgeConfig } from "@postep/bridge";
 
 const joinRoots = (roots?: string[]) => roots?.join(":") ?? "";
 
@@ -6,20 +6,27 @@ export function hasConfiguredOrgRoots(config: OrgBridgeConfig): boolean {
   return config.roots.length > 0 || (config.roamRoots?.length ?? 0) > 0;
 }
 
-export function documentsQueryKey(config: OrgBridgeConfig) {
-  return ["documents", joinRoots(config.roots), joinRoots(config.roamRoots)] as const;
+export function documentsQueryKey(
+  config: OrgBridgeConfig,
+  order: DocumentSort = "path",
+) {
+  return order === "path"
+    ? (["documents", joinRoots(config.roots), joinRoots(config.roamRoots)] as const)
+    : (["documents", joinRoots(config.roots), joinRoots(config.roamRoots), order] as const);
 }
 
 export function documentPreviewsQueryKey(
   config: OrgBridgeConfig,
   documentPaths?: string[],
+  order: DocumentSort = "path",
 ) {
-  return [
+  const key = [
     "document-previews",
     documentPaths?.join(":") ?? "",
     joinRoots(config.roots),
     joinRoots(config.roamRoots),
   ] as const;
+  return order === "path" ? key : ([...key, order] as const);
 }
 
 export function documentQueryKey(config: OrgBridgeConfig, path: string) {
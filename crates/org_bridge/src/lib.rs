@@ -1,16 +1,26 @@
 use anyhow::{Context, Result};
+use chrono::Local;
 use napi::{bindgen_prelude::AsyncTask, Env, JsUnknown, Task};
 use napi_derive::napi;
 use once_cell::sync::Lazy;
-use org_core::{service::AgendaSnapshot, OrgService};
+use org_core::{
+    agenda::AgendaFilter,
+    agenda_days::DaySpan,
+    expand_capture_template, parse_and_validate_timestamp,
+    service::{AgendaSnapshot, DocumentSort},
+    OrgError, OrgService,
+};
 use org_roam::build_roam_graph;
 use org_sync::{OrgSyncService, StorageBackend, SyncRoot};
 use parking_lot::RwLock;
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Days of history `load_habits` computes completion stats over.
+const HABIT_STATS_WINDOW_DAYS: usize = 28;
+
 #[napi(object)]
 #[derive(Clone, Debug)]
 pub struct OrgBridgeConfig {
@@ -116,6 +126,8 @@ pub struct CompleteAgendaParams {
     pub roam_roots: Option<Vec<String>>,
     pub path: String,
     pub headline_line: u32,
+    /// Done state to move to, such as `CANCELLED`; the file's first done state when absent.
+    pub done_keyword: Option<String>,
 }
 
 #[napi(object)]
@@ -125,6 +137,7 @@ pub struct CaptureRequest {
     pub roam_roots: Option<Vec<String>>,
     pub target_path: String,
     pub content: String,
+    pub target_headline: Option<String>,
 }
 
 #[napi(object)]
@@ -154,11 +167,47 @@ pub struct UpdateDocumentParams {
     pub raw: String,
 }
 
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct AppendNoteParams {
+    pub roots: Vec<String>,
+    pub roam_roots: Option<Vec<String>>,
+    pub path: String,
+    pub headline_line: u32,
+    pub content: String,
+}
+
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct RecomputeStatisticsParams {
+    pub roots: Vec<String>,
+    pub roam_roots: Option<Vec<String>>,
+    pub path: String,
+    pub headline_line: u32,
+    pub recursive: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Clone, Debug)]
+pub struct AgendaFilterParams {
+    pub roots: Vec<String>,
+    pub roam_roots: Option<Vec<String>>,
+    pub tags_any: Option<Vec<String>>,
+    pub tags_all: Option<Vec<String>>,
+    pub keywords: Option<Vec<String>>,
+    pub exclude_done: Option<bool>,
+}
+
 #[napi]
 pub fn ping() -> String {
     "postep-org-bridge".to_owned()
 }
 
+#[napi]
+pub fn validate_timestamp(text: String) -> serde_json::Value {
+    validate_timestamp_impl(&text)
+}
+
 #[napi]
 pub fn load_agenda_snapshot(config: OrgBridgeConfig) -> napi::Result<serde_json::Value> {
     load_agenda_snapshot_impl(config).map_err(to_napi_error)
@@ -169,6 +218,26 @@ pub fn load_agenda_snapshot_async(config: OrgBridgeConfig) -> AsyncTask<LoadAgen
     AsyncTask::new(LoadAgendaSnapshotTask { config })
 }
 
+#[napi]
+pub fn load_agenda_filtered(params: AgendaFilterParams) -> napi::Result<serde_json::Value> {
+    load_agenda_filtered_impl(params).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn load_agenda_filtered_async(params: AgendaFilterParams) -> AsyncTask<LoadAgendaFilteredTask> {
+    AsyncTask::new(LoadAgendaFilteredTask { params })
+}
+
+#[napi]
+pub fn load_habits(config: OrgBridgeConfig) -> napi::Result<serde_json::Value> {
+    load_habits_impl(config).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn load_habits_async(config: OrgBridgeConfig) -> AsyncTask<LoadHabitsTask> {
+    AsyncTask::new(LoadHabitsTask { config })
+}
+
 #[napi]
 pub fn complete_agenda_item(params: CompleteAgendaParams) -> napi::Result<serde_json::Value> {
     complete_agenda_item_impl(params).map_err(to_napi_error)
@@ -209,6 +278,19 @@ pub fn list_documents_async(config: OrgBridgeConfig) -> AsyncTask<ListDocumentsT
     AsyncTask::new(ListDocumentsTask { config })
 }
 
+#[napi]
+pub fn list_documents_sorted(config: OrgBridgeConfig, order: String) -> napi::Result<Vec<String>> {
+    list_documents_sorted_impl(config, &order).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn list_documents_sorted_async(
+    config: OrgBridgeConfig,
+    order: String,
+) -> AsyncTask<ListDocumentsSortedTask> {
+    AsyncTask::new(ListDocumentsSortedTask { config, order })
+}
+
 #[napi]
 pub fn load_document(config: OrgBridgeConfig, path: String) -> napi::Result<OrgDocumentPayload> {
     load_document_impl(config, path).map_err(to_napi_error)
@@ -249,6 +331,129 @@ pub fn set_agenda_status_async(params: SetAgendaStatusParams) -> AsyncTask<SetAg
     AsyncTask::new(SetAgendaStatusTask { params })
 }
 
+#[napi]
+pub fn headlines_matching(
+    config: OrgBridgeConfig,
+    query: String,
+) -> napi::Result<serde_json::Value> {
+    headlines_matching_impl(config, query).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn headlines_matching_async(
+    config: OrgBridgeConfig,
+    query: String,
+) -> AsyncTask<HeadlinesMatchingTask> {
+    AsyncTask::new(HeadlinesMatchingTask { config, query })
+}
+
+#[napi]
+pub fn week_agenda(config: OrgBridgeConfig) -> napi::Result<serde_json::Value> {
+    week_agenda_impl(config).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn week_agenda_async(config: OrgBridgeConfig) -> AsyncTask<WeekAgendaTask> {
+    AsyncTask::new(WeekAgendaTask { config })
+}
+
+#[napi]
+pub fn load_backlinks(config: OrgBridgeConfig, node_id: String) -> napi::Result<serde_json::Value> {
+    load_backlinks_impl(config, node_id).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn load_backlinks_async(
+    config: OrgBridgeConfig,
+    node_id: String,
+) -> AsyncTask<LoadBacklinksTask> {
+    AsyncTask::new(LoadBacklinksTask { config, node_id })
+}
+
+#[napi]
+pub fn search_documents(config: OrgBridgeConfig, query: String) -> napi::Result<serde_json::Value> {
+    search_documents_impl(config, query).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn search_documents_async(
+    config: OrgBridgeConfig,
+    query: String,
+) -> AsyncTask<SearchDocumentsTask> {
+    AsyncTask::new(SearchDocumentsTask { config, query })
+}
+
+#[napi]
+pub fn create_document(
+    config: OrgBridgeConfig,
+    relative_path: String,
+    content: String,
+) -> napi::Result<String> {
+    create_document_impl(config, relative_path, content).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn create_document_async(
+    config: OrgBridgeConfig,
+    relative_path: String,
+    content: String,
+) -> AsyncTask<CreateDocumentTask> {
+    AsyncTask::new(CreateDocumentTask {
+        config,
+        relative_path,
+        content,
+    })
+}
+
+#[napi]
+pub fn delete_document(config: OrgBridgeConfig, path: String) -> napi::Result<()> {
+    delete_document_impl(config, path).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn delete_document_async(
+    config: OrgBridgeConfig,
+    path: String,
+) -> AsyncTask<DeleteDocumentTask> {
+    AsyncTask::new(DeleteDocumentTask { config, path })
+}
+
+#[napi]
+pub fn rename_document(config: OrgBridgeConfig, from: String, to: String) -> napi::Result<()> {
+    rename_document_impl(config, from, to).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn rename_document_async(
+    config: OrgBridgeConfig,
+    from: String,
+    to: String,
+) -> AsyncTask<RenameDocumentTask> {
+    AsyncTask::new(RenameDocumentTask { config, from, to })
+}
+
+#[napi]
+pub fn append_note(params: AppendNoteParams) -> napi::Result<OrgDocumentPayload> {
+    append_note_impl(params).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn append_note_async(params: AppendNoteParams) -> AsyncTask<AppendNoteTask> {
+    AsyncTask::new(AppendNoteTask { params })
+}
+
+#[napi]
+pub fn recompute_statistics(params: RecomputeStatisticsParams) -> napi::Result<OrgDocumentPayload> {
+    recompute_statistics_impl(params).map_err(to_napi_error)
+}
+
+#[napi]
+pub fn recompute_statistics_async(
+    params: RecomputeStatisticsParams,
+) -> AsyncTask<RecomputeStatisticsTask> {
+    AsyncTask::new(RecomputeStatisticsTask { params })
+}
+
 pub struct LoadAgendaSnapshotTask {
     config: OrgBridgeConfig,
 }
@@ -266,6 +471,40 @@ impl Task for LoadAgendaSnapshotTask {
     }
 }
 
+pub struct LoadAgendaFilteredTask {
+    params: AgendaFilterParams,
+}
+
+impl Task for LoadAgendaFilteredTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        load_agenda_filtered_impl(self.params.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
+pub struct LoadHabitsTask {
+    config: OrgBridgeConfig,
+}
+
+impl Task for LoadHabitsTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        load_habits_impl(self.config.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
 pub struct CompleteAgendaItemTask {
     params: CompleteAgendaParams,
 }
@@ -334,6 +573,24 @@ impl Task for ListDocumentsTask {
     }
 }
 
+pub struct ListDocumentsSortedTask {
+    config: OrgBridgeConfig,
+    order: String,
+}
+
+impl Task for ListDocumentsSortedTask {
+    type Output = Vec<String>;
+    type JsValue = Vec<String>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        list_documents_sorted_impl(self.config.clone(), &self.order).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
 pub struct LoadDocumentTask {
     config: OrgBridgeConfig,
     path: String,
@@ -403,6 +660,173 @@ impl Task for SetAgendaStatusTask {
     }
 }
 
+pub struct HeadlinesMatchingTask {
+    config: OrgBridgeConfig,
+    query: String,
+}
+
+impl Task for HeadlinesMatchingTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        headlines_matching_impl(self.config.clone(), self.query.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
+pub struct WeekAgendaTask {
+    config: OrgBridgeConfig,
+}
+
+impl Task for WeekAgendaTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        week_agenda_impl(self.config.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
+pub struct LoadBacklinksTask {
+    config: OrgBridgeConfig,
+    node_id: String,
+}
+
+impl Task for LoadBacklinksTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        load_backlinks_impl(self.config.clone(), self.node_id.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
+pub struct SearchDocumentsTask {
+    config: OrgBridgeConfig,
+    query: String,
+}
+
+impl Task for SearchDocumentsTask {
+    type Output = serde_json::Value;
+    type JsValue = JsUnknown;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        search_documents_impl(self.config.clone(), self.query.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        env.to_js_value(&output)
+    }
+}
+
+pub struct CreateDocumentTask {
+    config: OrgBridgeConfig,
+    relative_path: String,
+    content: String,
+}
+
+impl Task for CreateDocumentTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        create_document_impl(
+            self.config.clone(),
+            self.relative_path.clone(),
+            self.content.clone(),
+        )
+        .map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+pub struct DeleteDocumentTask {
+    config: OrgBridgeConfig,
+    path: String,
+}
+
+impl Task for DeleteDocumentTask {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        delete_document_impl(self.config.clone(), self.path.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, _output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(())
+    }
+}
+
+pub struct RenameDocumentTask {
+    config: OrgBridgeConfig,
+    from: String,
+    to: String,
+}
+
+impl Task for RenameDocumentTask {
+    type Output = ();
+    type JsValue = ();
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        rename_document_impl(self.config.clone(), self.from.clone(), self.to.clone())
+            .map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, _output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(())
+    }
+}
+
+pub struct AppendNoteTask {
+    params: AppendNoteParams,
+}
+
+impl Task for AppendNoteTask {
+    type Output = OrgDocumentPayload;
+    type JsValue = OrgDocumentPayload;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        append_note_impl(self.params.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+pub struct RecomputeStatisticsTask {
+    params: RecomputeStatisticsParams,
+}
+
+impl Task for RecomputeStatisticsTask {
+    type Output = OrgDocumentPayload;
+    type JsValue = OrgDocumentPayload;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        recompute_statistics_impl(self.params.clone()).map_err(to_napi_error)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
 fn load_agenda_snapshot_impl(config: OrgBridgeConfig) -> Result<serde_json::Value> {
     let roam_roots = extract_roam_roots(&config.roam_roots);
     ensure_roots_registered(&config.roots, &roam_roots)?;
@@ -415,17 +839,66 @@ fn load_agenda_snapshot_impl(config: OrgBridgeConfig) -> Result<serde_json::Valu
     Ok(snapshot_to_json(&snapshot))
 }
 
+fn load_agenda_filtered_impl(params: AgendaFilterParams) -> Result<serde_json::Value> {
+    let AgendaFilterParams {
+        roots,
+        roam_roots,
+        tags_any,
+        tags_all,
+        keywords,
+        exclude_done,
+    } = params;
+    let roam_vec = roam_roots.unwrap_or_default();
+    ensure_roots_registered(&roots, &roam_vec)?;
+    let service = build_service(&roots, &roam_vec)?;
+
+    let filter = AgendaFilter {
+        tags_any: tags_any.unwrap_or_default(),
+        tags_all: tags_all.unwrap_or_default(),
+        keywords: keywords.unwrap_or_default(),
+        exclude_done: exclude_done.unwrap_or(false),
+    };
+    let snapshot = service
+        .agenda_filtered(&filter)
+        .context("failed to load filtered agenda")?;
+
+    Ok(snapshot_to_json(&snapshot))
+}
+
+/// Each habit with a `stats` object covering the last `HABIT_STATS_WINDOW_DAYS` days.
+fn load_habits_impl(config: OrgBridgeConfig) -> Result<serde_json::Value> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+
+    let habits = service.habits().context("failed to load habits")?;
+    let today = Local::now().date_naive();
+    let mut payload = Vec::with_capacity(habits.len());
+    for habit in habits {
+        let mut value = serde_json::to_value(&habit)?;
+        if let Some(object) = value.as_object_mut() {
+            object.insert(
+                "stats".to_string(),
+                serde_json::to_value(habit.stats(today, HABIT_STATS_WINDOW_DAYS))?,
+            );
+        }
+        payload.push(value);
+    }
+    Ok(serde_json::Value::Array(payload))
+}
+
 fn complete_agenda_item_impl(params: CompleteAgendaParams) -> Result<serde_json::Value> {
     let CompleteAgendaParams {
         roots,
         roam_roots,
         path,
         headline_line,
+        done_keyword,
     } = params;
     let roam_vec = roam_roots.clone().unwrap_or_default();
     ensure_roots_registered(&roots, &roam_vec)?;
     let service = build_service(&roots, &roam_vec)?;
-    service.complete_headline(&path, headline_line as usize)?;
+    service.complete_headline_as(&path, headline_line as usize, done_keyword.as_deref())?;
     let snapshot = service
         .agenda_snapshot()
         .context("failed to refresh agenda snapshot")?;
@@ -438,17 +911,38 @@ fn append_capture_entry_impl(request: CaptureRequest) -> Result<serde_json::Valu
         roam_roots,
         target_path,
         content,
+        target_headline,
     } = request;
     let roam_vec = roam_roots.clone().unwrap_or_default();
     ensure_roots_registered(&roots, &roam_vec)?;
     let service = build_service(&roots, &roam_vec)?;
-    service.append_to_document(&target_path, &content)?;
+    let content = expand_capture_template(&content, Local::now());
+    match target_headline.as_deref().map(str::trim) {
+        Some(headline) if !headline.is_empty() => {
+            service.append_under_headline(Path::new(&target_path), headline, &content)?
+        }
+        _ => service.append_to_document(&target_path, &content)?,
+    }
     let snapshot = service
         .agenda_snapshot()
         .context("failed to refresh agenda snapshot")?;
     Ok(snapshot_to_json(&snapshot))
 }
 
+fn validate_timestamp_impl(text: &str) -> serde_json::Value {
+    match parse_and_validate_timestamp(text) {
+        Ok(timestamp) => json!({
+            "valid": true,
+            "timestamp": timestamp,
+        }),
+        Err(err) => json!({
+            "valid": false,
+            "kind": err.kind(),
+            "error": err.to_string(),
+        }),
+    }
+}
+
 fn load_roam_graph_impl(config: OrgBridgeConfig) -> Result<serde_json::Value> {
     let roam_roots = extract_roam_roots(&config.roam_roots);
     ensure_roots_registered(&config.roots, &roam_roots)?;
@@ -460,11 +954,34 @@ fn load_roam_graph_impl(config: OrgBridgeConfig) -> Result<serde_json::Value> {
     }))
 }
 
+fn load_backlinks_impl(config: OrgBridgeConfig, node_id: String) -> Result<serde_json::Value> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    let graph = build_roam_graph(&service)?;
+    Ok(serde_json::to_value(graph.backlinks_for(&node_id))?)
+}
+
 fn list_documents_impl(config: OrgBridgeConfig) -> Result<Vec<String>> {
+    list_documents_sorted_impl(config, "path")
+}
+
+fn list_documents_sorted_impl(config: OrgBridgeConfig, order: &str) -> Result<Vec<String>> {
+    let order = match order {
+        "path" => DocumentSort::Path,
+        "modified_desc" => DocumentSort::ModifiedDesc,
+        "modified_asc" => DocumentSort::ModifiedAsc,
+        "title_asc" => DocumentSort::TitleAsc,
+        other => {
+            return Err(
+                OrgError::InvalidInput(format!("unknown document sort order: {}", other)).into(),
+            )
+        }
+    };
     let roam_roots = extract_roam_roots(&config.roam_roots);
     ensure_roots_registered(&config.roots, &roam_roots)?;
     let service = build_service(&config.roots, &roam_roots)?;
-    let docs = service.list_documents();
+    let docs = service.list_documents_sorted(order);
     Ok(docs
         .into_iter()
         .map(|path| path.display().to_string())
@@ -512,6 +1029,67 @@ fn update_document_impl(params: UpdateDocumentParams) -> Result<OrgDocumentPaylo
     })
 }
 
+fn append_note_impl(params: AppendNoteParams) -> Result<OrgDocumentPayload> {
+    let AppendNoteParams {
+        roots,
+        roam_roots,
+        path,
+        headline_line,
+        content,
+    } = params;
+    let roam_vec = roam_roots.clone().unwrap_or_default();
+    ensure_roots_registered(&roots, &roam_vec)?;
+    let service = build_service(&roots, &roam_vec)?;
+    service
+        .append_to_headline_body(Path::new(&path), headline_line as usize, &content)
+        .with_context(|| format!("failed to append note to {}:{}", path, headline_line))?;
+    let doc = service
+        .get_document(&path)
+        .with_context(|| format!("document not loaded after update: {}", path))?;
+    let lexical = service.lexical_nodes(&path)?;
+    let lexical_json = serde_json::to_value(lexical)?;
+    Ok(OrgDocumentPayload {
+        path,
+        raw: doc.raw().to_string(),
+        lexical: lexical_json,
+    })
+}
+
+fn recompute_statistics_impl(params: RecomputeStatisticsParams) -> Result<OrgDocumentPayload> {
+    let RecomputeStatisticsParams {
+        roots,
+        roam_roots,
+        path,
+        headline_line,
+        recursive,
+    } = params;
+    let roam_vec = roam_roots.clone().unwrap_or_default();
+    ensure_roots_registered(&roots, &roam_vec)?;
+    let service = build_service(&roots, &roam_vec)?;
+    service
+        .recompute_statistics(
+            Path::new(&path),
+            headline_line as usize,
+            recursive.unwrap_or(false),
+        )
+        .with_context(|| {
+            format!(
+                "failed to recompute statistics in {}:{}",
+                path, headline_line
+            )
+        })?;
+    let doc = service
+        .get_document(&path)
+        .with_context(|| format!("document not loaded after update: {}", path))?;
+    let lexical = service.lexical_nodes(&path)?;
+    let lexical_json = serde_json::to_value(lexical)?;
+    Ok(OrgDocumentPayload {
+        path,
+        raw: doc.raw().to_string(),
+        lexical: lexical_json,
+    })
+}
+
 fn set_roots_impl(config: OrgBridgeConfig) -> Result<()> {
     let roam_roots = extract_roam_roots(&config.roam_roots);
     ensure_roots_registered(&config.roots, &roam_roots)?;
@@ -537,6 +1115,62 @@ fn set_agenda_status_impl(params: SetAgendaStatusParams) -> Result<serde_json::V
     Ok(snapshot_to_json(&snapshot))
 }
 
+fn headlines_matching_impl(config: OrgBridgeConfig, query: String) -> Result<serde_json::Value> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    Ok(serde_json::to_value(service.headlines_matching(&query))?)
+}
+
+fn search_documents_impl(config: OrgBridgeConfig, query: String) -> Result<serde_json::Value> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    Ok(serde_json::to_value(service.search(&query))?)
+}
+
+fn create_document_impl(
+    config: OrgBridgeConfig,
+    relative_path: String,
+    content: String,
+) -> Result<String> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    let path = service
+        .create_document(Path::new(&relative_path), &content)
+        .with_context(|| format!("failed to create {}", relative_path))?;
+    Ok(path.display().to_string())
+}
+
+fn delete_document_impl(config: OrgBridgeConfig, path: String) -> Result<()> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    service
+        .delete_document(Path::new(&path))
+        .with_context(|| format!("failed to delete document: {}", path))
+}
+
+fn rename_document_impl(config: OrgBridgeConfig, from: String, to: String) -> Result<()> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    service
+        .rename_document(Path::new(&from), Path::new(&to))
+        .with_context(|| format!("failed to rename {} to {}", from, to))
+}
+
+fn week_agenda_impl(config: OrgBridgeConfig) -> Result<serde_json::Value> {
+    let roam_roots = extract_roam_roots(&config.roam_roots);
+    ensure_roots_registered(&config.roots, &roam_roots)?;
+    let service = build_service(&config.roots, &roam_roots)?;
+    let groups = service
+        .agenda_days(&DaySpan::current_week())
+        .context("failed to load week agenda")?;
+    Ok(serde_json::to_value(groups)?)
+}
+
 fn build_service(roots: &[String], roam_roots: &[String]) -> Result<Arc<OrgService>> {
     let key = ServiceKey::new(roots, roam_roots);
     if let Some(service) = SERVICE_CACHE.read().get(&key) {
@@ -566,8 +1200,71 @@ fn invalidate_service_cache(roots: &[String], roam_roots: &[String]) {
     SERVICE_CACHE.write().remove(&key);
 }
 
+/// Failure categories JavaScript can branch on. napi errors only carry a fixed `Status`, so
+/// the code leads the message as `[NotFound] document not loaded: ...`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BridgeErrorCode {
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    InvalidInput,
+    Io,
+    Parse,
+    Internal,
+}
+
+impl BridgeErrorCode {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFound => "NotFound",
+            Self::AlreadyExists => "AlreadyExists",
+            Self::PermissionDenied => "PermissionDenied",
+            Self::InvalidInput => "InvalidInput",
+            Self::Io => "Io",
+            Self::Parse => "Parse",
+            Self::Internal => "Internal",
+        }
+    }
+
+    /// Classifies by the first domain, I/O, or parse error in the cause chain. Anything else
+    /// is a failure the bridge did not anticipate.
+    fn classify(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(org) = cause.downcast_ref::<OrgError>() {
+                return match org {
+                    OrgError::NotFound(_) => Self::NotFound,
+                    OrgError::AlreadyExists(_) => Self::AlreadyExists,
+                    OrgError::InvalidInput(_) => Self::InvalidInput,
+                    OrgError::Internal(_) => Self::Internal,
+                };
+            }
+            if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+                return match io.kind() {
+                    std::io::ErrorKind::NotFound => Self::NotFound,
+                    std::io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+                    std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+                    std::io::ErrorKind::InvalidInput => Self::InvalidInput,
+                    std::io::ErrorKind::InvalidData => Self::Parse,
+                    _ => Self::Io,
+                };
+            }
+            if cause.is::<serde_json::Error>()
+                || cause.is::<chrono::ParseError>()
+                || cause.is::<std::num::ParseIntError>()
+            {
+                return Self::Parse;
+            }
+        }
+        Self::Internal
+    }
+}
+
 fn to_napi_error(err: anyhow::Error) -> napi::Error {
-    napi::Error::new(napi::Status::GenericFailure, err.to_string())
+    let code = BridgeErrorCode::classify(&err);
+    napi::Error::new(
+        napi::Status::GenericFailure,
+        format!("[{}] {:#}", code.as_str(), err),
+    )
 }
 
 fn snapshot_to_json(snapshot: &AgendaSnapshot) -> serde_json::Value {
@@ -576,3 +1273,305 @@ fn snapshot_to_json(snapshot: &AgendaSnapshot) -> serde_json::Value {
         "habits": snapshot.habits,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn week_agenda_returns_seven_day_groups() {
+        let value = week_agenda_impl(OrgBridgeConfig {
+            roots: Vec::new(),
+            roam_roots: None,
+        })
+        .expect("week agenda");
+        assert_eq!(value.as_array().map(Vec::len), Some(7));
+    }
+
+    #[test]
+    fn week_agenda_expands_repeaters_onto_their_dates() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let today = Local::now().date_naive();
+        let day = |offset: u64| today.checked_add_days(chrono::Days::new(offset)).unwrap();
+        std::fs::write(
+            temp.path().join("week.org"),
+            format!(
+                "* TODO Water plants\nSCHEDULED: <{} +2d>\n* TODO Renew passport\nSCHEDULED: <{} +999999999y>\n",
+                day(0).format("%Y-%m-%d"),
+                day(1).format("%Y-%m-%d"),
+            ),
+        )
+        .expect("write");
+
+        let value = week_agenda_impl(OrgBridgeConfig {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+        })
+        .expect("week agenda");
+        let groups = value.as_array().expect("groups");
+        if groups[0]["date"] != json!(day(0).to_string()) {
+            // The local date changed while the test ran; the file no longer starts today.
+            return;
+        }
+        let dates_of = |title: &str| -> Vec<String> {
+            groups
+                .iter()
+                .filter(|group| {
+                    group["items"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .any(|item| item["title"] == title)
+                })
+                .map(|group| group["date"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            dates_of("Water plants"),
+            [0, 2, 4, 6].map(|offset| day(offset).to_string())
+        );
+        assert_eq!(dates_of("Renew passport"), [day(1).to_string()]);
+    }
+
+    #[test]
+    fn validate_timestamp_reports_the_failure_kind() {
+        let valid = validate_timestamp_impl("<2025-03-14 Fri 09:30 +1w>");
+        assert_eq!(valid["valid"], json!(true));
+        assert_eq!(valid["timestamp"]["date"], json!("2025-03-14"));
+
+        let invalid = validate_timestamp_impl("<2025-03-14 Fri +1x>");
+        assert_eq!(invalid["valid"], json!(false));
+        assert_eq!(invalid["kind"], json!("bad_repeater"));
+    }
+
+    #[test]
+    fn load_backlinks_returns_empty_array_for_unknown_node() {
+        let value = load_backlinks_impl(
+            OrgBridgeConfig {
+                roots: Vec::new(),
+                roam_roots: None,
+            },
+            "missing-node".to_string(),
+        )
+        .expect("backlinks");
+        assert_eq!(value, json!([]));
+    }
+
+    #[test]
+    fn capture_expands_template_placeholders_before_appending() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("inbox.org");
+        let expected = || {
+            let today = Local::now().format("<%Y-%m-%d %a>");
+            format!("* TODO Call back\nSCHEDULED: {today}\n")
+        };
+
+        let before = expected();
+        append_capture_entry_impl(CaptureRequest {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+            target_path: path.to_string_lossy().into_owned(),
+            content: "* TODO %?Call back\nSCHEDULED: %t".to_string(),
+            target_headline: None,
+        })
+        .expect("capture");
+        let after = expected();
+
+        // The capture may straddle midnight, so either day's timestamp is correct.
+        let saved = std::fs::read_to_string(&path).expect("read");
+        assert!(saved == before || saved == after, "{saved:?}");
+    }
+
+    #[test]
+    fn capture_files_under_the_target_headline() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("inbox.org");
+        std::fs::write(&path, "* Inbox\n** TODO Old\n* Later\n").expect("write");
+
+        append_capture_entry_impl(CaptureRequest {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+            target_path: path.to_string_lossy().into_owned(),
+            content: "** TODO New".to_string(),
+            target_headline: Some("Inbox".to_string()),
+        })
+        .expect("capture");
+
+        let saved = std::fs::read_to_string(&path).expect("read");
+        assert_eq!(saved, "* Inbox\n** TODO Old\n** TODO New\n* Later\n");
+    }
+
+    #[test]
+    fn append_note_adds_text_under_headline_and_returns_document() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("tasks.org");
+        std::fs::write(&path, "* TODO First\n** Sub\n* TODO Second\n").expect("write");
+        let path = path.to_string_lossy().into_owned();
+
+        let payload = append_note_impl(AppendNoteParams {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+            path: path.clone(),
+            headline_line: 0,
+            content: "Called back".to_string(),
+        })
+        .expect("append note");
+
+        let expected = "* TODO First\nCalled back\n** Sub\n* TODO Second\n";
+        assert_eq!(payload.raw, expected);
+        assert_eq!(std::fs::read_to_string(&path).expect("read"), expected);
+        assert_eq!(payload.lexical[1]["type"], "paragraph");
+        assert_eq!(payload.lexical[1]["text"], "Called back");
+    }
+
+    #[test]
+    fn load_agenda_filtered_keeps_matching_items() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            temp.path().join("tasks.org"),
+            "* TODO Call plumber :home:\n* TODO Draft slides :work:\n* DONE Pay rent :home:\n",
+        )
+        .expect("write");
+
+        let value = load_agenda_filtered_impl(AgendaFilterParams {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+            tags_any: Some(vec!["home".to_string()]),
+            tags_all: None,
+            keywords: None,
+            exclude_done: Some(true),
+        })
+        .expect("filtered agenda");
+
+        let titles: Vec<&str> = value["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .filter_map(|item| item["title"].as_str())
+            .collect();
+        assert_eq!(titles, vec!["Call plumber"]);
+    }
+
+    #[test]
+    fn load_habits_includes_stats() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            temp.path().join("habits.org"),
+            "* TODO Stretch\nSCHEDULED: <2025-11-10 Mon .+1d>\n:PROPERTIES:\n:STYLE: habit\n:END:\n* TODO Errand\n",
+        )
+        .expect("write");
+
+        let value = load_habits_impl(OrgBridgeConfig {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+        })
+        .expect("habits");
+
+        let habits = value.as_array().expect("habit list");
+        assert_eq!(habits.len(), 1);
+        assert!(habits[0]["title"]
+            .as_str()
+            .is_some_and(|title| title.ends_with("Stretch")));
+        assert!(habits[0]["repeater"].is_object());
+        assert_eq!(habits[0]["stats"]["done_count"], 0);
+    }
+
+    #[test]
+    fn build_service_reuses_cached_service_for_equivalent_roots() {
+        let crate_dir = env!("CARGO_MANIFEST_DIR").to_string();
+        let src_dir = format!("{}/src", crate_dir);
+        let roots = vec![src_dir.clone(), crate_dir.clone(), src_dir.clone()];
+        let reordered = vec![crate_dir, src_dir];
+        assert_eq!(
+            ServiceKey::new(&roots, &[]),
+            ServiceKey::new(&reordered, &[])
+        );
+
+        let first = build_service(&roots, &[]).expect("first build");
+        let second = build_service(&reordered, &[]).expect("cached build");
+        assert!(Arc::ptr_eq(&first, &second));
+
+        invalidate_service_cache(&reordered, &[]);
+        let rebuilt = build_service(&roots, &[]).expect("rebuild");
+        assert!(!Arc::ptr_eq(&first, &rebuilt));
+    }
+
+    #[test]
+    fn errors_carry_a_category_code() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let missing = temp.path().join("missing.org");
+        let err = load_document_impl(
+            OrgBridgeConfig {
+                roots: vec![temp.path().to_string_lossy().to_string()],
+                roam_roots: None,
+            },
+            missing.to_string_lossy().to_string(),
+        )
+        .expect_err("missing document");
+        assert!(to_napi_error(err).reason.starts_with("[NotFound] "));
+
+        let io = anyhow::Error::new(std::io::Error::other("disk full")).context("writing");
+        assert_eq!(BridgeErrorCode::classify(&io), BridgeErrorCode::Io);
+        assert_eq!(to_napi_error(io).reason, "[Io] writing: disk full");
+
+        let parse = anyhow::Error::new(serde_json::from_str::<u32>("x").unwrap_err());
+        assert_eq!(BridgeErrorCode::classify(&parse), BridgeErrorCode::Parse);
+
+        let typed = anyhow::Error::new(OrgError::AlreadyExists("taken".to_string()))
+            .context("failed to create notes.org");
+        assert_eq!(
+            BridgeErrorCode::classify(&typed),
+            BridgeErrorCode::AlreadyExists
+        );
+        // Wording alone no longer decides the code.
+        let untyped = anyhow::anyhow!("document not loaded");
+        assert_eq!(
+            BridgeErrorCode::classify(&untyped),
+            BridgeErrorCode::Internal
+        );
+    }
+
+    #[test]
+    fn recompute_statistics_returns_document_with_updated_cookie() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("trip.org");
+        std::fs::write(&path, "* Trip [0/0]\n- [X] Flights\n- [ ] Hotel\n").expect("write");
+        let path = path.to_string_lossy().into_owned();
+
+        let payload = recompute_statistics_impl(RecomputeStatisticsParams {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+            path,
+            headline_line: 0,
+            recursive: None,
+        })
+        .expect("recompute statistics");
+
+        assert!(payload.raw.starts_with("* Trip [1/2]\n"));
+        assert_eq!(payload.lexical[0]["type"], "heading");
+        assert!(payload.lexical[0]["text"]
+            .as_str()
+            .is_some_and(|text| text.contains("[1/2]")));
+    }
+
+    #[test]
+    fn list_documents_sorted_accepts_known_orders_only() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        std::fs::write(temp.path().join("a.org"), "#+TITLE: Zebra\n").expect("write");
+        std::fs::write(temp.path().join("b.org"), "#+TITLE: Apple\n").expect("write");
+        let config = OrgBridgeConfig {
+            roots: vec![temp.path().to_string_lossy().into_owned()],
+            roam_roots: None,
+        };
+
+        let by_title = list_documents_sorted_impl(config.clone(), "title_asc").expect("sorted");
+        assert!(by_title[0].ends_with("b.org"));
+        assert!(by_title[1].ends_with("a.org"));
+
+        let err = list_documents_sorted_impl(config, "newest").expect_err("unknown order");
+        assert_eq!(
+            BridgeErrorCode::classify(&err),
+            BridgeErrorCode::InvalidInput
+        );
+    }
+}
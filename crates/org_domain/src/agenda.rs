@@ -1,13 +1,21 @@
-use chrono::{NaiveDate, NaiveTime};
+use chrono::{Days, Months, NaiveDate, NaiveDateTime, NaiveTime};
+use orgize::indextree::NodeId;
 use serde::{Deserialize, Serialize};
-use std::{cmp::Ordering, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
-use crate::document::OrgDocument;
+use crate::{document::OrgDocument, lexical};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum AgendaKind {
     Scheduled,
     Deadline,
+    /// A plain active timestamp in the headline's body, with no planning keyword.
+    Appointment,
     Floating,
 }
 
@@ -25,6 +33,60 @@ pub struct Repeater {
     pub unit: RepeaterUnit,
 }
 
+/// An org warning period such as `-2d`; `--2d` applies to the first occurrence only.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Warning {
+    pub amount: u32,
+    pub unit: RepeaterUnit,
+    pub first_only: bool,
+}
+
+impl Warning {
+    /// The first day of the warning period ending at `date`.
+    pub fn window_start(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self.unit {
+            RepeaterUnit::Day => date.checked_sub_days(Days::new(self.amount.into())),
+            RepeaterUnit::Week => date.checked_sub_days(Days::new(u64::from(self.amount) * 7)),
+            RepeaterUnit::Month => date.checked_sub_months(Months::new(self.amount)),
+            RepeaterUnit::Year => {
+                date.checked_sub_months(Months::new(self.amount.checked_mul(12)?))
+            }
+        }
+    }
+}
+
+impl Repeater {
+    /// Returns the next occurrence after `date`, or `None` if it would overflow the calendar.
+    pub fn advance_once(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self.unit {
+            RepeaterUnit::Day => date.checked_add_days(Days::new(self.amount.into())),
+            RepeaterUnit::Week => date.checked_add_days(Days::new(u64::from(self.amount) * 7)),
+            RepeaterUnit::Month => date.checked_add_months(Months::new(self.amount)),
+            RepeaterUnit::Year => {
+                date.checked_add_months(Months::new(self.amount.checked_mul(12)?))
+            }
+        }
+    }
+}
+
+/// One `SCHEDULED:` or `DEADLINE:` timestamp from a headline's planning line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlannedTimestamp {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+    pub raw: String,
+    pub repeater: Option<Repeater>,
+    pub warning: Option<Warning>,
+}
+
+/// Everything a headline's planning line says, shared by each agenda item for the headline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PlanningInfo {
+    pub scheduled: Option<PlannedTimestamp>,
+    pub deadline: Option<PlannedTimestamp>,
+    pub closed: Option<NaiveDateTime>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgendaItem {
     pub title: String,
@@ -34,9 +96,90 @@ pub struct AgendaItem {
     pub path: PathBuf,
     pub headline_line: usize,
     pub todo_keyword: Option<String>,
+    /// Whether `todo_keyword` is one of the file's done states, such as DONE or CANCELLED.
+    #[serde(default)]
+    pub done: bool,
     pub kind: AgendaKind,
     pub timestamp_raw: Option<String>,
     pub repeater: Option<Repeater>,
+    #[serde(default)]
+    pub warning: Option<Warning>,
+    /// When the headline was completed, from its `CLOSED:` planning entry.
+    #[serde(default)]
+    pub closed_at: Option<NaiveDateTime>,
+    /// Effective tags: the headline's own, its ancestors', and the file's `#+FILETAGS`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// The nearest `:CATEGORY:` property, else the file's `#+CATEGORY:`, else the file stem.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Minutes logged by the headline's closed `CLOCK:` entries.
+    #[serde(default)]
+    pub clocked_minutes: u64,
+    /// The headline's full planning, whatever this item's `kind`; `None` without any.
+    #[serde(default)]
+    pub planning: Option<PlanningInfo>,
+}
+
+impl AgendaItem {
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// The first non-empty line of `context` outside drawers and planning lines, cut to
+    /// `max_chars` characters (including a trailing `…` when shortened).
+    pub fn context_preview(&self, max_chars: usize) -> String {
+        let mut in_drawer = false;
+        let line = self.context.lines().map(str::trim).find(|line| {
+            if in_drawer {
+                in_drawer = !line.eq_ignore_ascii_case(":END:");
+                return false;
+            }
+            if line.len() > 2 && line.starts_with(':') && line.ends_with(':') {
+                in_drawer = !line.eq_ignore_ascii_case(":END:");
+                return false;
+            }
+            !line.is_empty() && !is_planning_line(line)
+        });
+        let Some(line) = line else {
+            return String::new();
+        };
+        if line.chars().count() <= max_chars {
+            return line.to_string();
+        }
+        let kept: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{}…", kept.trim_end())
+    }
+}
+
+/// Predicates for narrowing an agenda. Empty lists place no constraint.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AgendaFilter {
+    /// Keep items carrying at least one of these tags.
+    #[serde(default)]
+    pub tags_any: Vec<String>,
+    /// Keep items carrying every one of these tags.
+    #[serde(default)]
+    pub tags_all: Vec<String>,
+    /// Keep items whose TODO keyword is one of these.
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub exclude_done: bool,
+}
+
+impl AgendaFilter {
+    pub fn matches(&self, item: &AgendaItem) -> bool {
+        let has_tag = |tag: &String| item.tags.contains(tag);
+        (self.tags_any.is_empty() || self.tags_any.iter().any(has_tag))
+            && self.tags_all.iter().all(has_tag)
+            && (self.keywords.is_empty()
+                || item
+                    .todo_keyword
+                    .as_ref()
+                    .is_some_and(|keyword| self.keywords.contains(keyword)))
+            && !(self.exclude_done && item.is_done())
+    }
 }
 
 impl PartialEq for AgendaItem {
@@ -50,6 +193,9 @@ impl PartialEq for AgendaItem {
             && self.kind == other.kind
             && self.timestamp_raw == other.timestamp_raw
             && self.repeater == other.repeater
+            && self.warning == other.warning
+            && self.closed_at == other.closed_at
+            && self.tags == other.tags
     }
 }
 
@@ -81,54 +227,151 @@ pub fn build_agenda(documents: &[(PathBuf, OrgDocument)]) -> Vec<AgendaItem> {
         let path = path.clone();
         let mut state = HeadingState::default();
         let mut in_drawer = false;
+        let file_tags = parse_file_tags(doc.raw());
+        let keywords = TodoKeywords::from_document(doc.raw());
+        let mut file_category = parse_file_category(doc.raw()).or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        });
+        let mut ancestors: Vec<(usize, Vec<String>, Option<String>)> = Vec::new();
 
         for (idx, line) in doc.raw().lines().enumerate() {
-            let trimmed = line.trim();
-
-            if trimmed.eq_ignore_ascii_case(":PROPERTIES:")
-                || trimmed.eq_ignore_ascii_case(":LOGBOOK:")
-            {
-                in_drawer = true;
-                continue;
-            }
-
-            if trimmed.eq_ignore_ascii_case(":END:") && in_drawer {
-                in_drawer = false;
-                continue;
-            }
-
-            if line.starts_with('*') {
+            if let Some(depth) = headline_depth(line) {
                 state.emit(&path, &mut items);
                 in_drawer = false;
-                let (todo, title) = parse_headline(line);
+                let (todo, title, own_tags) = parse_headline(line, &keywords);
+                while ancestors
+                    .last()
+                    .is_some_and(|(level, _, _)| *level >= depth)
+                {
+                    ancestors.pop();
+                }
+                let mut tags = file_tags.clone();
+                for tag in ancestors
+                    .iter()
+                    .flat_map(|(_, tags, _)| tags)
+                    .chain(own_tags.iter())
+                {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
+                    }
+                }
+                let category = ancestors
+                    .last()
+                    .and_then(|(_, _, category)| category.clone())
+                    .or_else(|| file_category.clone());
+                ancestors.push((depth, own_tags, category.clone()));
                 state.line_index = idx;
+                state.done = todo.as_deref().is_some_and(|todo| keywords.is_done(todo));
                 state.todo_keyword = todo;
                 state.title = Some(title);
+                state.tags = tags;
+                state.category = category;
                 continue;
             }
 
-            if in_drawer {
-                continue;
+            if let Some(category) = state.absorb(line, &mut in_drawer) {
+                // Children inherit the headline's category; a drawer above the first headline
+                // sets the file's.
+                match ancestors.last_mut() {
+                    Some((_, _, inherited)) => *inherited = Some(category),
+                    None => file_category = Some(category),
+                }
             }
+        }
 
-            if trimmed.starts_with("SCHEDULED:") {
-                if let Some(info) = parse_timestamp_from_line(trimmed) {
-                    state.schedule = Some(info);
-                }
-                continue;
+        state.emit(&path, &mut items);
+    }
+
+    items.sort();
+    items
+}
+
+/// The agenda [`build_agenda`] produces, with headline structure taken from the orgize parse
+/// tree: nesting, TODO keywords (the document's own `#+TODO:` set), priority, title, and tags.
+/// orgize 0.9 rejects timestamps carrying a repeater or warning cookie and then drops the
+/// headline's planning line and property drawer with it, so each section's planning, drawers,
+/// and clock lines still go through the same line handling `build_agenda` uses. orgize keeps no
+/// source positions either; headlines are matched to their lines in document order.
+pub fn build_agenda_parsed(documents: &[(PathBuf, OrgDocument)]) -> Vec<AgendaItem> {
+    let mut items = Vec::new();
+
+    for (path, doc) in documents {
+        let keywords = TodoKeywords::from_document(doc.raw());
+        let org = doc.parsed();
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let headline_lines: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| headline_depth(line).is_some())
+            .map(|(idx, _)| idx)
+            .collect();
+        let file_tags = parse_file_tags(doc.raw());
+
+        let mut preamble = HeadingState::default();
+        let mut in_drawer = false;
+        let mut file_category = None;
+        for line in &lines[..headline_lines.first().copied().unwrap_or(lines.len())] {
+            file_category = preamble.absorb(line, &mut in_drawer).or(file_category);
+        }
+        let file_category = file_category
+            .or_else(|| parse_file_category(doc.raw()))
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            });
+
+        // Each headline's effective category, for its descendants to inherit.
+        let mut categories: HashMap<NodeId, Option<String>> = HashMap::new();
+        for (position, (headline, &line_index)) in org.headlines().zip(&headline_lines).enumerate()
+        {
+            let title = headline.title(&org);
+            let mut ancestors = Vec::new();
+            let mut cursor = headline.parent(&org);
+            while let Some(ancestor) = cursor {
+                ancestors.push(ancestor);
+                cursor = ancestor.parent(&org);
             }
 
-            if trimmed.starts_with("DEADLINE:") {
-                if let Some(info) = parse_timestamp_from_line(trimmed) {
-                    state.deadline = Some(info);
+            let mut tags = file_tags.clone();
+            for tag in ancestors
+                .iter()
+                .rev()
+                .flat_map(|ancestor| &ancestor.title(&org).tags)
+                .chain(&title.tags)
+            {
+                if !tags.iter().any(|known| known == tag) {
+                    tags.push(tag.to_string());
                 }
-                continue;
             }
+            let keyword = title.keyword.as_deref();
+            let mut state = HeadingState {
+                title: Some(match title.priority {
+                    Some(priority) => format!("[#{priority}] {}", title.raw),
+                    None => title.raw.to_string(),
+                }),
+                todo_keyword: keyword.map(str::to_string),
+                done: keyword.is_some_and(|keyword| keywords.is_done(keyword)),
+                line_index,
+                tags,
+                category: ancestors
+                    .first()
+                    .and_then(|parent| categories.get(&parent.headline_node()).cloned())
+                    .unwrap_or_else(|| file_category.clone()),
+                ..HeadingState::default()
+            };
 
-            state.lines.push(line.to_string());
+            let section_end = headline_lines
+                .get(position + 1)
+                .copied()
+                .unwrap_or(lines.len());
+            let mut in_drawer = false;
+            for line in &lines[line_index + 1..section_end] {
+                state.absorb(line, &mut in_drawer);
+            }
+            categories.insert(headline.headline_node(), state.category.clone());
+            state.emit(path, &mut items);
         }
-
-        state.emit(&path, &mut items);
     }
 
     items.sort();
@@ -141,21 +384,89 @@ struct TimestampInfo {
     time: Option<NaiveTime>,
     raw: Option<String>,
     repeater: Option<Repeater>,
+    warning: Option<Warning>,
 }
 
 #[derive(Debug, Default)]
 struct HeadingState {
     title: Option<String>,
     todo_keyword: Option<String>,
+    done: bool,
     line_index: usize,
     lines: Vec<String>,
     schedule: Option<TimestampInfo>,
     deadline: Option<TimestampInfo>,
+    appointments: Vec<TimestampInfo>,
+    closed_at: Option<NaiveDateTime>,
+    tags: Vec<String>,
+    category: Option<String>,
+    clocked_minutes: u64,
+    /// Set by `:STYLE: habit`; habits are listed by `habit::extract_habits` instead.
+    is_habit: bool,
 }
 
 impl HeadingState {
-    fn emit(&mut self, path: &PathBuf, out: &mut Vec<AgendaItem>) {
-        let Some(title_owned) = self.title.take() else {
+    /// Folds one line of the heading's section in: drawer markers, clock entries, drawer
+    /// properties, and planning timestamps, with anything else kept as context. Returns the
+    /// value of a `:CATEGORY:` drawer line, which also becomes the heading's category.
+    fn absorb(&mut self, line: &str, in_drawer: &mut bool) -> Option<String> {
+        let trimmed = line.trim();
+
+        if trimmed.eq_ignore_ascii_case(":PROPERTIES:") || trimmed.eq_ignore_ascii_case(":LOGBOOK:")
+        {
+            *in_drawer = true;
+            return None;
+        }
+
+        if trimmed.eq_ignore_ascii_case(":END:") && *in_drawer {
+            *in_drawer = false;
+            return None;
+        }
+
+        if let Some(minutes) = clock_minutes(trimmed) {
+            self.clocked_minutes += minutes;
+            return None;
+        }
+
+        if *in_drawer {
+            if is_habit_style(trimmed) {
+                self.is_habit = true;
+            }
+            let category = category_property(trimmed);
+            if category.is_some() {
+                self.category = category.clone();
+            }
+            return category;
+        }
+
+        if is_planning_line(trimmed) {
+            for (keyword, segment) in planning_entries(trimmed) {
+                let Some(info) = parse_timestamp(segment) else {
+                    continue;
+                };
+                // Only active timestamps schedule an item; CLOSED uses an inactive one.
+                let active = segment.starts_with('<');
+                match keyword {
+                    "SCHEDULED:" if active => self.schedule = Some(info),
+                    "DEADLINE:" if active => self.deadline = Some(info),
+                    "SCHEDULED:" | "DEADLINE:" => {}
+                    _ => {
+                        self.closed_at = info
+                            .date
+                            .map(|date| date.and_time(info.time.unwrap_or_default()))
+                    }
+                }
+            }
+            return None;
+        }
+
+        self.appointments.extend(active_timestamps(line));
+        self.lines.push(line.to_string());
+        None
+    }
+
+    fn emit(&mut self, path: &Path, out: &mut Vec<AgendaItem>) {
+        let Some(title) = self.title.take().filter(|_| !self.is_habit) else {
             self.reset();
             return;
         };
@@ -167,56 +478,63 @@ impl HeadingState {
             .cloned()
             .collect::<Vec<_>>()
             .join("\n");
-        let todo_keyword = self.todo_keyword.clone();
-        let line_idx = self.line_index;
-
-        let mut emitted = false;
-
-        if let Some(info) = self.schedule.take() {
-            out.push(AgendaItem {
-                title: title_owned.clone(),
-                date: info.date,
+        let planned = |info: &Option<TimestampInfo>| {
+            let info = info.as_ref()?;
+            Some(PlannedTimestamp {
+                date: info.date?,
                 time: info.time,
-                context: context.clone(),
-                path: path.clone(),
-                headline_line: line_idx,
-                todo_keyword: todo_keyword.clone(),
-                kind: AgendaKind::Scheduled,
-                timestamp_raw: info.raw.clone(),
+                raw: info.raw.clone().unwrap_or_default(),
                 repeater: info.repeater,
+                warning: info.warning,
+            })
+        };
+        let planning = PlanningInfo {
+            scheduled: planned(&self.schedule),
+            deadline: planned(&self.deadline),
+            closed: self.closed_at,
+        };
+        let planning = (planning != PlanningInfo::default()).then_some(planning);
+        let item = |kind: AgendaKind, info: Option<TimestampInfo>| {
+            let info = info.unwrap_or(TimestampInfo {
+                date: None,
+                time: None,
+                raw: None,
+                repeater: None,
+                warning: None,
             });
-            emitted = true;
-        }
-
-        if let Some(info) = self.deadline.take() {
-            out.push(AgendaItem {
-                title: title_owned.clone(),
+            AgendaItem {
+                title: title.clone(),
                 date: info.date,
                 time: info.time,
                 context: context.clone(),
-                path: path.clone(),
-                headline_line: line_idx,
-                todo_keyword: todo_keyword.clone(),
-                kind: AgendaKind::Deadline,
-                timestamp_raw: info.raw.clone(),
+                path: path.to_path_buf(),
+                headline_line: self.line_index,
+                todo_keyword: self.todo_keyword.clone(),
+                done: self.done,
+                kind,
+                timestamp_raw: info.raw,
                 repeater: info.repeater,
-            });
-            emitted = true;
-        }
+                warning: info.warning,
+                closed_at: self.closed_at,
+                tags: self.tags.clone(),
+                category: self.category.clone(),
+                clocked_minutes: self.clocked_minutes,
+                planning: planning.clone(),
+            }
+        };
 
-        if !emitted {
-            out.push(AgendaItem {
-                title: title_owned,
-                date: None,
-                time: None,
-                context,
-                path: path.clone(),
-                headline_line: line_idx,
-                todo_keyword,
-                kind: AgendaKind::Floating,
-                timestamp_raw: None,
-                repeater: None,
-            });
+        let before = out.len();
+        if let Some(info) = self.schedule.clone() {
+            out.push(item(AgendaKind::Scheduled, Some(info)));
+        }
+        if let Some(info) = self.deadline.clone() {
+            out.push(item(AgendaKind::Deadline, Some(info)));
+        }
+        for info in &self.appointments {
+            out.push(item(AgendaKind::Appointment, Some(info.clone())));
+        }
+        if out.len() == before {
+            out.push(item(AgendaKind::Floating, None));
         }
 
         self.reset();
@@ -225,39 +543,413 @@ impl HeadingState {
     fn reset(&mut self) {
         self.title = None;
         self.todo_keyword = None;
+        self.done = false;
         self.line_index = 0;
         self.lines.clear();
         self.schedule = None;
         self.deadline = None;
+        self.appointments.clear();
+        self.closed_at = None;
+        self.tags.clear();
+        self.category = None;
+        self.clocked_minutes = 0;
+        self.is_habit = false;
+    }
+}
+
+/// Whether a property drawer line is `:STYLE: habit`.
+fn is_habit_style(trimmed: &str) -> bool {
+    trimmed
+        .strip_prefix(':')
+        .and_then(|rest| rest.split_once(':'))
+        .is_some_and(|(key, value)| {
+            key.trim().eq_ignore_ascii_case("STYLE") && value.trim().eq_ignore_ascii_case("habit")
+        })
+}
+
+/// Duration of a closed `CLOCK: [start]--[end] => H:MM` line in minutes, taken from the
+/// `=>` total when present and otherwise from the two timestamps. Running clocks give `None`.
+fn clock_minutes(trimmed: &str) -> Option<u64> {
+    let rest = trimmed.strip_prefix("CLOCK:")?;
+    let (range, total) = match rest.split_once("=>") {
+        Some((range, total)) => (range, Some(total.trim())),
+        None => (rest, None),
+    };
+    if let Some((hours, minutes)) = total.and_then(|total| total.split_once(':')) {
+        let hours: u64 = hours.trim().parse().ok()?;
+        let minutes: u64 = minutes.trim().parse().ok()?;
+        return Some(hours * 60 + minutes);
+    }
+    let (start, end) = range.split_once("--")?;
+    let stamp = |text: &str| {
+        let info = parse_timestamp(text)?;
+        Some(info.date?.and_time(info.time?))
+    };
+    let minutes = (stamp(end)? - stamp(start)?).num_minutes();
+    u64::try_from(minutes).ok()
+}
+
+/// The value of a `:CATEGORY:` property drawer line.
+fn category_property(trimmed: &str) -> Option<String> {
+    let (key, value) = trimmed.strip_prefix(':')?.split_once(':')?;
+    let value = value.trim();
+    (key.trim().eq_ignore_ascii_case("CATEGORY") && !value.is_empty()).then(|| value.to_string())
+}
+
+/// Splits a leading `[#X]` priority cookie off `text`, returning the priority and the text
+/// after the cookie.
+pub(crate) fn split_priority_cookie(text: &str) -> Option<(char, &str)> {
+    let tail = text.strip_prefix("[#")?;
+    let priority = tail.chars().next()?;
+    let rest = tail[priority.len_utf8()..].strip_prefix(']')?;
+    Some((priority, rest))
+}
+
+/// Returns the number of leading stars when `line` is an org headline: stars followed by a
+/// space or the end of the line. This is the rule orgize parses headlines by, so line numbers
+/// found with it line up with `Org::headlines`.
+pub(crate) fn headline_depth(line: &str) -> Option<usize> {
+    let stars = line.chars().take_while(|c| *c == '*').count();
+    let rest = &line[stars..];
+    (stars > 0 && (rest.is_empty() || rest.starts_with(' '))).then_some(stars)
+}
+
+/// Returns the line range covered by the subtree whose headline sits at `headline_line`: the
+/// headline itself through the line before the next headline of the same or shallower depth.
+pub(crate) fn subtree_range(lines: &[&str], headline_line: usize) -> Option<Range<usize>> {
+    let depth = headline_depth(lines.get(headline_line)?)?;
+    let end = lines
+        .iter()
+        .enumerate()
+        .skip(headline_line + 1)
+        .find(|(_, line)| headline_depth(line).is_some_and(|other| other <= depth))
+        .map(|(idx, _)| idx)
+        .unwrap_or(lines.len());
+    Some(headline_line..end)
+}
+
+/// The TODO keywords a document declares with `#+TODO:`, `#+SEQ_TODO:`, or `#+TYP_TODO:`
+/// lines, split into active and done states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoKeywords {
+    pub active: Vec<String>,
+    pub done: Vec<String>,
+}
+
+impl Default for TodoKeywords {
+    fn default() -> Self {
+        let words = |list: &[&str]| list.iter().map(|word| word.to_string()).collect();
+        Self {
+            active: words(&["TODO", "NEXT", "WAITING", "HOLD", "STARTED"]),
+            done: words(&["DONE", "CANCELLED", "CANCELED"]),
+        }
+    }
+}
+
+impl TodoKeywords {
+    /// Reads every keyword line in `raw`, falling back to the default set when there are none.
+    /// As in Emacs, a sequence without `|` treats its last keyword as the done state, and
+    /// fast-access keys such as `TODO(t)` are dropped.
+    pub fn from_document(raw: &str) -> Self {
+        let mut keywords = Self {
+            active: Vec::new(),
+            done: Vec::new(),
+        };
+        for line in raw.lines() {
+            let trimmed = line.trim();
+            let Some(value) = ["#+TODO:", "#+SEQ_TODO:", "#+TYP_TODO:"]
+                .iter()
+                .find(|prefix| {
+                    trimmed
+                        .get(..prefix.len())
+                        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+                })
+                .map(|prefix| &trimmed[prefix.len()..])
+            else {
+                continue;
+            };
+            let words: Vec<&str> = value
+                .split_whitespace()
+                .map(|word| word.split('(').next().unwrap_or(word))
+                .filter(|word| !word.is_empty())
+                .collect();
+            let (active, done) = match words.iter().position(|word| *word == "|") {
+                Some(bar) => (&words[..bar], &words[bar + 1..]),
+                None if words.len() > 1 => words.split_at(words.len() - 1),
+                None => (&words[..], &[][..]),
+            };
+            keywords
+                .active
+                .extend(active.iter().map(|word| word.to_string()));
+            keywords
+                .done
+                .extend(done.iter().map(|word| word.to_string()));
+        }
+        if keywords.active.is_empty() && keywords.done.is_empty() {
+            return Self::default();
+        }
+        keywords
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.active
+            .iter()
+            .chain(&self.done)
+            .any(|known| known == word)
+    }
+
+    pub fn is_done(&self, word: &str) -> bool {
+        self.done.iter().any(|known| known == word)
+    }
+
+    /// The state completing an item moves it to: the first done keyword, or `DONE`.
+    pub fn first_done(&self) -> &str {
+        self.done.first().map(String::as_str).unwrap_or("DONE")
     }
 }
 
-fn parse_headline(line: &str) -> (Option<String>, String) {
+/// Splits a headline into its TODO keyword, title, and trailing tags. The first word only
+/// counts as a keyword when `keywords` declares it.
+pub(crate) fn parse_headline(
+    line: &str,
+    keywords: &TodoKeywords,
+) -> (Option<String>, String, Vec<String>) {
     let content = line.trim_start_matches('*').trim();
     if content.is_empty() {
-        return (None, String::new());
+        return (None, String::new(), Vec::new());
     }
+    let (content, tags) = lexical::parse_tags(content);
 
     let mut parts = content.split_whitespace();
     if let Some(first) = parts.next() {
-        if first.chars().all(|c| c.is_ascii_uppercase()) {
+        if keywords.contains(first) {
             let rest = content[first.len()..].trim_start().to_string();
-            return (Some(first.to_string()), rest);
+            return (Some(first.to_string()), rest, tags);
+        }
+    }
+
+    (None, content, tags)
+}
+
+/// Collects the tags declared by `#+FILETAGS:` lines anywhere in the document.
+pub(crate) fn parse_file_tags(raw: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let trimmed = line.trim();
+        let Some(value) = trimmed
+            .get(..11)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("#+FILETAGS:"))
+            .map(|_| &trimmed[11..])
+        else {
+            continue;
+        };
+        for tag in value.split(|c: char| c == ':' || c.is_whitespace()) {
+            if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+                tags.push(tag.to_string());
+            }
         }
     }
+    tags
+}
+
+/// The value of the first `#+CATEGORY:` line in the document.
+pub(crate) fn parse_file_category(raw: &str) -> Option<String> {
+    raw.lines().find_map(|line| {
+        let trimmed = line.trim();
+        let value = trimmed
+            .get(..11)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("#+CATEGORY:"))
+            .map(|_| trimmed[11..].trim())?;
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+const PLANNING_KEYWORDS: [&str; 3] = ["SCHEDULED:", "DEADLINE:", "CLOSED:"];
+
+pub(crate) fn is_planning_line(trimmed: &str) -> bool {
+    PLANNING_KEYWORDS
+        .iter()
+        .any(|keyword| trimmed.starts_with(keyword))
+}
+
+/// Splits a planning line such as `CLOSED: [..] SCHEDULED: <..>` into each keyword and the
+/// text that follows it up to the next keyword. Keywords only count at the start of the line or
+/// after whitespace, so tab-separated entries split the same way as space-separated ones.
+pub(crate) fn planning_entries(line: &str) -> Vec<(&'static str, &str)> {
+    let mut positions: Vec<(usize, &'static str)> = PLANNING_KEYWORDS
+        .iter()
+        .filter_map(|keyword| {
+            line.match_indices(keyword)
+                .map(|(pos, _)| pos)
+                .find(|pos| {
+                    line[..*pos]
+                        .chars()
+                        .next_back()
+                        .is_none_or(char::is_whitespace)
+                })
+                .map(|pos| (pos, *keyword))
+        })
+        .collect();
+    positions.sort();
+    positions
+        .iter()
+        .enumerate()
+        .map(|(idx, (pos, keyword))| {
+            let end = positions
+                .get(idx + 1)
+                .map(|(next, _)| *next)
+                .unwrap_or(line.len());
+            (*keyword, line[pos + keyword.len()..end].trim())
+        })
+        .collect()
+}
 
-    (None, content.to_string())
+/// Why [`parse_and_validate_timestamp`] rejected its input.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TimestampError {
+    #[error("expected a timestamp like <2025-01-31 Fri> or [2025-01-31 Fri]")]
+    Malformed,
+    #[error("`{0}` is not a valid YYYY-MM-DD date")]
+    BadDate(String),
+    #[error("`{0}` is not a valid HH:MM time or time range")]
+    BadTime(String),
+    #[error("`{0}` is not a valid repeater such as +1w, ++2d, or .+1m")]
+    BadRepeater(String),
+    #[error("`{0}` is not a valid warning period such as -3d or --1w")]
+    BadWarning(String),
+    #[error("unexpected `{0}` in timestamp")]
+    Unexpected(String),
 }
 
-fn parse_timestamp_from_line(line: &str) -> Option<TimestampInfo> {
-    let (_, rest) = line.split_once(':')?;
-    parse_timestamp(rest.trim())
+impl TimestampError {
+    /// Short machine-readable name for the failure, e.g. `bad_date`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TimestampError::Malformed => "malformed",
+            TimestampError::BadDate(_) => "bad_date",
+            TimestampError::BadTime(_) => "bad_time",
+            TimestampError::BadRepeater(_) => "bad_repeater",
+            TimestampError::BadWarning(_) => "bad_warning",
+            TimestampError::Unexpected(_) => "unexpected",
+        }
+    }
 }
 
+/// Strictly parses a single active or inactive timestamp typed by the user. Unlike the lenient
+/// agenda parser, every token must be understood: the date, an optional weekday name, an
+/// optional `HH:MM` time or `HH:MM-HH:MM` range, one repeater, and one warning period.
+pub fn parse_and_validate_timestamp(text: &str) -> Result<PlannedTimestamp, TimestampError> {
+    let text = text.trim();
+    let inner = text
+        .strip_prefix('<')
+        .and_then(|rest| rest.strip_suffix('>'))
+        .or_else(|| text.strip_prefix('[')?.strip_suffix(']'))
+        .filter(|inner| !inner.contains(['<', '>', '[', ']']))
+        .ok_or(TimestampError::Malformed)?;
+
+    let mut tokens = inner.split_whitespace();
+    let date_token = tokens.next().ok_or(TimestampError::Malformed)?;
+    let date = NaiveDate::parse_from_str(date_token, "%Y-%m-%d")
+        .map_err(|_| TimestampError::BadDate(date_token.to_string()))?;
+
+    let mut weekday = None;
+    let mut time = None;
+    let mut repeater = None;
+    let mut warning = None;
+    let unexpected = |token: &str| TimestampError::Unexpected(token.to_string());
+    for token in tokens {
+        if token.chars().all(char::is_alphabetic) {
+            if weekday.is_some() || time.is_some() || repeater.is_some() || warning.is_some() {
+                return Err(unexpected(token));
+            }
+            weekday = Some(token);
+            continue;
+        }
+        if token.starts_with('+') || token.starts_with(".+") {
+            if repeater.is_some() {
+                return Err(unexpected(token));
+            }
+            repeater = Some(
+                parse_repeater(token)
+                    .filter(|_| is_repeater_token(token))
+                    .ok_or_else(|| TimestampError::BadRepeater(token.to_string()))?,
+            );
+        } else if token.starts_with('-') {
+            let dashes = token.chars().take_while(|c| *c == '-').count();
+            if warning.is_some() {
+                return Err(unexpected(token));
+            }
+            warning = Some(
+                parse_warning(token)
+                    .filter(|_| is_interval(&token[dashes..]))
+                    .ok_or_else(|| TimestampError::BadWarning(token.to_string()))?,
+            );
+        } else if token.starts_with(|c: char| c.is_ascii_digit()) {
+            if time.is_some() {
+                return Err(unexpected(token));
+            }
+            time = Some(parse_strict_time_range(token)?);
+        } else {
+            return Err(unexpected(token));
+        }
+    }
+
+    Ok(PlannedTimestamp {
+        date,
+        time,
+        raw: inner.to_string(),
+        repeater,
+        warning,
+    })
+}
+
+/// `+1w`, `++1w`, or `.+1w`, optionally with an org-habit maximum such as `.+2d/3d`.
+fn is_repeater_token(token: &str) -> bool {
+    let rest = token
+        .strip_prefix(".+")
+        .or_else(|| token.strip_prefix("++"))
+        .or_else(|| token.strip_prefix('+'))
+        .unwrap_or(token);
+    match rest.split_once('/') {
+        Some((interval, maximum)) => is_interval(interval) && is_interval(maximum),
+        None => is_interval(rest),
+    }
+}
+
+/// A count followed by one of the `d`, `w`, `m`, or `y` units.
+fn is_interval(text: &str) -> bool {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    digits > 0
+        && text[digits..].len() == 1
+        && matches!(text.as_bytes()[digits], b'd' | b'w' | b'm' | b'y')
+}
+
+/// An `HH:MM` time, or the start of an `HH:MM-HH:MM` range whose end is also checked.
+fn parse_strict_time_range(token: &str) -> Result<NaiveTime, TimestampError> {
+    let bad_time = || TimestampError::BadTime(token.to_string());
+    let parse = |value: &str| NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| bad_time());
+    match token.split_once('-') {
+        Some((start, end)) => {
+            let start = parse(start)?;
+            if parse(end)? < start {
+                return Err(bad_time());
+            }
+            Ok(start)
+        }
+        None => parse(token),
+    }
+}
+
+/// Parses the first active (`<..>`) or inactive (`[..]`) timestamp in `segment`.
 fn parse_timestamp(segment: &str) -> Option<TimestampInfo> {
-    let start = segment.find('<')?;
+    let start = segment.find(['<', '['])?;
+    let close = if segment[start..].starts_with('<') {
+        '>'
+    } else {
+        ']'
+    };
     let tail = &segment[start + 1..];
-    let end = tail.find('>')?;
+    let end = tail.find(close)?;
     let inner = &tail[..end];
 
     let mut parts = inner.split_whitespace();
@@ -267,18 +959,16 @@ fn parse_timestamp(segment: &str) -> Option<TimestampInfo> {
 
     let mut time: Option<NaiveTime> = None;
     let mut repeater: Option<Repeater> = None;
+    let mut warning: Option<Warning> = None;
+    // Tokens after the date may appear in any order; anything unrecognised (such as the
+    // weekday) is skipped.
     for part in parts {
-        if time.is_none() {
-            if let Some(parsed) = parse_time_segment(part) {
-                time = Some(parsed);
-                continue;
-            }
-        }
-        if repeater.is_none() {
-            if let Some(rep) = parse_repeater(part) {
-                repeater = Some(rep);
-                continue;
-            }
+        if part.starts_with('+') || part.starts_with(".+") {
+            repeater = repeater.or_else(|| parse_repeater(part));
+        } else if part.starts_with('-') {
+            warning = warning.or_else(|| parse_warning(part));
+        } else if time.is_none() {
+            time = parse_time_segment(part);
         }
     }
 
@@ -287,9 +977,20 @@ fn parse_timestamp(segment: &str) -> Option<TimestampInfo> {
         time,
         raw: Some(inner.to_string()),
         repeater,
+        warning,
     })
 }
 
+/// Every active `<YYYY-MM-DD ...>` timestamp on a body line. Only the start of a
+/// `<a>--<b>` range counts.
+fn active_timestamps(line: &str) -> Vec<TimestampInfo> {
+    line.match_indices('<')
+        .filter(|(pos, _)| !line[..*pos].ends_with("--"))
+        .filter_map(|(pos, _)| parse_timestamp(&line[pos..]))
+        .filter(|info| info.date.is_some())
+        .collect()
+}
+
 fn parse_time_segment(segment: &str) -> Option<NaiveTime> {
     if !segment.contains(':') {
         return None;
@@ -298,7 +999,7 @@ fn parse_time_segment(segment: &str) -> Option<NaiveTime> {
     NaiveTime::parse_from_str(candidate, "%H:%M").ok()
 }
 
-fn parse_repeater(segment: &str) -> Option<Repeater> {
+pub(crate) fn parse_repeater(segment: &str) -> Option<Repeater> {
     let mut s = segment.trim();
     if s.is_empty() {
         return None;
@@ -315,23 +1016,41 @@ fn parse_repeater(segment: &str) -> Option<Repeater> {
         // skip diary style repeater like /+1w
         s = stripped;
     }
+    let (amount, unit) = parse_amount_and_unit(s)?;
+    Some(Repeater {
+        amount: amount.max(1),
+        unit,
+    })
+}
+
+fn parse_warning(segment: &str) -> Option<Warning> {
+    let s = segment.trim();
+    let dash_count = s.chars().take_while(|c| *c == '-').count();
+    if dash_count == 0 || dash_count > 2 {
+        return None;
+    }
+    let (amount, unit) = parse_amount_and_unit(&s[dash_count..])?;
+    Some(Warning {
+        amount,
+        unit,
+        first_only: dash_count == 2,
+    })
+}
+
+fn parse_amount_and_unit(s: &str) -> Option<(u32, RepeaterUnit)> {
     let digits_len = s.chars().take_while(|c| c.is_ascii_digit()).count();
     if digits_len == 0 {
         return None;
     }
     let amount = s[..digits_len].parse::<u32>().ok()?;
-    let unit_char = s[digits_len..].chars().next()?;
-    let unit = match unit_char {
+    let unit = match s[digits_len..].chars().next()? {
         'd' | 'D' => RepeaterUnit::Day,
         'w' | 'W' => RepeaterUnit::Week,
         'm' | 'M' => RepeaterUnit::Month,
         'y' | 'Y' => RepeaterUnit::Year,
         _ => return None,
     };
-    Some(Repeater {
-        amount: amount.max(1),
-        unit,
-    })
+    Some((amount, unit))
 }
 
 #[cfg(test)]
@@ -340,6 +1059,32 @@ mod tests {
     use crate::document::OrgDocument;
     use std::path::PathBuf;
 
+    /// The agenda from [`build_agenda`], after checking [`build_agenda_parsed`] agrees.
+    fn agenda_from_both_builders(documents: &[(PathBuf, OrgDocument)]) -> Vec<AgendaItem> {
+        let scanned = build_agenda(documents);
+        assert_eq!(
+            serde_json::to_value(build_agenda_parsed(documents)).unwrap(),
+            serde_json::to_value(&scanned).unwrap()
+        );
+        scanned
+    }
+
+    #[test]
+    fn huge_yearly_amounts_have_no_next_date_instead_of_overflowing() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let repeater = Repeater {
+            amount: 999_999_999,
+            unit: RepeaterUnit::Year,
+        };
+        assert_eq!(repeater.advance_once(date), None);
+        let warning = Warning {
+            amount: 999_999_999,
+            unit: RepeaterUnit::Year,
+            first_only: false,
+        };
+        assert_eq!(warning.window_start(date), None);
+    }
+
     #[test]
     fn parses_repeater_information() {
         let raw = r#"
@@ -347,7 +1092,7 @@ mod tests {
 SCHEDULED: <2025-10-24 Fri 06:30 ++1d>
 "#;
         let doc = OrgDocument::from_string("repeat_test.org", raw.to_string());
-        let items = build_agenda(&[(PathBuf::from("repeat_test.org"), doc)]);
+        let items = agenda_from_both_builders(&[(PathBuf::from("repeat_test.org"), doc)]);
         assert_eq!(items.len(), 1);
         let item = &items[0];
         assert_eq!(item.kind, AgendaKind::Scheduled);
@@ -360,6 +1105,179 @@ SCHEDULED: <2025-10-24 Fri 06:30 ++1d>
         assert_eq!(repeater.unit, RepeaterUnit::Day);
     }
 
+    #[test]
+    fn skips_full_and_localized_weekday_names() {
+        let raw = "* TODO Standup\nSCHEDULED: <2025-11-10 Monday 09:00>\n* TODO Retro\nDEADLINE: <2025-11-14 ven. 16:30 -1d>\n";
+        let doc = OrgDocument::from_string("weekdays.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("weekdays.org"), doc)]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].date, NaiveDate::from_ymd_opt(2025, 11, 10));
+        assert_eq!(items[0].time, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(items[1].date, NaiveDate::from_ymd_opt(2025, 11, 14));
+        assert_eq!(items[1].time, NaiveTime::from_hms_opt(16, 30, 0));
+        assert!(items[1].warning.is_some());
+    }
+
+    #[test]
+    fn emits_plain_body_timestamps_as_appointments() {
+        let raw = "* Dentist\nBook for <2025-11-07 Fri 10:00> or <2025-11-12 Wed>.\n:LOGBOOK:\n- Note taken on <2025-11-01 Sat>\n:END:\nLogged [2025-11-02 Sun] and away <2025-11-20 Thu>--<2025-11-22 Sat>.\n* TODO Call back\nSCHEDULED: <2025-11-03 Mon>\nAlso <2025-11-04 Tue>\n";
+        let doc = OrgDocument::from_string("plain.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("plain.org"), doc)]);
+
+        let appointments: Vec<_> = items
+            .iter()
+            .filter(|item| item.kind == AgendaKind::Appointment)
+            .map(|item| (item.title.as_str(), item.date, item.time))
+            .collect();
+        let day = |day: u32| NaiveDate::from_ymd_opt(2025, 11, day);
+        assert_eq!(
+            appointments,
+            vec![
+                ("Call back", day(4), None),
+                ("Dentist", day(7), NaiveTime::from_hms_opt(10, 0, 0)),
+                ("Dentist", day(12), None),
+                ("Dentist", day(20), None),
+            ]
+        );
+        assert!(items
+            .iter()
+            .any(|item| item.title == "Call back" && item.kind == AgendaKind::Scheduled));
+        assert!(items.iter().all(|item| item.kind != AgendaKind::Floating));
+    }
+
+    #[test]
+    fn every_item_carries_the_full_planning() {
+        let raw = "* TODO Report\nCLOSED: [2025-11-01 Sat 08:15] SCHEDULED: <2025-11-03 Mon 09:00 +1w>\nDEADLINE: <2025-11-07 Fri -1d>\n* TODO Someday\n";
+        let doc = OrgDocument::from_string("planning.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("planning.org"), doc)]);
+
+        let expected = PlanningInfo {
+            scheduled: Some(PlannedTimestamp {
+                date: NaiveDate::from_ymd_opt(2025, 11, 3).unwrap(),
+                time: NaiveTime::from_hms_opt(9, 0, 0),
+                raw: "2025-11-03 Mon 09:00 +1w".to_string(),
+                repeater: Some(Repeater {
+                    amount: 1,
+                    unit: RepeaterUnit::Week,
+                }),
+                warning: None,
+            }),
+            deadline: Some(PlannedTimestamp {
+                date: NaiveDate::from_ymd_opt(2025, 11, 7).unwrap(),
+                time: None,
+                raw: "2025-11-07 Fri -1d".to_string(),
+                repeater: None,
+                warning: Some(Warning {
+                    amount: 1,
+                    unit: RepeaterUnit::Day,
+                    first_only: false,
+                }),
+            }),
+            closed: NaiveDate::from_ymd_opt(2025, 11, 1)
+                .and_then(|date| date.and_hms_opt(8, 15, 0)),
+        };
+        let report: Vec<_> = items.iter().filter(|item| item.title == "Report").collect();
+        assert_eq!(report.len(), 2);
+        assert!(report
+            .iter()
+            .all(|item| item.planning.as_ref() == Some(&expected)));
+        let someday = items.iter().find(|item| item.title == "Someday").unwrap();
+        assert_eq!(someday.planning, None);
+    }
+
+    #[test]
+    fn parses_tab_separated_planning_line() {
+        let raw = "* TODO Task\n\t  SCHEDULED:\t<2025-11-03 Mon>\tDEADLINE:\t<2025-11-05 Wed>\n";
+        let doc = OrgDocument::from_string("tabs.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("tabs.org"), doc)]);
+        let date_of = |kind: AgendaKind| {
+            items
+                .iter()
+                .find(|item| item.kind == kind)
+                .and_then(|item| item.date)
+        };
+        assert_eq!(
+            date_of(AgendaKind::Scheduled),
+            NaiveDate::from_ymd_opt(2025, 11, 3)
+        );
+        assert_eq!(
+            date_of(AgendaKind::Deadline),
+            NaiveDate::from_ymd_opt(2025, 11, 5)
+        );
+    }
+
+    #[test]
+    fn reads_every_entry_on_a_combined_planning_line() {
+        let raw = "* DONE Ship release\nCLOSED: [2025-11-04 Tue 17:30] SCHEDULED: <2025-11-03 Mon> DEADLINE: <2025-11-05 Wed>\n* DONE Quick fix\nCLOSED: [2025-11-06 Thu]\n";
+        let doc = OrgDocument::from_string("planning.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("planning.org"), doc)]);
+        let kinds: Vec<(&str, AgendaKind, Option<NaiveDateTime>)> = items
+            .iter()
+            .map(|item| (item.title.as_str(), item.kind.clone(), item.closed_at))
+            .collect();
+        let closed = |day: u32, hour: u32, minute: u32| {
+            NaiveDate::from_ymd_opt(2025, 11, day)
+                .and_then(|date| date.and_hms_opt(hour, minute, 0))
+        };
+        assert_eq!(
+            kinds,
+            vec![
+                ("Quick fix", AgendaKind::Floating, closed(6, 0, 0)),
+                ("Ship release", AgendaKind::Scheduled, closed(4, 17, 30)),
+                ("Ship release", AgendaKind::Deadline, closed(4, 17, 30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_warning_and_repeater_in_any_order() {
+        let raw = r#"
+* TODO Renew passport
+DEADLINE: <2025-11-10 Mon -2d +1w>
+* TODO Pay invoice
+DEADLINE: <2025-11-12 Wed +1m 10:00 --3d>
+"#;
+        let doc = OrgDocument::from_string("warning_test.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("warning_test.org"), doc)]);
+        let renew = items
+            .iter()
+            .find(|item| item.title == "Renew passport")
+            .expect("renew item");
+        assert_eq!(
+            renew.repeater,
+            Some(Repeater {
+                amount: 1,
+                unit: RepeaterUnit::Week
+            })
+        );
+        assert_eq!(
+            renew.warning,
+            Some(Warning {
+                amount: 2,
+                unit: RepeaterUnit::Day,
+                first_only: false
+            })
+        );
+
+        let invoice = items
+            .iter()
+            .find(|item| item.title == "Pay invoice")
+            .expect("invoice item");
+        assert_eq!(
+            invoice.repeater.map(|rep| rep.unit),
+            Some(RepeaterUnit::Month)
+        );
+        assert_eq!(invoice.time, NaiveTime::from_hms_opt(10, 0, 0));
+        assert_eq!(
+            invoice.warning,
+            Some(Warning {
+                amount: 3,
+                unit: RepeaterUnit::Day,
+                first_only: true
+            })
+        );
+    }
+
     #[test]
     fn builds_agenda_with_scheduled_deadline_and_floating_items() {
         let raw = r#"
@@ -372,7 +1290,7 @@ DEADLINE: <2025-10-25 Sat>
 * Read a book
 "#;
         let doc = OrgDocument::from_string("agenda_test.org", raw.to_string());
-        let items = build_agenda(&[(PathBuf::from("agenda_test.org"), doc)]);
+        let items = agenda_from_both_builders(&[(PathBuf::from("agenda_test.org"), doc)]);
         assert_eq!(items.len(), 3);
 
         let scheduled = items
@@ -429,7 +1347,7 @@ Notes line that should appear.
 :END:
 "#;
         let doc = OrgDocument::from_string("drawer_test.org", raw.to_string());
-        let items = build_agenda(&[(PathBuf::from("drawer_test.org"), doc)]);
+        let items = agenda_from_both_builders(&[(PathBuf::from("drawer_test.org"), doc)]);
         assert_eq!(items.len(), 1);
         let item = &items[0];
         assert!(item.context.contains("Notes line"));
@@ -442,4 +1360,223 @@ Notes line that should appear.
             "logbook entries should be omitted"
         );
     }
+
+    #[test]
+    fn context_preview_truncates_the_first_body_line() {
+        let raw = "* TODO Plan trip\n:NOTES:\nhidden drawer text\n:END:\n\nBook flights and hotels for the whole family before prices go up.\n\nSecond paragraph that should not appear.\n";
+        let doc = OrgDocument::from_string("trip.org", raw.to_string());
+        let item = agenda_from_both_builders(&[(PathBuf::from("trip.org"), doc)]).remove(0);
+
+        assert_eq!(item.context_preview(20), "Book flights and ho…");
+        assert_eq!(
+            item.context_preview(200),
+            "Book flights and hotels for the whole family before prices go up."
+        );
+    }
+
+    #[test]
+    fn only_declared_todo_keywords_count_as_keywords() {
+        let raw = "#+TODO: TODO NEXT(n) | DONE CANCELLED\n#+SEQ_TODO: DRAFT REVIEWED\n* API redesign\n* NEXT Ship it\n* REVIEWED Essay\n";
+        let keywords = TodoKeywords::from_document(raw);
+        assert_eq!(keywords.active, ["TODO", "NEXT", "DRAFT"]);
+        assert_eq!(keywords.done, ["DONE", "CANCELLED", "REVIEWED"]);
+        assert_eq!(keywords.first_done(), "DONE");
+
+        let doc = OrgDocument::from_string("keywords.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("keywords.org"), doc)]);
+        let parsed: Vec<(Option<&str>, &str)> = items
+            .iter()
+            .map(|item| (item.todo_keyword.as_deref(), item.title.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [
+                (None, "API redesign"),
+                (Some("REVIEWED"), "Essay"),
+                (Some("NEXT"), "Ship it"),
+            ]
+        );
+
+        let defaults = TodoKeywords::from_document("* FYI note\n");
+        assert_eq!(defaults, TodoKeywords::default());
+        assert!(!defaults.contains("FYI"));
+    }
+
+    #[test]
+    fn inherits_file_and_ancestor_tags() {
+        let raw = r#"#+filetags: :work:
+* Project :client:
+** TODO Draft proposal :writing:
+SCHEDULED: <2025-11-03 Mon>
+** TODO Send invoice
+* TODO Water plants :home:
+"#;
+        let doc = OrgDocument::from_string("tags_test.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("tags_test.org"), doc)]);
+        assert_eq!(items.len(), 4);
+        assert!(items
+            .iter()
+            .all(|item| item.tags.contains(&"work".to_string())));
+
+        let draft = items
+            .iter()
+            .find(|item| item.title == "Draft proposal")
+            .expect("tags stripped from title");
+        assert_eq!(draft.tags, vec!["work", "client", "writing"]);
+        let plants = items
+            .iter()
+            .find(|item| item.title == "Water plants")
+            .expect("sibling item present");
+        assert_eq!(plants.tags, vec!["work", "home"]);
+    }
+
+    #[test]
+    fn resolves_category_from_properties_file_keyword_or_stem() {
+        let raw = r#"#+CATEGORY: Work
+* TODO Draft proposal
+* Errands
+:PROPERTIES:
+:CATEGORY: Home
+:END:
+** TODO Buy milk
+"#;
+        let doc = OrgDocument::from_string("tasks.org", raw.to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("notes/tasks.org"), doc)]);
+        let category_of = |title: &str| {
+            items
+                .iter()
+                .find(|item| item.title == title)
+                .and_then(|item| item.category.clone())
+        };
+        assert_eq!(category_of("Draft proposal").as_deref(), Some("Work"));
+        assert_eq!(category_of("Errands").as_deref(), Some("Home"));
+        assert_eq!(category_of("Buy milk").as_deref(), Some("Home"));
+
+        let plain = OrgDocument::from_string("inbox.org", "* TODO Triage\n".to_string());
+        let items = agenda_from_both_builders(&[(PathBuf::from("notes/inbox.org"), plain)]);
+        assert_eq!(items[0].category.as_deref(), Some("inbox"));
+    }
+
+    #[test]
+    fn parsed_agenda_matches_line_scanner() {
+        let raw = r#"#+TITLE: Parity
+#+FILETAGS: :home:
+#+TODO: PLAN NEXT | SHIPPED
+:PROPERTIES:
+:CATEGORY: chores
+:END:
+* PLAN [#A] Launch :work:
+DEADLINE: <2025-11-14 Fri -2d> SCHEDULED: <2025-11-10 Mon 09:00-10:00 +1w>
+:PROPERTIES:
+:CATEGORY: launch
+:END:
+:LOGBOOK:
+CLOCK: [2025-11-09 Sun 10:00]--[2025-11-09 Sun 11:30] =>  1:30
+:END:
+Write the announcement.
+** SHIPPED Beta :mobile:
+CLOSED: [2025-11-08 Sat 17:45] SCHEDULED: <2025-11-08 Sat>
+Notes on the beta.
+** NEXT Review
+* Water plants
+SCHEDULED: <2025-11-10 Mon .+2d>
+:PROPERTIES:
+:STYLE: habit
+:END:
+* Someday idea
+Just a thought.
+"#;
+        let documents = vec![(
+            PathBuf::from("parity.org"),
+            OrgDocument::from_string("parity.org", raw.to_string()),
+        )];
+
+        let scanned = build_agenda(&documents);
+        let parsed = build_agenda_parsed(&documents);
+        assert_eq!(scanned.len(), 5);
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&scanned).unwrap()
+        );
+    }
+
+    #[test]
+    fn agenda_lines_skip_tab_starred_lines() {
+        let raw = "* TODO One\n*\tnot a headline\n#+begin_src sh\n* TODO Inside\n#+end_src\n* TODO Two\nSCHEDULED: <2025-03-14 Fri>\n";
+        let documents = vec![(
+            PathBuf::from("odd.org"),
+            OrgDocument::from_string("odd.org", raw.to_string()),
+        )];
+
+        let items = agenda_from_both_builders(&documents);
+        let two = items.iter().find(|item| item.title == "Two").unwrap();
+        assert_eq!(two.headline_line, 5);
+        assert_eq!(two.date, NaiveDate::from_ymd_opt(2025, 3, 14));
+        let inside = items.iter().find(|item| item.title == "Inside").unwrap();
+        assert_eq!(inside.headline_line, 3);
+    }
+
+    #[test]
+    fn agenda_keeps_bold_lines_in_context() {
+        let raw = "* TODO Draft\n*Important:* ask legal first.\n* TODO Ship\n*important* note about shipping\n";
+        let documents = vec![(
+            PathBuf::from("bold.org"),
+            OrgDocument::from_string("bold.org", raw.to_string()),
+        )];
+
+        let items = agenda_from_both_builders(&documents);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].context, "*Important:* ask legal first.");
+        assert_eq!(items[1].title, "Ship");
+        assert_eq!(items[1].context, "*important* note about shipping");
+    }
+
+    #[test]
+    fn validates_a_complete_timestamp() {
+        let parsed = parse_and_validate_timestamp("<2025-03-14 Fri 09:30-10:00 .+1w -2d>").unwrap();
+        assert_eq!(parsed.date, NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+        assert_eq!(parsed.time, NaiveTime::from_hms_opt(9, 30, 0));
+        assert_eq!(parsed.raw, "2025-03-14 Fri 09:30-10:00 .+1w -2d");
+        let repeater = parsed.repeater.unwrap();
+        assert_eq!((repeater.amount, repeater.unit), (1, RepeaterUnit::Week));
+        assert!(parsed.warning.is_some());
+
+        let inactive = parse_and_validate_timestamp("[2025-03-14]").unwrap();
+        assert_eq!(inactive.time, None);
+    }
+
+    #[test]
+    fn rejects_impossible_dates_and_times() {
+        assert_eq!(
+            parse_and_validate_timestamp("<2024-02-30 Fri>"),
+            Err(TimestampError::BadDate("2024-02-30".to_string()))
+        );
+        assert_eq!(
+            parse_and_validate_timestamp("<2024-02-20 Tue 25:00>"),
+            Err(TimestampError::BadTime("25:00".to_string()))
+        );
+        assert_eq!(
+            parse_and_validate_timestamp("2024-02-20"),
+            Err(TimestampError::Malformed)
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_repeaters_and_warnings() {
+        let err = parse_and_validate_timestamp("<2024-02-20 Tue +1x>").unwrap_err();
+        assert_eq!(err, TimestampError::BadRepeater("+1x".to_string()));
+        assert_eq!(err.kind(), "bad_repeater");
+        assert_eq!(
+            parse_and_validate_timestamp("<2024-02-20 Tue ---3d>"),
+            Err(TimestampError::BadWarning("---3d".to_string()))
+        );
+        assert_eq!(
+            parse_and_validate_timestamp("<2024-02-20 Tue +1w +2d>"),
+            Err(TimestampError::Unexpected("+2d".to_string()))
+        );
+        assert_eq!(
+            parse_and_validate_timestamp("<2025-03-14 foo bar>"),
+            Err(TimestampError::Unexpected("bar".to_string()))
+        );
+    }
 }
@@ -0,0 +1,271 @@
+use chrono::{Days, Local, NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::agenda::{split_priority_cookie, AgendaItem, AgendaKind};
+
+/// The range of days an agenda view covers, plus the date treated as "today".
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DaySpan {
+    pub start: NaiveDate,
+    pub days: u32,
+    pub today: NaiveDate,
+    /// Keep at most this many items per day, dropping the least important first.
+    #[serde(default)]
+    pub max_items_per_day: Option<usize>,
+    /// Wall-clock time on `today`, reported on today's group for a "now" indicator.
+    #[serde(default)]
+    pub now: Option<NaiveTime>,
+}
+
+impl DaySpan {
+    /// Seven days starting at `today`.
+    pub fn week_from(today: NaiveDate) -> Self {
+        Self::upcoming(today, 7)
+    }
+
+    /// `days` days starting at `today`. Future scheduled items land on their own day rather
+    /// than being previewed on today.
+    pub fn upcoming(today: NaiveDate, days: u32) -> Self {
+        Self {
+            start: today,
+            days,
+            today,
+            max_items_per_day: None,
+            now: None,
+        }
+    }
+
+    /// Caps each day at `limit` items; `DayGroup::truncated` counts the ones left out.
+    pub fn with_max_items_per_day(mut self, limit: usize) -> Self {
+        self.max_items_per_day = Some(limit);
+        self
+    }
+
+    /// Marks `now` as the current time of day on `today`.
+    pub fn with_now(mut self, now: NaiveTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Seven days starting at the local calendar date, with the current local time as `now`.
+    pub fn current_week() -> Self {
+        let now = Local::now().naive_local();
+        Self::week_from(now.date()).with_now(now.time())
+    }
+
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        (0..self.days).filter_map(|offset| self.start.checked_add_days(Days::new(offset.into())))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DayGroup {
+    pub date: NaiveDate,
+    pub items: Vec<AgendaItem>,
+    /// Items hidden by `DaySpan::max_items_per_day`.
+    #[serde(default)]
+    pub truncated: usize,
+    /// Minutes since midnight of `DaySpan::now`, set only on today's group.
+    #[serde(default)]
+    pub now_minutes: Option<u32>,
+}
+
+/// Buckets dated agenda items into one group per day of `span`.
+///
+/// Repeating items appear on every occurrence inside the span. Unfinished TODOs whose date is
+/// before `span.today` carry over onto today, as in org's agenda.
+pub fn group_by_day(items: &[AgendaItem], span: &DaySpan) -> Vec<DayGroup> {
+    let mut groups: Vec<DayGroup> = span
+        .dates()
+        .map(|date| DayGroup {
+            date,
+            items: Vec::new(),
+            truncated: 0,
+            now_minutes: span
+                .now
+                .filter(|_| date == span.today)
+                .map(|now| now.hour() * 60 + now.minute()),
+        })
+        .collect();
+    let Some(last_day) = groups.last().map(|group| group.date) else {
+        return groups;
+    };
+
+    for item in items {
+        if item.kind == AgendaKind::Floating {
+            continue;
+        }
+        let Some(base) = item.date else {
+            continue;
+        };
+        for occurrence in occurrences_between(item, base, span.start, last_day) {
+            if let Some(group) = groups.iter_mut().find(|group| group.date == occurrence) {
+                group.items.push(item.clone());
+            }
+        }
+        if is_overdue(item, span.today) {
+            if let Some(group) = groups.iter_mut().find(|group| group.date == span.today) {
+                if !group.items.contains(item) {
+                    group.items.push(item.clone());
+                }
+            }
+        }
+    }
+
+    for group in &mut groups {
+        if let Some(limit) = span
+            .max_items_per_day
+            .filter(|limit| group.items.len() > *limit)
+        {
+            group
+                .items
+                .sort_by(|a, b| importance(a).cmp(&importance(b)).then_with(|| a.cmp(b)));
+            group.truncated = group.items.len() - limit;
+            group.items.truncate(limit);
+        }
+        group.items.sort();
+    }
+    groups
+}
+
+/// Sort key placing the items most worth showing first: unfinished before done, then by
+/// `[#X]` priority cookie (none counts as `B`), then earliest date and time.
+fn importance(item: &AgendaItem) -> impl Ord {
+    let priority = split_priority_cookie(&item.title)
+        .map(|(priority, _)| priority)
+        .unwrap_or('B');
+    (item.is_done(), priority, item.date, item.time)
+}
+
+/// Whether `item` is an unfinished TODO whose scheduled or deadline date is before `today`.
+/// Such items carry over onto today in the agenda; appointments stay on their own day.
+pub fn is_overdue(item: &AgendaItem, today: NaiveDate) -> bool {
+    matches!(item.kind, AgendaKind::Scheduled | AgendaKind::Deadline)
+        && item.date.is_some_and(|date| date < today)
+        && item.todo_keyword.is_some()
+        && !item.is_done()
+}
+
+/// Enumerates the dates `item` falls on between `from` and `to` (inclusive), starting at `base`.
+pub(crate) fn occurrences_between(
+    item: &AgendaItem,
+    base: NaiveDate,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    let mut current = Some(base);
+    while let Some(date) = current {
+        if date > to {
+            break;
+        }
+        if date >= from {
+            dates.push(date);
+        }
+        current = item
+            .repeater
+            .and_then(|repeater| repeater.advance_once(date));
+    }
+    dates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agenda::build_agenda, document::OrgDocument};
+    use std::path::PathBuf;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    #[test]
+    fn buckets_items_into_days_with_repeats_and_carry_over() {
+        let raw = r#"
+* TODO Late report
+SCHEDULED: <2025-11-01 Sat>
+* DONE Finished earlier
+SCHEDULED: <2025-11-02 Sun>
+* TODO Stretch
+SCHEDULED: <2025-11-10 Mon +2d>
+* TODO Dentist
+DEADLINE: <2025-11-14 Fri>
+* TODO Next month
+SCHEDULED: <2025-12-01 Mon>
+* Someday
+"#;
+        let doc = OrgDocument::from_string("days.org", raw.to_string());
+        let items = build_agenda(&[(PathBuf::from("days.org"), doc)]);
+        let groups = group_by_day(&items, &DaySpan::week_from(date(10)));
+
+        assert_eq!(groups.len(), 7);
+        let titles = |day: u32| -> Vec<&str> {
+            groups
+                .iter()
+                .find(|group| group.date == date(day))
+                .map(|group| group.items.iter().map(|item| item.title.as_str()).collect())
+                .unwrap_or_default()
+        };
+        assert_eq!(titles(10), vec!["Late report", "Stretch"]);
+        assert_eq!(titles(11), Vec::<&str>::new());
+        assert_eq!(titles(12), vec!["Stretch"]);
+        assert_eq!(titles(14), vec!["Stretch", "Dentist"]);
+        assert!(groups
+            .iter()
+            .all(|group| group.items.iter().all(|item| item.title != "Next month")));
+    }
+
+    #[test]
+    fn caps_items_per_day_keeping_the_most_important() {
+        let mut raw = String::new();
+        for index in 0..10 {
+            // Non-ASCII cookies sort after every ASCII priority.
+            let priority = if index % 2 == 0 { "[#A] " } else { "[#é] " };
+            raw.push_str(&format!(
+                "* TODO {}Task {}\nSCHEDULED: <2025-11-10 Mon 0{}:00>\n",
+                priority, index, index
+            ));
+        }
+        let doc = OrgDocument::from_string("busy.org", raw);
+        let items = build_agenda(&[(PathBuf::from("busy.org"), doc)]);
+        let span = DaySpan::week_from(date(10)).with_max_items_per_day(5);
+        let groups = group_by_day(&items, &span);
+
+        assert_eq!(groups[0].items.len(), 5);
+        assert_eq!(groups[0].truncated, 5);
+        assert!(groups[0]
+            .items
+            .iter()
+            .all(|item| item.title.starts_with("[#A]")));
+        assert_eq!(groups[1].truncated, 0);
+    }
+
+    #[test]
+    fn upcoming_window_places_future_scheduled_items_on_their_day() {
+        let raw = "* TODO Dentist\nSCHEDULED: <2025-11-15 Sat>\n";
+        let doc = OrgDocument::from_string("upcoming.org", raw.to_string());
+        let items = build_agenda(&[(PathBuf::from("upcoming.org"), doc)]);
+        let groups = group_by_day(&items, &DaySpan::upcoming(date(10), 14));
+
+        assert_eq!(groups.len(), 14);
+        let days_with_dentist: Vec<NaiveDate> = groups
+            .iter()
+            .filter(|group| group.items.iter().any(|item| item.title == "Dentist"))
+            .map(|group| group.date)
+            .collect();
+        assert_eq!(days_with_dentist, vec![date(15)]);
+    }
+
+    #[test]
+    fn reports_now_minutes_on_today_only() {
+        let span =
+            DaySpan::week_from(date(10)).with_now(NaiveTime::from_hms_opt(14, 35, 20).unwrap());
+        let groups = group_by_day(&[], &span);
+
+        assert_eq!(groups[0].now_minutes, Some(14 * 60 + 35));
+        assert!(groups[1..].iter().all(|group| group.now_minutes.is_none()));
+        assert!(group_by_day(&[], &DaySpan::week_from(date(10)))
+            .iter()
+            .all(|group| group.now_minutes.is_none()));
+    }
+}
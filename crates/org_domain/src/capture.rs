@@ -0,0 +1,61 @@
+use chrono::{DateTime, Local};
+
+/// Expands the org-capture placeholders in `template` using `now`:
+///
+/// - `%t` / `%T`: active timestamp, without / with the time of day
+/// - `%u` / `%U`: inactive timestamp, without / with the time of day
+/// - `%?`: cursor position, removed from the saved entry
+///
+/// Any other `%` sequence is left as written.
+pub fn expand_capture_template(template: &str, now: DateTime<Local>) -> String {
+    let mut expanded = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            expanded.push(ch);
+            continue;
+        }
+        let replacement = match chars.peek() {
+            Some('t') => now.format("<%Y-%m-%d %a>").to_string(),
+            Some('T') => now.format("<%Y-%m-%d %a %H:%M>").to_string(),
+            Some('u') => now.format("[%Y-%m-%d %a]").to_string(),
+            Some('U') => now.format("[%Y-%m-%d %a %H:%M]").to_string(),
+            Some('?') => String::new(),
+            _ => {
+                expanded.push(ch);
+                continue;
+            }
+        };
+        chars.next();
+        expanded.push_str(&replacement);
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn friday_morning() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 3, 14, 9, 5, 0).unwrap()
+    }
+
+    #[test]
+    fn expands_timestamp_placeholders() {
+        let expanded = expand_capture_template(
+            "* TODO %?Call back\nSCHEDULED: %t\n:CREATED: %U\nat %T on %u",
+            friday_morning(),
+        );
+        assert_eq!(
+            expanded,
+            "* TODO Call back\nSCHEDULED: <2025-03-14 Fri>\n:CREATED: [2025-03-14 Fri 09:05]\nat <2025-03-14 Fri 09:05> on [2025-03-14 Fri]"
+        );
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders_alone() {
+        let expanded = expand_capture_template("100% done %x %", friday_morning());
+        assert_eq!(expanded, "100% done %x %");
+    }
+}
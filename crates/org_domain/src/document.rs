@@ -1,35 +1,86 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use orgize::Org;
+use orgize::{Org, ParseConfig};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::agenda::TodoKeywords;
+use crate::lexical::{self, LexicalNode};
+
+/// A `#+begin_src` block and the file it tangles to, if any.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SrcBlock {
+    pub language: Option<String>,
+    pub content: String,
+    pub tangle: Option<String>,
+}
+
+/// The line terminator a document was written with.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The terminator used by most lines of `raw`; ties and single-line text count as LF.
+    pub fn detect(raw: &str) -> Self {
+        let total = raw.matches('\n').count();
+        let crlf = raw.matches("\r\n").count();
+        if crlf > total - crlf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Rewrites every line break in `text` with this terminator.
+    pub fn apply(self, text: &str) -> String {
+        let normalized = text.replace("\r\n", "\n");
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::CrLf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
 
 /// Representation of an Org file on disk. Parsing is performed lazily.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrgDocument {
     path: PathBuf,
     raw: String,
+    #[serde(default)]
+    line_ending: LineEnding,
     #[serde(skip)]
     loaded_at: DateTime<Utc>,
+    #[serde(skip)]
+    modified: Option<SystemTime>,
 }
 
 impl OrgDocument {
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref().to_path_buf();
+        let modified = fs::metadata(&path)?.modified().ok();
         let raw = fs::read_to_string(&path)?;
         Ok(Self {
             path,
+            line_ending: LineEnding::detect(&raw),
             raw,
             loaded_at: Utc::now(),
+            modified,
         })
     }
 
     pub fn from_string(path: impl AsRef<Path>, raw: String) -> Self {
         Self {
             path: path.as_ref().to_path_buf(),
+            line_ending: LineEnding::detect(&raw),
             raw,
             loaded_at: Utc::now(),
+            modified: None,
         }
     }
 
@@ -41,16 +92,243 @@ impl OrgDocument {
         &self.raw
     }
 
+    /// The dominant line ending detected when the document was read.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
     pub fn loaded_at(&self) -> DateTime<Utc> {
         self.loaded_at
     }
 
+    /// Filesystem modification time observed when the document was read from disk.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Whether the file on disk still has the modification time and length this document was
+    /// read at. The length catches rewrites landing within the same mtime tick.
+    pub fn is_current_on_disk(&self) -> bool {
+        let Ok(meta) = fs::metadata(&self.path) else {
+            return false;
+        };
+        self.modified.is_some()
+            && meta.modified().ok() == self.modified
+            && meta.len() == self.raw.len() as u64
+    }
+
+    /// The orgize parse tree, recognising the document's own `#+TODO:` keywords.
     pub fn parsed(&self) -> Org<'_> {
-        Org::parse(&self.raw)
+        let keywords = TodoKeywords::from_document(&self.raw);
+        let config = ParseConfig {
+            todo_keywords: (keywords.active, keywords.done),
+        };
+        Org::parse_custom(&self.raw, &config)
+    }
+
+    /// Source blocks in document order with their `:tangle` targets. Example blocks are
+    /// skipped, as is `:tangle no`.
+    pub fn src_blocks(&self) -> Vec<SrcBlock> {
+        lexical::document_to_lexical(self)
+            .into_iter()
+            .filter_map(|node| {
+                let LexicalNode::CodeBlock {
+                    language,
+                    text,
+                    raw,
+                    ..
+                } = node
+                else {
+                    return None;
+                };
+                let header = raw.lines().map(str::trim).find(|line| {
+                    line.get(..11)
+                        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("#+begin_src"))
+                })?;
+                let mut args = header
+                    .split_whitespace()
+                    .skip_while(|arg| *arg != ":tangle");
+                let tangle = args
+                    .nth(1)
+                    .filter(|target| !target.starts_with(':') && *target != "no")
+                    .map(str::to_string);
+                Some(SrcBlock {
+                    language,
+                    content: text,
+                    tangle,
+                })
+            })
+            .collect()
+    }
+
+    /// A stable identity for the document: its file-level `:ID:` property (one before the
+    /// first headline) when present, otherwise a hash of its path.
+    pub fn canonical_id(&self) -> String {
+        let file_id = self
+            .raw
+            .lines()
+            .take_while(|line| !line.starts_with('*'))
+            .find_map(|line| {
+                let trimmed = line.trim();
+                trimmed
+                    .get(..4)
+                    .filter(|key| key.eq_ignore_ascii_case(":ID:"))
+                    .map(|_| trimmed[4..].trim())
+                    .filter(|id| !id.is_empty())
+            });
+        match file_id {
+            Some(id) => id.to_string(),
+            None => format!(
+                "path-{:016x}",
+                fnv1a(self.path.to_string_lossy().as_bytes())
+            ),
+        }
+    }
+
+    /// FNV-1a hash of the raw contents, for caches that must notice edits.
+    pub fn content_hash(&self) -> u64 {
+        fnv1a(self.raw.as_bytes())
     }
 
+    /// The `#+TITLE:` value. A title split across several `#+TITLE:` lines is joined with
+    /// spaces, as Org's exporter does.
+    pub fn title(&self) -> Option<String> {
+        self.keyword_value("TITLE")
+    }
+
+    /// The `#+SUBTITLE:` value, joined the same way as [`OrgDocument::title`].
+    pub fn subtitle(&self) -> Option<String> {
+        self.keyword_value("SUBTITLE")
+    }
+
+    fn keyword_value(&self, keyword: &str) -> Option<String> {
+        let parts: Vec<&str> = self
+            .raw
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.trim().strip_prefix("#+")?.split_once(':')?;
+                name.eq_ignore_ascii_case(keyword).then(|| value.trim())
+            })
+            .filter(|value| !value.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// Takes on contents that were just written to `path`, along with the file's new
+    /// modification time.
     pub fn replace_raw(&mut self, new_raw: String) {
         self.raw = new_raw;
         self.loaded_at = Utc::now();
+        if let Ok(modified) = fs::metadata(&self.path).and_then(|meta| meta.modified()) {
+            self.modified = Some(modified);
+        }
+    }
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher` its output is fixed across Rust releases.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrite_within_the_same_mtime_is_not_current() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("notes.org");
+        fs::write(&path, "* Draft\n").unwrap();
+        let doc = OrgDocument::load(&path).unwrap();
+        assert!(doc.is_current_on_disk());
+
+        fs::write(&path, "* Draft\nMore.\n").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(doc.modified().unwrap())
+            .unwrap();
+        assert!(!doc.is_current_on_disk());
+    }
+
+    #[test]
+    fn canonical_id_prefers_file_id_over_path_hash() {
+        let with_id = OrgDocument::from_string(
+            "/notes/a.org",
+            "#+TITLE: A\n:PROPERTIES:\n:ID: 7f3c-file\n:END:\n* Heading\n:PROPERTIES:\n:ID: heading\n:END:\n".to_string(),
+        );
+        assert_eq!(with_id.canonical_id(), "7f3c-file");
+
+        let without = OrgDocument::from_string(
+            "/notes/b.org",
+            "* Heading\n:PROPERTIES:\n:ID: heading\n:END:\n".to_string(),
+        );
+        let again = OrgDocument::from_string("/notes/b.org", String::new());
+        let other = OrgDocument::from_string("/notes/c.org", String::new());
+        assert_eq!(without.canonical_id(), again.canonical_id());
+        assert_ne!(without.canonical_id(), other.canonical_id());
+        assert_eq!(
+            OrgDocument::from_string("", String::new()).canonical_id(),
+            "path-cbf29ce484222325"
+        );
+    }
+
+    #[test]
+    fn extracts_src_blocks_with_tangle_targets() {
+        let raw = r#"* Code
+#+begin_src rust :tangle src/main.rs :mkdirp yes
+fn main() {
+    println!("hi");
+}
+#+end_src
+#+BEGIN_EXAMPLE
+not code
+#+END_EXAMPLE
+#+BEGIN_SRC sh :results output
+ls
+#+END_SRC
+"#;
+        let doc = OrgDocument::from_string("tangle.org", raw.to_string());
+        assert_eq!(
+            doc.src_blocks(),
+            vec![
+                SrcBlock {
+                    language: Some("rust".to_string()),
+                    content: "fn main() {\n    println!(\"hi\");\n}".to_string(),
+                    tangle: Some("src/main.rs".to_string()),
+                },
+                SrcBlock {
+                    language: Some("sh".to_string()),
+                    content: "ls".to_string(),
+                    tangle: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn joins_title_lines_and_reads_subtitle() {
+        let doc = OrgDocument::from_string(
+            "talk.org",
+            "#+TITLE: Parsing Org\n#+title: Without Tears\n#+SUBTITLE: A field guide\n* Intro\n"
+                .to_string(),
+        );
+        assert_eq!(doc.title().as_deref(), Some("Parsing Org Without Tears"));
+        assert_eq!(doc.subtitle().as_deref(), Some("A field guide"));
+
+        let bare = OrgDocument::from_string("bare.org", "* Heading\n".to_string());
+        assert_eq!(bare.title(), None);
+        assert_eq!(bare.subtitle(), None);
+    }
+
+    #[test]
+    fn detects_majority_line_ending() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\nb\nc\r\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("single line"), LineEnding::Lf);
+        assert_eq!(LineEnding::CrLf.apply("a\nb\r\nc\n"), "a\r\nb\r\nc\r\n");
     }
 }
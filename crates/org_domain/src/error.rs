@@ -0,0 +1,18 @@
+/// Failures the domain reports itself, by category, so callers such as the bridge can branch
+/// on the variant rather than on the wording of the message. I/O and parse failures keep their
+/// own error types in the `anyhow` chain.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum OrgError {
+    /// A document, headline, or other target that is not loaded or does not exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// A create or rename whose destination is already taken.
+    #[error("{0}")]
+    AlreadyExists(String),
+    /// Arguments the operation cannot act on, such as a path outside the roots.
+    #[error("{0}")]
+    InvalidInput(String),
+    /// A failure inside the service itself rather than in what the caller asked for.
+    #[error("{0}")]
+    Internal(String),
+}
@@ -1,7 +1,7 @@
-use chrono::NaiveDate;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 
-use crate::document::OrgDocument;
+use crate::{agenda, document::OrgDocument};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Habit {
@@ -16,7 +16,18 @@ pub struct Habit {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HabitLogEntry {
     pub date: NaiveDate,
+    /// State the entry moved to, e.g. `DONE` in `- State "DONE" from "TODO"`.
     pub state: String,
+    #[serde(default)]
+    pub from_state: Option<String>,
+}
+
+impl HabitLogEntry {
+    fn is_done(&self, done_states: &[&str]) -> bool {
+        done_states
+            .iter()
+            .any(|done| self.state.eq_ignore_ascii_case(done))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -29,10 +40,179 @@ pub struct HabitRepeater {
 pub enum HabitFrequency {
     Daily(u32),
     Weekly(u32),
+    /// Expected only on the listed weekdays, from a `:HABIT_DAYS:` property such as
+    /// `Mon Wed Fri` or `weekdays`.
+    WeeklyOn(Vec<Weekday>),
     Monthly(u32),
     Yearly(u32),
 }
 
+/// Completion figures for a habit, counted in repeat periods (days for `+1d`, weeks for
+/// `+1w`, and so on) rather than calendar days.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HabitStats {
+    /// Completed periods running back from today; today's period does not break the streak
+    /// while it is still open.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// `done_count / expected_count`, or 0 when nothing was expected.
+    pub completion_rate: f64,
+    /// Periods that fall in the window.
+    pub expected_count: usize,
+    /// Periods in the window with at least one DONE entry.
+    pub done_count: usize,
+    /// Whether a DONE entry is logged on today itself.
+    #[serde(default)]
+    pub done_today: bool,
+    /// Whether the habit falls due today; see [`Habit::expected_on`].
+    #[serde(default)]
+    pub expected_today: bool,
+}
+
+impl Habit {
+    /// Statistics over the `window` days ending at `today`. Streaks look at the whole log.
+    pub fn stats(&self, today: NaiveDate, window: usize) -> HabitStats {
+        self.stats_counting(today, window, &["DONE"])
+    }
+
+    /// Like [`Habit::stats`], but counts log entries that moved to any of `done_states`.
+    pub fn stats_counting(
+        &self,
+        today: NaiveDate,
+        window: usize,
+        done_states: &[&str],
+    ) -> HabitStats {
+        let frequency = self
+            .repeater
+            .as_ref()
+            .and_then(|repeater| repeater.frequency.as_ref());
+        let due_weekdays = match frequency {
+            Some(HabitFrequency::WeeklyOn(days)) => Some(days.as_slice()),
+            _ => None,
+        };
+        let period = frequency.map_or(1, HabitFrequency::period_days);
+        let done_dates: Vec<NaiveDate> = self
+            .log_entries
+            .iter()
+            .filter(|entry| entry.is_done(done_states))
+            .map(|entry| entry.date)
+            .chain(self.last_repeat)
+            .filter(|date| *date <= today)
+            .collect();
+        // Period 0 ends today, period 1 the `period` days before it, and so on. Habits kept to
+        // certain weekdays have one period per due day instead, counted back from the latest
+        // one, and completions on other days do not count.
+        let period_of = |date: NaiveDate| match due_weekdays {
+            Some(days) => days
+                .contains(&date.weekday())
+                .then(|| due_days_after(days, date, today)),
+            None => Some(((today - date).num_days() as u64 / period) as usize),
+        };
+        let oldest = done_dates.iter().filter_map(|date| period_of(*date)).max();
+        let mut done = vec![false; oldest.map_or(0, |oldest| oldest + 1)];
+        for period in done_dates.iter().filter_map(|date| period_of(*date)) {
+            done[period] = true;
+        }
+
+        let expected_count = match due_weekdays {
+            Some(days) => due_days_after(days, today - Days::new(window as u64), today),
+            None => (window as u64).div_ceil(period) as usize,
+        };
+        let done_count = done.iter().take(expected_count).filter(|hit| **hit).count();
+        // The latest period is still open, so missing it does not break the streak yet.
+        let open = due_weekdays.is_none_or(|days| days.contains(&today.weekday()));
+        let skip_open_period = usize::from(open && !done.first().copied().unwrap_or(false));
+        let current_streak = done
+            .iter()
+            .skip(skip_open_period)
+            .take_while(|hit| **hit)
+            .count();
+        let longest_streak = done
+            .split(|hit| !*hit)
+            .map(<[bool]>::len)
+            .max()
+            .unwrap_or(0);
+        let completion_rate = if expected_count == 0 {
+            0.0
+        } else {
+            done_count as f64 / expected_count as f64
+        };
+        HabitStats {
+            current_streak,
+            longest_streak,
+            completion_rate,
+            expected_count,
+            done_count,
+            done_today: done_dates.contains(&today),
+            // A day the habit was done on was due, whatever SCHEDULED has moved on to.
+            expected_today: done_dates.contains(&today) || self.expected_on(today),
+        }
+    }
+}
+
+impl Habit {
+    /// Whether the habit falls due on `date`. Repeats count from the scheduled date when there
+    /// is one, backwards as well as forwards since completing a habit moves SCHEDULED on to its
+    /// next due date; weekday-restricted habits only look at the weekday of `date`.
+    pub fn expected_on(&self, date: NaiveDate) -> bool {
+        let Some(frequency) = self
+            .repeater
+            .as_ref()
+            .and_then(|repeater| repeater.frequency.as_ref())
+        else {
+            return true;
+        };
+        match (frequency, self.scheduled) {
+            (HabitFrequency::WeeklyOn(days), _) => days.contains(&date.weekday()),
+            (_, None) => true,
+            (frequency, Some(anchor)) if date >= anchor => frequency.repeats_on(anchor, date),
+            (frequency, Some(anchor)) => frequency.repeats_on(date, anchor),
+        }
+    }
+}
+
+impl HabitFrequency {
+    /// Whether a repeat that started on `anchor` lands on `date` (`date >= anchor`).
+    fn repeats_on(&self, anchor: NaiveDate, date: NaiveDate) -> bool {
+        let months =
+            (date.year() - anchor.year()) * 12 + date.month() as i32 - anchor.month() as i32;
+        match self {
+            HabitFrequency::Daily(n) => (date - anchor).num_days() % i64::from((*n).max(1)) == 0,
+            HabitFrequency::Weekly(n) => {
+                (date - anchor).num_days() % (i64::from((*n).max(1)) * 7) == 0
+            }
+            HabitFrequency::WeeklyOn(days) => days.contains(&date.weekday()),
+            HabitFrequency::Monthly(n) => {
+                date.day() == anchor.day() && months % (*n).max(1) as i32 == 0
+            }
+            HabitFrequency::Yearly(n) => {
+                date.ordinal() == anchor.ordinal()
+                    && (date.year() - anchor.year()) % (*n).max(1) as i32 == 0
+            }
+        }
+    }
+
+    /// Approximate length of one repeat in days; months count as 30 and years as 365.
+    fn period_days(&self) -> u64 {
+        match self {
+            HabitFrequency::Daily(n) => u64::from(*n),
+            HabitFrequency::Weekly(n) => u64::from(*n) * 7,
+            HabitFrequency::WeeklyOn(_) => 1,
+            HabitFrequency::Monthly(n) => u64::from(*n) * 30,
+            HabitFrequency::Yearly(n) => u64::from(*n) * 365,
+        }
+        .max(1)
+    }
+}
+
+/// How many days in `(from, to]` fall on one of `days`.
+fn due_days_after(days: &[Weekday], from: NaiveDate, to: NaiveDate) -> usize {
+    let span = (to - from).num_days().max(0) as u64;
+    let due_on = |offset: u64| days.contains(&(from + Days::new(offset)).weekday());
+    let per_week = (1..=7).filter(|offset| due_on(*offset)).count();
+    per_week * (span / 7) as usize + (1..=span % 7).filter(|offset| due_on(*offset)).count()
+}
+
 impl HabitRepeater {
     fn from_token(token: &str) -> Self {
         let frequency = parse_frequency(token);
@@ -52,6 +232,7 @@ struct HabitBuilder {
     repeater: Option<HabitRepeater>,
     log_entries: Vec<HabitLogEntry>,
     last_repeat: Option<NaiveDate>,
+    weekdays: Vec<Weekday>,
 }
 
 impl HabitBuilder {
@@ -67,14 +248,22 @@ impl HabitBuilder {
             return None;
         }
         let description = self.description_lines.join("\n").trim().to_string();
-        let last_repeat = self
-            .last_repeat
-            .or_else(|| self.log_entries.iter().map(|entry| entry.date).max());
+        let mut repeater = self.repeater;
+        if let Some(repeater) = repeater.as_mut().filter(|_| !self.weekdays.is_empty()) {
+            repeater.frequency = Some(HabitFrequency::WeeklyOn(self.weekdays));
+        }
+        let last_repeat = self.last_repeat.or_else(|| {
+            self.log_entries
+                .iter()
+                .filter(|entry| entry.is_done(&["DONE"]))
+                .map(|entry| entry.date)
+                .max()
+        });
         Some(Habit {
             title: self.title,
             scheduled: self.scheduled,
             description,
-            repeater: self.repeater,
+            repeater,
             log_entries: self.log_entries,
             last_repeat,
         })
@@ -130,6 +319,8 @@ pub fn extract_habits(doc: &OrgDocument) -> Vec<Habit> {
                                     if let Some(date) = extract_date_from_brackets(value) {
                                         builder.last_repeat = Some(date);
                                     }
+                                } else if key_upper == "HABIT_DAYS" {
+                                    builder.weekdays = parse_weekdays(value);
                                 }
                             }
                         }
@@ -145,7 +336,7 @@ pub fn extract_habits(doc: &OrgDocument) -> Vec<Habit> {
             continue;
         }
 
-        if trimmed.starts_with("SCHEDULED:") {
+        if agenda::is_planning_line(trimmed) {
             if let Some(info) = parse_scheduled(trimmed) {
                 builder.scheduled = Some(info.date);
                 builder.repeater = info.repeater;
@@ -171,8 +362,11 @@ struct ScheduledInfo {
 }
 
 fn parse_scheduled(line: &str) -> Option<ScheduledInfo> {
-    let rest = line.trim_start_matches("SCHEDULED:").trim();
-    let bracket = rest.strip_prefix('<')?.strip_suffix('>')?;
+    let (_, rest) = agenda::planning_entries(line)
+        .into_iter()
+        .find(|(keyword, _)| *keyword == "SCHEDULED:")?;
+    let bracket = rest.strip_prefix('<')?;
+    let bracket = &bracket[..bracket.find('>')?];
     let mut parts = bracket.split_whitespace();
     let date_str = parts.next()?;
     let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
@@ -198,11 +392,21 @@ fn parse_logbook_entry(line: &str) -> Option<HabitLogEntry> {
     if !line.starts_with('-') {
         return None;
     }
-    let state = line.split('"').nth(1)?.trim().to_string();
+    let mut quoted = line.split('"').skip(1).step_by(2);
+    let state = quoted.next()?.trim().to_string();
+    let from_state = line
+        .contains(" from ")
+        .then(|| quoted.next())
+        .flatten()
+        .map(|from| from.trim().to_string());
     let date_section = line.split('[').nth(1)?.split(']').next()?;
     let date_str = date_section.split_whitespace().next()?;
     let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
-    Some(HabitLogEntry { date, state })
+    Some(HabitLogEntry {
+        date,
+        state,
+        from_state,
+    })
 }
 
 fn parse_frequency(token: &str) -> Option<HabitFrequency> {
@@ -222,11 +426,116 @@ fn parse_frequency(token: &str) -> Option<HabitFrequency> {
     }
 }
 
+/// Parses a `:HABIT_DAYS:` value: weekday names (`Mon`, `wednesday`) separated by spaces or
+/// commas, plus the `weekdays` and `weekends` shorthands.
+fn parse_weekdays(value: &str) -> Vec<Weekday> {
+    let mut days = Vec::new();
+    for token in value.split(|c: char| c == ',' || c.is_whitespace()) {
+        let expanded: &[Weekday] = match token.to_ascii_lowercase().as_str() {
+            "" => &[],
+            "weekdays" => &[
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            "weekends" => &[Weekday::Sat, Weekday::Sun],
+            _ => match token.parse::<Weekday>() {
+                Ok(day) => &[day][..],
+                Err(_) => &[],
+            },
+        };
+        for day in expanded {
+            if !days.contains(day) {
+                days.push(*day);
+            }
+        }
+    }
+    days
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::document::OrgDocument;
 
+    fn habit_done_on(repeater: &str, days: &[u32]) -> Habit {
+        Habit {
+            title: "Stretch".to_string(),
+            scheduled: None,
+            description: String::new(),
+            repeater: Some(HabitRepeater::from_token(repeater)),
+            log_entries: days
+                .iter()
+                .map(|day| HabitLogEntry {
+                    date: NaiveDate::from_ymd_opt(2025, 10, *day).unwrap(),
+                    state: "DONE".to_string(),
+                    from_state: Some("TODO".to_string()),
+                })
+                .collect(),
+            last_repeat: None,
+        }
+    }
+
+    #[test]
+    fn daily_habit_stats_count_gaps_and_streaks() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        let habit = habit_done_on("+1d", &[1, 2, 3, 5, 6, 8, 9]);
+
+        let stats = habit.stats(today, 10);
+        assert_eq!(stats.expected_count, 10);
+        assert_eq!(stats.done_count, 7);
+        assert!((stats.completion_rate - 0.7).abs() < f64::EPSILON);
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.longest_streak, 3);
+
+        let stats = habit.stats(today, 5);
+        assert_eq!((stats.expected_count, stats.done_count), (5, 3));
+
+        let missed_yesterday = habit_done_on("+1d", &[7, 10]);
+        assert_eq!(missed_yesterday.stats(today, 10).current_streak, 1);
+    }
+
+    #[test]
+    fn weekly_habit_stats_count_weeks() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 28).unwrap();
+        let habit = habit_done_on("+1w", &[1, 9, 27]);
+
+        let stats = habit.stats(today, 28);
+        // Weeks end on Oct 28, 21, 14, and 7; only the week ending the 21st was missed.
+        assert_eq!(stats.expected_count, 4);
+        assert_eq!(stats.done_count, 3);
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.longest_streak, 2);
+    }
+
+    #[test]
+    fn weekday_habit_stats_only_count_due_days() {
+        let mondays_wednesdays_fridays = [6, 8, 10, 13, 15, 17, 20, 22, 24, 27, 29, 31];
+        let mut habit = habit_done_on("+1w", &mondays_wednesdays_fridays);
+        habit.repeater.as_mut().unwrap().frequency = Some(HabitFrequency::WeeklyOn(vec![
+            Weekday::Mon,
+            Weekday::Wed,
+            Weekday::Fri,
+        ]));
+
+        let friday = NaiveDate::from_ymd_opt(2025, 10, 31).unwrap();
+        let stats = habit.stats(friday, 28);
+        assert_eq!((stats.expected_count, stats.done_count), (12, 12));
+        assert!((stats.completion_rate - 1.0).abs() < f64::EPSILON);
+        assert_eq!((stats.current_streak, stats.longest_streak), (12, 12));
+
+        // The weekend is not due, so Friday's completion keeps the streak going.
+        let sunday = NaiveDate::from_ymd_opt(2025, 11, 2).unwrap();
+        assert_eq!(habit.stats(sunday, 28).current_streak, 12);
+
+        habit.log_entries.retain(|entry| entry.date.day() != 29);
+        let stats = habit.stats(friday, 28);
+        assert_eq!(stats.done_count, 11);
+        assert_eq!((stats.current_streak, stats.longest_streak), (1, 10));
+    }
+
     #[test]
     fn extracts_habit_with_repeater_and_logbook() {
         let raw = r#"
@@ -264,4 +573,117 @@ Take a short mindful break.
         assert_eq!(habit.repeater.as_ref().unwrap().raw, "+1d");
         assert!(habit.description.contains("mindful"));
     }
+
+    #[test]
+    fn reads_scheduled_from_tab_separated_planning_line() {
+        let raw = "* TODO Stretch\n\tDEADLINE:\t<2025-10-25 Sat>\tSCHEDULED:\t<2025-10-20 Mon .+2d>\n:PROPERTIES:\n:STYLE: habit\n:END:\n";
+        let doc = OrgDocument::from_string("habit_tabs.org", raw.to_string());
+        let habits = extract_habits(&doc);
+        assert_eq!(habits.len(), 1);
+        assert_eq!(
+            habits[0].scheduled,
+            Some(NaiveDate::from_ymd_opt(2025, 10, 20).unwrap())
+        );
+        assert_eq!(habits[0].repeater.as_ref().unwrap().raw, ".+2d");
+        assert!(habits[0].description.is_empty());
+    }
+
+    #[test]
+    fn habit_days_restrict_expected_weekdays() {
+        let raw = r#"
+* TODO Gym
+SCHEDULED: <2025-10-21 Tue +1w>
+:PROPERTIES:
+:STYLE: habit
+:HABIT_DAYS: Mon, Wed, Fri
+:END:
+"#;
+        let doc = OrgDocument::from_string("habit_days.org", raw.to_string());
+        let habit = &extract_habits(&doc)[0];
+        assert_eq!(
+            habit.repeater.as_ref().unwrap().frequency,
+            Some(HabitFrequency::WeeklyOn(vec![
+                Weekday::Mon,
+                Weekday::Wed,
+                Weekday::Fri
+            ]))
+        );
+        let expected: Vec<bool> = (20..=26)
+            .map(|day| habit.expected_on(NaiveDate::from_ymd_opt(2025, 10, day).unwrap()))
+            .collect();
+        assert_eq!(expected, vec![true, false, true, false, true, false, false]);
+    }
+
+    #[test]
+    fn weekly_habit_is_expected_on_its_scheduled_weekday() {
+        let mut habit = habit_done_on("+1w", &[]);
+        habit.scheduled = NaiveDate::from_ymd_opt(2025, 10, 21);
+        assert!(habit.expected_on(NaiveDate::from_ymd_opt(2025, 10, 28).unwrap()));
+        assert!(!habit.expected_on(NaiveDate::from_ymd_opt(2025, 10, 27).unwrap()));
+        assert!(habit.expected_on(NaiveDate::from_ymd_opt(2025, 10, 14).unwrap()));
+        assert!(!habit.expected_on(NaiveDate::from_ymd_opt(2025, 10, 15).unwrap()));
+    }
+
+    #[test]
+    fn only_done_log_entries_count_toward_stats() {
+        let raw = r#"
+* TODO Read
+SCHEDULED: <2025-10-01 Wed +1d>
+:PROPERTIES:
+:STYLE: habit
+:END:
+:LOGBOOK:
+- State "TODO"       from "DONE"       [2025-10-10 Fri]
+- State "DONE"       from "TODO"       [2025-10-09 Thu]
+- State "DONE"       from "TODO"       [2025-10-08 Wed]
+- State "TODO"       from "DONE"       [2025-10-07 Tue]
+:END:
+"#;
+        let doc = OrgDocument::from_string("habit_states.org", raw.to_string());
+        let habit = &extract_habits(&doc)[0];
+        assert_eq!(habit.log_entries.len(), 4);
+        assert_eq!(habit.log_entries[0].state, "TODO");
+        assert_eq!(habit.log_entries[0].from_state.as_deref(), Some("DONE"));
+        assert_eq!(habit.last_repeat, NaiveDate::from_ymd_opt(2025, 10, 9));
+
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        let stats = habit.stats(today, 4);
+        assert_eq!(stats.done_count, 2);
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(
+            habit.stats_counting(today, 4, &["DONE", "TODO"]).done_count,
+            4
+        );
+    }
+
+    #[test]
+    fn stats_report_whether_today_is_done_and_expected() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        let done = habit_done_on("+1d", &[9, 10]).stats(today, 7);
+        assert!(done.done_today);
+        assert!(done.expected_today);
+
+        let outstanding = habit_done_on("+1d", &[8, 9]).stats(today, 7);
+        assert!(!outstanding.done_today);
+        assert!(outstanding.expected_today);
+
+        let mut weekly = habit_done_on("+1w", &[3]);
+        weekly.scheduled = NaiveDate::from_ymd_opt(2025, 10, 4);
+        assert!(!weekly.stats(today, 7).expected_today);
+    }
+
+    #[test]
+    fn completed_and_rescheduled_habit_is_still_expected_today() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 10).unwrap();
+        let mut habit = habit_done_on(".+1d", &[9, 10]);
+        habit.scheduled = today.succ_opt();
+        let stats = habit.stats(today, 7);
+        assert!(stats.done_today);
+        assert!(stats.expected_today);
+
+        let mut every_other_day = habit_done_on("+2d", &[]);
+        every_other_day.scheduled = NaiveDate::from_ymd_opt(2025, 10, 12);
+        assert!(every_other_day.expected_on(today));
+        assert!(!every_other_day.expected_on(today.succ_opt().unwrap()));
+    }
 }
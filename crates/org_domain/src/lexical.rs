@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 
 use serde::Serialize;
 
-use crate::document::OrgDocument;
+use crate::{agenda::TodoKeywords, document::OrgDocument, markdown::LanguageAliases};
 
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(tag = "type")]
@@ -46,6 +46,7 @@ pub enum LexicalNode {
     #[serde(rename = "paragraph")]
     Paragraph {
         text: String,
+        spans: Vec<TextSpan>,
         raw: String,
         line_start: usize,
         line_end: usize,
@@ -56,6 +57,7 @@ pub enum LexicalNode {
         ordered: bool,
         checked: Option<bool>,
         text: String,
+        spans: Vec<TextSpan>,
         raw: String,
         line_start: usize,
         line_end: usize,
@@ -63,6 +65,28 @@ pub enum LexicalNode {
     #[serde(rename = "code_block")]
     CodeBlock {
         language: Option<String>,
+        /// `language` as a Markdown fence info string, mapped through [`LanguageAliases`].
+        fence_language: Option<String>,
+        name: Option<String>,
+        text: String,
+        raw: String,
+        line_start: usize,
+        line_end: usize,
+    },
+    /// A `#+begin_verse` block; each source line is kept as its own line.
+    #[serde(rename = "verse")]
+    Verse {
+        lines: Vec<String>,
+        raw: String,
+        line_start: usize,
+        line_end: usize,
+    },
+    /// Babel output: a `#+RESULTS:` line plus the fixed-width lines, table, block, or
+    /// `:RESULTS:` drawer below it.
+    #[serde(rename = "result")]
+    Result {
+        /// Text after `#+RESULTS:`, usually the name of the block that produced it.
+        name: Option<String>,
         text: String,
         raw: String,
         line_start: usize,
@@ -70,7 +94,12 @@ pub enum LexicalNode {
     },
     #[serde(rename = "table")]
     Table {
+        /// Rows above the first `|---` separator; empty without one.
+        header: Vec<Vec<String>>,
+        /// Remaining rows, without separators, padded to the widest row.
         rows: Vec<Vec<String>>,
+        /// Text of the `#+TBLFM:` lines directly below the table, one formula line per line.
+        formula: Option<String>,
         raw: String,
         line_start: usize,
         line_end: usize,
@@ -91,6 +120,16 @@ pub enum LexicalNode {
     },
 }
 
+/// A run of inline text sharing the same formatting.
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+pub struct TextSpan {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 struct SourceLine {
     number: usize,
@@ -108,7 +147,9 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
         })
         .collect();
 
+    let keywords = TodoKeywords::from_document(doc.raw());
     let mut nodes: Vec<LexicalNode> = Vec::new();
+    let mut list_indents: Vec<usize> = Vec::new();
     let mut idx = 0;
 
     while idx < source.len() {
@@ -120,7 +161,7 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
             continue;
         }
 
-        if let Some(node) = parse_heading(line) {
+        if let Some(node) = parse_heading(line, &keywords) {
             nodes.push(node);
             idx += 1;
             continue;
@@ -139,7 +180,7 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
             continue;
         }
 
-        if let Some(drawer_name) = drawer_name(trimmed) {
+        if let Some(drawer_name) = opens_drawer(&source, idx) {
             let (node, next_idx) = collect_drawer(&source, idx, drawer_name);
             nodes.push(node);
             idx = next_idx;
@@ -147,7 +188,22 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
         }
 
         if begins_block(trimmed, "#+BEGIN_SRC") || begins_block(trimmed, "#+BEGIN_EXAMPLE") {
-            let (node, next_idx) = collect_code_block(&source, idx);
+            let (mut node, next_idx) = collect_code_block(&source, idx);
+            attach_block_name(&mut nodes, &mut node);
+            nodes.push(node);
+            idx = next_idx;
+            continue;
+        }
+
+        if begins_block(trimmed, "#+BEGIN_VERSE") {
+            let (node, next_idx) = collect_verse_block(&source, idx);
+            nodes.push(node);
+            idx = next_idx;
+            continue;
+        }
+
+        if is_results_keyword(trimmed) {
+            let (node, next_idx) = collect_results(&source, idx);
             nodes.push(node);
             idx = next_idx;
             continue;
@@ -176,7 +232,10 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
             continue;
         }
 
-        if let Some(node) = parse_list_item(line) {
+        if !matches!(nodes.last(), Some(LexicalNode::ListItem { .. })) {
+            list_indents.clear();
+        }
+        if let Some(node) = parse_list_item(line, &mut list_indents) {
             nodes.push(node);
             idx += 1;
             continue;
@@ -190,6 +249,7 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
     if nodes.is_empty() {
         nodes.push(LexicalNode::Paragraph {
             text: String::new(),
+            spans: Vec::new(),
             raw: String::new(),
             line_start: 0,
             line_end: 0,
@@ -199,17 +259,9 @@ pub fn document_to_lexical(doc: &OrgDocument) -> Vec<LexicalNode> {
     nodes
 }
 
-fn parse_heading(line: &SourceLine) -> Option<LexicalNode> {
+fn parse_heading(line: &SourceLine, keywords: &TodoKeywords) -> Option<LexicalNode> {
     let trimmed = line.text.trim_start();
-    let stars_len = trimmed.chars().take_while(|c| *c == '*').count();
-    if stars_len == 0
-        || !trimmed
-            .chars()
-            .nth(stars_len)
-            .is_some_and(|c| c.is_whitespace())
-    {
-        return None;
-    }
+    let stars_len = heading_stars(trimmed)?;
 
     let content = trimmed[stars_len..].trim();
     let (content_without_tags, tags) = parse_tags(content);
@@ -218,7 +270,7 @@ fn parse_heading(line: &SourceLine) -> Option<LexicalNode> {
     let mut priority = None;
 
     if let Some(first) = parts.peek().copied() {
-        if is_todo_keyword(first) {
+        if keywords.contains(first) {
             todo_keyword = Some(first.to_string());
             parts.next();
         }
@@ -317,6 +369,10 @@ fn collect_until_drawer_end(source: &[SourceLine], start: usize) -> (Vec<String>
     let mut idx = start;
     let mut raw = Vec::new();
     while idx < source.len() {
+        // An unterminated drawer stops at the next headline rather than swallowing it.
+        if idx > start && heading_stars(source[idx].text.trim_start()).is_some() {
+            break;
+        }
         let text = source[idx].text.clone();
         let is_end = text.trim().eq_ignore_ascii_case(":END:");
         raw.push(text);
@@ -330,19 +386,29 @@ fn collect_until_drawer_end(source: &[SourceLine], start: usize) -> (Vec<String>
 
 fn collect_code_block(source: &[SourceLine], start: usize) -> (LexicalNode, usize) {
     let first = source[start].text.trim();
+    // Example blocks are verbatim text; anything after the marker is a switch such as `-n`,
+    // not a language.
+    let is_example = begins_block(first, "#+BEGIN_EXAMPLE");
+    let end_marker = if is_example {
+        "#+END_EXAMPLE"
+    } else {
+        "#+END_SRC"
+    };
     let language = first
         .split_whitespace()
         .nth(1)
+        .filter(|_| !is_example)
         .map(|lang| lang.trim().to_string())
         .filter(|lang| !lang.is_empty());
+    let fence_language = language
+        .as_deref()
+        .map(|lang| LanguageAliases::default().fence_language(lang).to_string());
     let mut idx = start;
     let mut raw = Vec::new();
     let mut body = Vec::new();
     while idx < source.len() {
         let text = source[idx].text.clone();
-        let trimmed = text.trim();
-        let is_end = trimmed.eq_ignore_ascii_case("#+END_SRC")
-            || trimmed.eq_ignore_ascii_case("#+END_EXAMPLE");
+        let is_end = text.trim().eq_ignore_ascii_case(end_marker);
         if idx != start && !is_end {
             body.push(text.clone());
         }
@@ -357,6 +423,8 @@ fn collect_code_block(source: &[SourceLine], start: usize) -> (LexicalNode, usiz
     (
         LexicalNode::CodeBlock {
             language,
+            fence_language,
+            name: None,
             text: body.join("\n"),
             raw: raw.join("\n"),
             line_start,
@@ -366,27 +434,165 @@ fn collect_code_block(source: &[SourceLine], start: usize) -> (LexicalNode, usiz
     )
 }
 
+fn collect_verse_block(source: &[SourceLine], start: usize) -> (LexicalNode, usize) {
+    let mut idx = start;
+    let mut raw = Vec::new();
+    let mut lines = Vec::new();
+    while idx < source.len() {
+        let text = source[idx].text.clone();
+        let is_end = text.trim().eq_ignore_ascii_case("#+END_VERSE");
+        if idx != start && !is_end {
+            lines.push(text.clone());
+        }
+        raw.push(text);
+        idx += 1;
+        if is_end {
+            break;
+        }
+    }
+    let line_start = source[start].number;
+    let line_end = source[idx - 1].number;
+    (
+        LexicalNode::Verse {
+            lines,
+            raw: raw.join("\n"),
+            line_start,
+            line_end,
+        },
+        idx,
+    )
+}
+
+/// Collects a `#+RESULTS:` line and the output directly below it: a `#+BEGIN_` block, a
+/// `:RESULTS:` drawer, or a run of `: ` fixed-width lines and table rows.
+fn collect_results(source: &[SourceLine], start: usize) -> (LexicalNode, usize) {
+    let header = source[start].text.trim();
+    let name = header
+        .split_once(':')
+        .map(|(_, rest)| rest.trim().to_string())
+        .filter(|rest| !rest.is_empty());
+    let mut idx = start + 1;
+    let mut body = Vec::new();
+    let next = source.get(idx).map(|line| line.text.trim()).unwrap_or("");
+    if begins_block(next, "#+BEGIN_") || next.eq_ignore_ascii_case(":RESULTS:") {
+        idx += 1;
+        while idx < source.len() {
+            let trimmed = source[idx].text.trim();
+            idx += 1;
+            if begins_block(trimmed, "#+END_") || trimmed.eq_ignore_ascii_case(":END:") {
+                break;
+            }
+            body.push(source[idx - 1].text.clone());
+        }
+    } else {
+        while let Some(line) = source.get(idx) {
+            let trimmed = line.text.trim();
+            if trimmed == ":" {
+                body.push(String::new());
+            } else if let Some(rest) = trimmed.strip_prefix(": ") {
+                body.push(rest.to_string());
+            } else if is_table_row(trimmed) {
+                body.push(trimmed.to_string());
+            } else {
+                break;
+            }
+            idx += 1;
+        }
+    }
+    let raw: Vec<&str> = source[start..idx]
+        .iter()
+        .map(|line| line.text.as_str())
+        .collect();
+    (
+        LexicalNode::Result {
+            name,
+            text: body.join("\n"),
+            raw: raw.join("\n"),
+            line_start: source[start].number,
+            line_end: source[idx - 1].number,
+        },
+        idx,
+    )
+}
+
+/// Folds a `#+NAME:` directive on the line directly above a block into that block.
+fn attach_block_name(nodes: &mut Vec<LexicalNode>, block: &mut LexicalNode) {
+    let LexicalNode::CodeBlock {
+        name,
+        raw,
+        line_start,
+        ..
+    } = block
+    else {
+        return;
+    };
+    let is_name_directive = matches!(
+        nodes.last(),
+        Some(LexicalNode::Directive { keyword, line_end, .. })
+            if keyword.eq_ignore_ascii_case("NAME") && *line_end + 1 == *line_start
+    );
+    if !is_name_directive {
+        return;
+    }
+    if let Some(LexicalNode::Directive {
+        text,
+        raw: directive_raw,
+        line_start: directive_start,
+        ..
+    }) = nodes.pop()
+    {
+        *name = Some(text).filter(|text| !text.is_empty());
+        *raw = format!("{}\n{}", directive_raw, raw);
+        *line_start = directive_start;
+    }
+}
+
 fn collect_table(source: &[SourceLine], start: usize) -> (LexicalNode, usize) {
     let mut idx = start;
     let mut raw = Vec::new();
-    let mut rows = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut header_rows = 0;
     while idx < source.len() && is_table_row(source[idx].text.trim()) {
         let line = source[idx].text.clone();
-        rows.push(
-            line.trim()
-                .trim_matches('|')
-                .split('|')
-                .map(|cell| cell.trim().to_string())
-                .collect(),
-        );
+        let trimmed = line.trim();
+        if trimmed.starts_with("|-") {
+            if header_rows == 0 {
+                header_rows = rows.len();
+            }
+        } else {
+            rows.push(
+                trimmed
+                    .trim_matches('|')
+                    .split('|')
+                    .map(|cell| cell.trim().to_string())
+                    .collect(),
+            );
+        }
         raw.push(line);
         idx += 1;
     }
+    let mut formulas = Vec::new();
+    while let Some(line) = source.get(idx) {
+        let trimmed = line.text.trim();
+        if !begins_block(trimmed, "#+TBLFM:") {
+            break;
+        }
+        formulas.push(trimmed["#+TBLFM:".len()..].trim().to_string());
+        raw.push(line.text.clone());
+        idx += 1;
+    }
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in rows.iter_mut() {
+        row.resize(width, String::new());
+    }
+    let header = rows.drain(..header_rows).collect();
     let line_start = source[start].number;
     let line_end = source[idx - 1].number;
     (
         LexicalNode::Table {
+            header,
             rows,
+            formula: (!formulas.is_empty()).then(|| formulas.join("\n")),
             raw: raw.join("\n"),
             line_start,
             line_end,
@@ -402,15 +608,15 @@ fn collect_paragraph(source: &[SourceLine], start: usize) -> (LexicalNode, usize
         let line = &source[idx];
         let trimmed = line.text.trim();
         if trimmed.is_empty()
-            || parse_heading(line).is_some()
+            || heading_stars(line.text.trim_start()).is_some()
             || parse_planning(line).is_some()
-            || drawer_name(trimmed).is_some()
+            || opens_drawer(source, idx).is_some()
             || begins_block(trimmed, "#+BEGIN_SRC")
             || begins_block(trimmed, "#+BEGIN_EXAMPLE")
             || trimmed.starts_with("#+")
             || is_horizontal_rule(trimmed)
             || is_table_row(trimmed)
-            || parse_list_item(line).is_some()
+            || split_list_marker(trimmed).is_some()
         {
             break;
         }
@@ -419,13 +625,15 @@ fn collect_paragraph(source: &[SourceLine], start: usize) -> (LexicalNode, usize
     }
     let line_start = source[start].number;
     let line_end = source[idx - 1].number;
+    let text = lines
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join(" ");
     (
         LexicalNode::Paragraph {
-            text: lines
-                .iter()
-                .map(|line| line.trim())
-                .collect::<Vec<_>>()
-                .join(" "),
+            spans: parse_inline(&text),
+            text,
             raw: lines.join("\n"),
             line_start,
             line_end,
@@ -434,40 +642,261 @@ fn collect_paragraph(source: &[SourceLine], start: usize) -> (LexicalNode, usize
     )
 }
 
-fn parse_list_item(line: &SourceLine) -> Option<LexicalNode> {
-    let indent = line.text.chars().take_while(|c| c.is_whitespace()).count();
-    let trimmed = line.text[indent..].trim_start();
+/// Serializes lexical nodes back to org markup.
+///
+/// Headings, paragraphs, and list items are rebuilt from their fields so edits to `text` and
+/// friends are saved; every other node is written back from its `raw` source. Feeding the
+/// output through `document_to_lexical` and back yields the same text.
+pub fn lexical_to_document(nodes: &[LexicalNode]) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut ordered_counters: Vec<u32> = Vec::new();
+    let mut previous_was_paragraph = false;
 
-    let (ordered, rest) = if let Some(rest) = trimmed
-        .strip_prefix("- ")
-        .or_else(|| trimmed.strip_prefix("+ "))
-        .or_else(|| trimmed.strip_prefix("* "))
-    {
-        (false, rest)
-    } else if let Some(idx) = trimmed.find(|c: char| c == '.' || c == ')') {
-        if trimmed[..idx].chars().all(|c| c.is_ascii_digit()) {
-            (true, trimmed[idx + 1..].trim_start())
+    for node in nodes {
+        if !matches!(node, LexicalNode::ListItem { .. }) {
+            ordered_counters.clear();
+        }
+        let is_paragraph = matches!(node, LexicalNode::Paragraph { .. });
+        if is_paragraph && previous_was_paragraph {
+            lines.push(String::new());
+        }
+        previous_was_paragraph = is_paragraph;
+
+        match node {
+            LexicalNode::Heading {
+                depth,
+                text,
+                todo_keyword,
+                priority,
+                tags,
+                ..
+            } => {
+                let mut line = "*".repeat((*depth).max(1) as usize);
+                if let Some(keyword) = todo_keyword {
+                    line.push(' ');
+                    line.push_str(keyword);
+                }
+                if let Some(priority) = priority {
+                    line.push_str(&format!(" [#{}]", priority));
+                }
+                if !text.is_empty() {
+                    line.push(' ');
+                    line.push_str(text);
+                }
+                if !tags.is_empty() {
+                    line.push_str(&format!(" :{}:", tags.join(":")));
+                }
+                lines.push(line);
+            }
+            LexicalNode::Paragraph { text, .. } => {
+                if !text.is_empty() {
+                    lines.push(text.clone());
+                }
+            }
+            LexicalNode::ListItem {
+                depth,
+                ordered,
+                checked,
+                text,
+                ..
+            } => {
+                let level = (*depth).max(1) as usize;
+                ordered_counters.resize(level, 0);
+                let bullet = if *ordered {
+                    ordered_counters[level - 1] += 1;
+                    format!("{}.", ordered_counters[level - 1])
+                } else {
+                    "-".to_string()
+                };
+                let checkbox = match checked {
+                    Some(true) => " [X]",
+                    Some(false) => " [ ]",
+                    None => "",
+                };
+                let mut line = format!("{}{}{}", "  ".repeat(level - 1), bullet, checkbox);
+                if !text.is_empty() {
+                    line.push(' ');
+                    line.push_str(text);
+                }
+                lines.push(line);
+            }
+            LexicalNode::Planning { raw, .. }
+            | LexicalNode::PropertyDrawer { raw, .. }
+            | LexicalNode::Drawer { raw, .. }
+            | LexicalNode::CodeBlock { raw, .. }
+            | LexicalNode::Verse { raw, .. }
+            | LexicalNode::Result { raw, .. }
+            | LexicalNode::Table { raw, .. }
+            | LexicalNode::Directive { raw, .. }
+            | LexicalNode::HorizontalRule { raw, .. } => lines.push(raw.clone()),
+        }
+    }
+
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// Splits org inline markup into formatted spans: `*bold*`, `/italic/`, `=code=`,
+/// `~verbatim~` (also `code`), and `[[target][label]]` links. Markers only count when they
+/// sit on a word boundary and hug their content, so a lone `* ` is left as text.
+pub fn parse_inline(text: &str) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    push_inline(text, &TextSpan::default(), &mut spans);
+    spans
+}
+
+fn push_inline(text: &str, style: &TextSpan, spans: &mut Vec<TextSpan>) {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut plain_start = 0;
+    let mut idx = 0;
+    while idx < chars.len() {
+        let (offset, marker) = chars[idx];
+        if text[offset..].starts_with("[[") {
+            if let Some(end) = text[offset..].find("]]") {
+                let inner = &text[offset + 2..offset + end];
+                let (target, label) = inner.split_once("][").unwrap_or((inner, inner));
+                push_plain(&text[plain_start..offset], style, spans);
+                let linked = TextSpan {
+                    link: Some(target.to_string()),
+                    ..style.clone()
+                };
+                push_inline(label, &linked, spans);
+                plain_start = offset + end + 2;
+                idx = chars
+                    .iter()
+                    .position(|(pos, _)| *pos >= plain_start)
+                    .unwrap_or(chars.len());
+                continue;
+            }
+        }
+        let opens = matches!(marker, '*' | '/' | '=' | '~')
+            && (idx == 0 || is_emphasis_border(chars[idx - 1].1))
+            && chars.get(idx + 1).is_some_and(|(_, c)| !c.is_whitespace());
+        let close = opens
+            .then(|| {
+                (idx + 2..chars.len()).find(|&pos| {
+                    chars[pos].1 == marker
+                        && !chars[pos - 1].1.is_whitespace()
+                        && chars
+                            .get(pos + 1)
+                            .is_none_or(|(_, c)| is_emphasis_border(*c))
+                })
+            })
+            .flatten();
+        let Some(close) = close else {
+            idx += 1;
+            continue;
+        };
+        push_plain(&text[plain_start..offset], style, spans);
+        let inner = &text[offset + 1..chars[close].0];
+        let mut nested = style.clone();
+        match marker {
+            '*' => nested.bold = true,
+            '/' => nested.italic = true,
+            _ => nested.code = true,
+        }
+        if nested.code {
+            push_plain(inner, &nested, spans);
         } else {
-            return None;
+            push_inline(inner, &nested, spans);
         }
-    } else {
-        return None;
-    };
+        plain_start = chars[close].0 + 1;
+        idx = close + 1;
+    }
+    push_plain(&text[plain_start..], style, spans);
+}
+
+fn push_plain(text: &str, style: &TextSpan, spans: &mut Vec<TextSpan>) {
+    if text.is_empty() {
+        return;
+    }
+    if let Some(last) = spans.last_mut() {
+        let same_style = last.bold == style.bold
+            && last.italic == style.italic
+            && last.code == style.code
+            && last.link == style.link;
+        if same_style {
+            last.text.push_str(text);
+            return;
+        }
+    }
+    spans.push(TextSpan {
+        text: text.to_string(),
+        ..style.clone()
+    });
+}
+
+pub(crate) fn is_emphasis_border(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '(' | ')' | '"' | '\'' | ',' | '.' | ';' | ':' | '!' | '?' | '-' | '{' | '}'
+        )
+}
 
+/// Columns a tab advances to when measuring list indentation, as in Emacs' default `tab-width`.
+const TAB_WIDTH: usize = 8;
+
+/// Parses a list item, deriving its depth from `indents`, the indentation columns of the
+/// enclosing items. Each deeper column nests one level, whatever its width.
+fn parse_list_item(line: &SourceLine, indents: &mut Vec<usize>) -> Option<LexicalNode> {
+    let trimmed = line.text.trim_start();
+    let (ordered, rest) = split_list_marker(trimmed)?;
     let (checked, text) = parse_checkbox(rest.trim());
 
+    let column = indent_columns(&line.text[..line.text.len() - trimmed.len()]);
+    while indents.last().is_some_and(|last| *last > column) {
+        indents.pop();
+    }
+    if indents.last() != Some(&column) {
+        indents.push(column);
+    }
+
     Some(LexicalNode::ListItem {
-        depth: (indent / 2 + 1) as u32,
+        depth: indents.len() as u32,
         ordered,
         checked,
         text: text.to_string(),
+        spans: parse_inline(text),
         raw: line.text.clone(),
         line_start: line.number,
         line_end: line.number,
     })
 }
 
+/// Splits a bullet (`-`, `+`, `*`) or counter (`1.`, `1)`) off a trimmed line.
+pub(crate) fn split_list_marker(trimmed: &str) -> Option<(bool, &str)> {
+    if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("+ "))
+        .or_else(|| trimmed.strip_prefix("* "))
+    {
+        return Some((false, rest));
+    }
+    let idx = trimmed.find(['.', ')'])?;
+    let is_counter = idx > 0 && trimmed[..idx].chars().all(|c| c.is_ascii_digit());
+    is_counter.then(|| (true, trimmed[idx + 1..].trim_start()))
+}
+
+fn indent_columns(indent: &str) -> usize {
+    indent.chars().fold(0, |column, c| {
+        if c == '\t' {
+            (column / TAB_WIDTH + 1) * TAB_WIDTH
+        } else {
+            column + 1
+        }
+    })
+}
+
+/// Strips a `[ ]`, `[X]`, or partial `[-]` checkbox. Partial boxes carry no checked state.
 fn parse_checkbox(text: &str) -> (Option<bool>, &str) {
+    if let Some(rest) = text.strip_prefix("[-]") {
+        return (None, rest.trim_start());
+    }
     if let Some(rest) = text.strip_prefix("[ ]") {
         return (Some(false), rest.trim_start());
     }
@@ -496,7 +925,7 @@ fn parse_directive(line: &SourceLine) -> LexicalNode {
     }
 }
 
-fn parse_tags(content: &str) -> (String, Vec<String>) {
+pub(crate) fn parse_tags(content: &str) -> (String, Vec<String>) {
     let trimmed = content.trim_end();
     let Some(last_space) = trimmed.rfind(' ') else {
         return (trimmed.to_string(), Vec::new());
@@ -517,11 +946,28 @@ fn parse_tags(content: &str) -> (String, Vec<String>) {
     (trimmed[..last_space].trim_end().to_string(), tags)
 }
 
-fn is_todo_keyword(token: &str) -> bool {
-    token
-        .chars()
-        .all(|c| c.is_ascii_uppercase() || c == '-' || c == '_')
-        && token.chars().any(|c| c.is_ascii_alphabetic())
+/// Number of leading stars when `trimmed` is a heading line.
+fn heading_stars(trimmed: &str) -> Option<usize> {
+    let stars_len = trimmed.chars().take_while(|c| *c == '*').count();
+    (stars_len > 0
+        && trimmed
+            .chars()
+            .nth(stars_len)
+            .is_some_and(|c| c.is_whitespace()))
+    .then_some(stars_len)
+}
+
+/// The name of the drawer opened at `source[idx]`. `:LOGBOOK:` always opens one; any other
+/// `:NAME:` line only does when an `:END:` closes it before the next headline or drawer, so
+/// a stray tag-like line such as `:smile:` stays ordinary text.
+fn opens_drawer(source: &[SourceLine], idx: usize) -> Option<String> {
+    let name = drawer_name(source[idx].text.trim())?;
+    let closed = source[idx + 1..]
+        .iter()
+        .map(|line| line.text.trim())
+        .take_while(|line| heading_stars(line).is_none() && drawer_name(line).is_none())
+        .any(|line| line.eq_ignore_ascii_case(":END:"));
+    (name == "LOGBOOK" || closed).then_some(name)
 }
 
 fn drawer_name(trimmed: &str) -> Option<String> {
@@ -542,6 +988,12 @@ fn begins_block(trimmed: &str, marker: &str) -> bool {
         .is_some_and(|prefix| prefix.eq_ignore_ascii_case(marker))
 }
 
+/// `#+RESULTS:` or `#+RESULTS[hash]:`, in any case.
+fn is_results_keyword(trimmed: &str) -> bool {
+    begins_block(trimmed, "#+RESULTS")
+        && matches!(trimmed.as_bytes().get(9), Some(b':') | Some(b'['))
+}
+
 fn is_table_row(trimmed: &str) -> bool {
     trimmed.starts_with('|') && trimmed.ends_with('|') && trimmed.len() >= 2
 }
@@ -598,4 +1050,275 @@ assert!(true);
             .any(|node| matches!(node, LexicalNode::Table { rows, .. } if rows.len() == 2)));
         assert!(nodes.iter().any(|node| matches!(node, LexicalNode::CodeBlock { language: Some(lang), text, .. } if lang == "rust" && text.contains("assert"))));
     }
+
+    #[test]
+    fn round_trips_through_org_text_idempotently() {
+        let fixtures = [
+            "* TODO [#A] Ship renderer :work:mobile:\nSCHEDULED: <2025-11-03 Mon>\n:PROPERTIES:\n:ID: abc\n:END:\nFirst paragraph\nwraps here.\n\nSecond paragraph.\n",
+            "#+TITLE: Lists\n- [ ] open\n- [X] done\n  - nested\n1. first\n2) second\n   1. inner\n",
+            "** Code\n#+NAME: demo\n#+begin_src rust\nfn main() {}\n#+end_src\n| a | b |\n|---+---|\n| 1 | 2 |\n-----\n* Empty :tag:\n",
+            "",
+        ];
+        for fixture in fixtures {
+            let doc = OrgDocument::from_string("fixture.org", fixture.to_string());
+            let first = lexical_to_document(&document_to_lexical(&doc));
+            let reparsed = OrgDocument::from_string("fixture.org", first.clone());
+            let second = lexical_to_document(&document_to_lexical(&reparsed));
+            assert_eq!(first, second, "fixture: {fixture:?}");
+        }
+
+        let doc = OrgDocument::from_string("fixture.org", fixtures[1].to_string());
+        assert_eq!(
+            lexical_to_document(&document_to_lexical(&doc)),
+            "#+TITLE: Lists\n- [ ] open\n- [X] done\n  - nested\n1. first\n2. second\n  1. inner\n"
+        );
+    }
+
+    #[test]
+    fn keeps_table_formulas_attached_through_round_trip() {
+        let raw = "| a | b | sum |\n|---+---+-----|\n| 1 | 2 | 3 |\n#+TBLFM: $3=$1+$2\nAfter.\n";
+        let doc = OrgDocument::from_string("formula.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+        assert_eq!(nodes.len(), 2);
+        assert!(matches!(
+            &nodes[0],
+            LexicalNode::Table { rows, formula: Some(formula), line_end: 3, .. }
+                if formula == "$3=$1+$2" && rows.len() == 1
+        ));
+        assert_eq!(lexical_to_document(&nodes), raw);
+    }
+
+    #[test]
+    fn parses_tables_with_and_without_header_separator() {
+        let raw = "|  Name | Qty  |\n|------+-----|\n| apple |  3 | ripe |\n| pear |\n\n| a | b |\n| c |\n";
+        let doc = OrgDocument::from_string("tables.org", raw.to_string());
+        let tables: Vec<_> = document_to_lexical(&doc)
+            .into_iter()
+            .filter_map(|node| match node {
+                LexicalNode::Table { header, rows, .. } => Some((header, rows)),
+                _ => None,
+            })
+            .collect();
+        let cells = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[0].0, vec![cells(&["Name", "Qty", ""])]);
+        assert_eq!(
+            tables[0].1,
+            vec![cells(&["apple", "3", "ripe"]), cells(&["pear", "", ""])]
+        );
+        assert!(tables[1].0.is_empty());
+        assert_eq!(tables[1].1, vec![cells(&["a", "b"]), cells(&["c", ""])]);
+    }
+
+    #[test]
+    fn keeps_every_row_above_the_first_rule_as_the_header() {
+        let raw =
+            "| Name | Qty |\n| (unit) |\n|---+---|\n| apple | 3 |\n|---+---|\n| total | 3 |\n";
+        let doc = OrgDocument::from_string("headers.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+        let LexicalNode::Table { header, rows, .. } = &nodes[0] else {
+            panic!("expected a table, got {nodes:?}");
+        };
+        let cells = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            header,
+            &vec![cells(&["Name", "Qty"]), cells(&["(unit)", ""])]
+        );
+        assert_eq!(rows, &vec![cells(&["apple", "3"]), cells(&["total", "3"])]);
+        assert_eq!(lexical_to_document(&nodes), raw);
+    }
+
+    #[test]
+    fn strips_checkbox_markers_from_list_items() {
+        let raw = "- [ ] open\n- [X] done\n- [x] also done\n- [-] partial\n- plain [ ] text\n";
+        let doc = OrgDocument::from_string("checks.org", raw.to_string());
+        let items: Vec<(Option<bool>, String)> = document_to_lexical(&doc)
+            .into_iter()
+            .filter_map(|node| match node {
+                LexicalNode::ListItem { checked, text, .. } => Some((checked, text)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (Some(false), "open".to_string()),
+                (Some(true), "done".to_string()),
+                (Some(true), "also done".to_string()),
+                (None, "partial".to_string()),
+                (None, "plain [ ] text".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn nests_lists_by_relative_indentation() {
+        let depths = |raw: &str| -> Vec<u32> {
+            let doc = OrgDocument::from_string("lists.org", raw.to_string());
+            document_to_lexical(&doc)
+                .into_iter()
+                .filter_map(|node| match node {
+                    LexicalNode::ListItem { depth, .. } => Some(depth),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            depths("- top\n    - four\n        - eight\n    - four again\n- top again\n"),
+            vec![1, 2, 3, 2, 1]
+        );
+        assert_eq!(
+            depths("- top\n\t- tab\n\t\t- two tabs\n        - eight spaces\n"),
+            vec![1, 2, 3, 2]
+        );
+        assert_eq!(depths("   - indented start\n   - sibling\n"), vec![1, 1]);
+    }
+
+    #[test]
+    fn splits_inline_markup_into_spans() {
+        let span =
+            |text: &str, bold: bool, italic: bool, code: bool, link: Option<&str>| TextSpan {
+                text: text.to_string(),
+                bold,
+                italic,
+                code,
+                link: link.map(str::to_string),
+            };
+        assert_eq!(
+            parse_inline("Run =cargo test= for *really /fast/* [[https://a.dev][the *docs*]] now"),
+            vec![
+                span("Run ", false, false, false, None),
+                span("cargo test", false, false, true, None),
+                span(" for ", false, false, false, None),
+                span("really ", true, false, false, None),
+                span("fast", true, true, false, None),
+                span(" ", false, false, false, None),
+                span("the ", false, false, false, Some("https://a.dev")),
+                span("docs", true, false, false, Some("https://a.dev")),
+                span(" now", false, false, false, None),
+            ]
+        );
+        assert_eq!(
+            parse_inline("* not bold, 2*3*4 and ~a/b~"),
+            vec![
+                span("* not bold, 2*3*4 and ", false, false, false, None),
+                span("a/b", false, false, true, None),
+            ]
+        );
+
+        let doc = OrgDocument::from_string("spans.org", "- item with /style/\n".to_string());
+        assert!(matches!(
+            &document_to_lexical(&doc)[0],
+            LexicalNode::ListItem { spans, .. } if spans.len() == 2 && spans[1].italic
+        ));
+    }
+
+    #[test]
+    fn keeps_multi_line_source_blocks_with_language_and_indentation() {
+        let raw = "#+Begin_Src rust :results silent\nfn main() {\n    let total = 1 + 2;\n\n    println!(\"{total}\");\n}\n#+END_src\n";
+        let doc = OrgDocument::from_string("code.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+        assert_eq!(nodes.len(), 1);
+        assert!(matches!(
+            &nodes[0],
+            LexicalNode::CodeBlock { language: Some(language), text, line_start: 0, line_end: 6, .. }
+                if language == "rust"
+                    && text == "fn main() {\n    let total = 1 + 2;\n\n    println!(\"{total}\");\n}"
+        ));
+    }
+
+    #[test]
+    fn keeps_example_blocks_verbatim() {
+        let raw = "#+begin_example -n\n  indented line\n\n*not a heading*\n#+END_SRC\n#+end_example\nAfter.\n";
+        let doc = OrgDocument::from_string("example.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+        assert!(matches!(
+            &nodes[0],
+            LexicalNode::CodeBlock { language: None, text, line_end: 5, .. }
+                if text == "  indented line\n\n*not a heading*\n#+END_SRC"
+        ));
+        assert!(matches!(&nodes[1], LexicalNode::Paragraph { text, .. } if text == "After."));
+    }
+
+    #[test]
+    fn keeps_line_breaks_in_verse_blocks() {
+        let raw = "#+begin_verse\nGreat clouds overhead\n  Tiny black birds rise and fall\nSnow covers Emacs\n#+end_verse\nAfter.\n";
+        let doc = OrgDocument::from_string("verse.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+        assert!(matches!(
+            &nodes[0],
+            LexicalNode::Verse { lines, line_end: 4, .. } if lines == &[
+                "Great clouds overhead",
+                "  Tiny black birds rise and fall",
+                "Snow covers Emacs",
+            ]
+        ));
+        assert!(matches!(&nodes[1], LexicalNode::Paragraph { text, .. } if text == "After."));
+        assert_eq!(lexical_to_document(&nodes), raw);
+    }
+
+    #[test]
+    fn separates_babel_results_from_prose() {
+        let raw = "#+begin_src sh\necho hi\n#+end_src\n#+RESULTS: greet\n: hi\n: there\nSome prose.\n#+results:\n#+begin_example\nline one\n#+end_example\nMore prose.\n";
+        let doc = OrgDocument::from_string("results.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+
+        assert_eq!(nodes.len(), 5);
+        assert!(matches!(
+            &nodes[1],
+            LexicalNode::Result { name: Some(name), text, line_start: 3, line_end: 5, .. }
+                if name == "greet" && text == "hi\nthere"
+        ));
+        assert!(matches!(&nodes[2], LexicalNode::Paragraph { text, .. } if text == "Some prose."));
+        assert!(matches!(
+            &nodes[3],
+            LexicalNode::Result { name: None, text, line_start: 7, line_end: 10, .. }
+                if text == "line one"
+        ));
+        assert!(matches!(&nodes[4], LexicalNode::Paragraph { text, .. } if text == "More prose."));
+        assert_eq!(lexical_to_document(&nodes), raw);
+    }
+
+    #[test]
+    fn attaches_name_directive_to_following_code_block() {
+        let raw = "#+NAME: fig1\n#+begin_src python\nprint(1)\n#+end_src\n#+NAME: orphan\n\n#+begin_src sh\nls\n#+end_src\n";
+        let doc = OrgDocument::from_string("named.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+
+        assert!(matches!(
+            &nodes[0],
+            LexicalNode::CodeBlock { name: Some(name), line_start: 0, raw, .. }
+                if name == "fig1" && raw.starts_with("#+NAME: fig1")
+        ));
+        assert!(matches!(&nodes[1], LexicalNode::Directive { keyword, .. } if keyword == "NAME"));
+        assert!(matches!(
+            &nodes[2],
+            LexicalNode::CodeBlock { name: None, .. }
+        ));
+    }
+
+    #[test]
+    fn only_opens_drawers_that_are_closed_before_the_next_heading() {
+        let raw = "* Journal\nGreat day\n:smile:\nMore text\n:NOTES:\nprivate\n:END:\n:LOGBOOK:\n* Next\nBody\n";
+        let doc = OrgDocument::from_string("drawers.org", raw.to_string());
+        let nodes = document_to_lexical(&doc);
+
+        assert!(matches!(
+            &nodes[1],
+            LexicalNode::Paragraph { raw, .. } if raw == "Great day\n:smile:\nMore text"
+        ));
+        assert!(matches!(
+            &nodes[2],
+            LexicalNode::Drawer { name, text, .. } if name == "NOTES" && text == "private"
+        ));
+        assert!(matches!(
+            &nodes[3],
+            LexicalNode::Drawer { name, line_end: 7, .. } if name == "LOGBOOK"
+        ));
+        assert!(matches!(&nodes[4], LexicalNode::Heading { text, .. } if text == "Next"));
+        assert_eq!(nodes.len(), 6);
+    }
 }
@@ -1,8 +1,15 @@
 pub mod agenda;
+pub mod agenda_days;
+pub mod capture;
 pub mod document;
+pub mod error;
 pub mod habit;
 pub mod lexical;
+pub mod markdown;
 pub mod notifications;
 pub mod service;
 
+pub use crate::agenda::{parse_and_validate_timestamp, TimestampError};
+pub use crate::capture::expand_capture_template;
+pub use crate::error::OrgError;
 pub use crate::service::{OrgService, OrgServiceBuilder};
@@ -0,0 +1,425 @@
+use std::collections::HashMap;
+
+use crate::lexical::{is_emphasis_border, LexicalNode};
+
+/// Maps `#+begin_src` language names to the fence info strings Markdown highlighters expect,
+/// e.g. `emacs-lisp` to `lisp`. Names without an alias are used as written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageAliases {
+    aliases: HashMap<String, String>,
+}
+
+impl Default for LanguageAliases {
+    fn default() -> Self {
+        let pairs = [
+            ("emacs-lisp", "lisp"),
+            ("elisp", "lisp"),
+            ("sh", "bash"),
+            ("shell", "bash"),
+            ("js", "javascript"),
+            ("ts", "typescript"),
+            ("C", "c"),
+            ("C++", "cpp"),
+            ("ipython", "python"),
+        ];
+        Self {
+            aliases: pairs
+                .into_iter()
+                .map(|(org, fence)| (org.to_string(), fence.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl LanguageAliases {
+    /// A map without the built-in aliases.
+    pub fn empty() -> Self {
+        Self {
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Adds or replaces the alias for the org language `org`.
+    pub fn with_alias(mut self, org: &str, fence: &str) -> Self {
+        self.aliases.insert(org.to_string(), fence.to_string());
+        self
+    }
+
+    /// The fence language for the org language `org`; names without an alias come back as is.
+    pub fn fence_language<'a>(&'a self, org: &'a str) -> &'a str {
+        self.aliases.get(org).map(String::as_str).unwrap_or(org)
+    }
+}
+
+/// Renders lexical blocks as Markdown with the default [`LanguageAliases`]. Planning lines,
+/// drawers, and directives are metadata rather than prose, so they are omitted.
+pub fn lexical_to_markdown(nodes: &[LexicalNode]) -> String {
+    lexical_to_markdown_with(nodes, &LanguageAliases::default())
+}
+
+/// [`lexical_to_markdown`] with `aliases` naming the fence language of each code block.
+pub fn lexical_to_markdown_with(nodes: &[LexicalNode], aliases: &LanguageAliases) -> String {
+    let mut blocks: Vec<String> = Vec::new();
+    let mut previous_was_list = false;
+
+    for node in nodes {
+        let rendered = match node {
+            LexicalNode::Heading {
+                depth,
+                text,
+                todo_keyword,
+                ..
+            } => {
+                let hashes = "#".repeat((*depth).clamp(1, 6) as usize);
+                match todo_keyword {
+                    Some(keyword) => format!("{} {} {}", hashes, keyword, inline_to_markdown(text)),
+                    None => format!("{} {}", hashes, inline_to_markdown(text)),
+                }
+            }
+            LexicalNode::Paragraph { text, .. } => inline_to_markdown(text),
+            LexicalNode::ListItem {
+                depth,
+                ordered,
+                checked,
+                text,
+                ..
+            } => {
+                let indent = "  ".repeat(depth.saturating_sub(1) as usize);
+                let bullet = if *ordered { "1." } else { "-" };
+                let checkbox = match checked {
+                    Some(true) => "[x] ",
+                    Some(false) => "[ ] ",
+                    None => "",
+                };
+                let line = format!(
+                    "{}{} {}{}",
+                    indent,
+                    bullet,
+                    checkbox,
+                    inline_to_markdown(text)
+                );
+                if previous_was_list {
+                    if let Some(last) = blocks.last_mut() {
+                        last.push('\n');
+                        last.push_str(&line);
+                        continue;
+                    }
+                }
+                previous_was_list = true;
+                blocks.push(line);
+                continue;
+            }
+            LexicalNode::CodeBlock { language, text, .. } => {
+                let language = language.as_deref().unwrap_or("");
+                format!("```{}\n{}\n```", aliases.fence_language(language), text)
+            }
+            LexicalNode::Verse { lines, .. } => lines
+                .iter()
+                .map(|line| inline_to_markdown(line.trim()))
+                .collect::<Vec<_>>()
+                .join("  \n"),
+            LexicalNode::Result { text, .. } => format!("```\n{}\n```", text),
+            LexicalNode::Table { header, rows, .. } => table_to_markdown(header, rows),
+            LexicalNode::HorizontalRule { .. } => "---".to_string(),
+            LexicalNode::Planning { .. }
+            | LexicalNode::PropertyDrawer { .. }
+            | LexicalNode::Drawer { .. }
+            | LexicalNode::Directive { .. } => continue,
+        };
+        previous_was_list = false;
+        if !rendered.is_empty() {
+            blocks.push(rendered);
+        }
+    }
+
+    let mut output = blocks.join("\n\n");
+    output.push('\n');
+    output
+}
+
+fn table_to_markdown(header: &[Vec<String>], rows: &[Vec<String>]) -> String {
+    // Markdown tables have exactly one header row: the first org header row, or the first row
+    // when there is none. Any further header rows follow it as body rows.
+    let mut all_rows = header.iter().chain(rows);
+    let Some(first) = all_rows.next() else {
+        return String::new();
+    };
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = row.iter().map(|cell| inline_to_markdown(cell)).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = vec![render_row(first)];
+    lines.push(format!("|{}", " --- |".repeat(first.len())));
+    lines.extend(all_rows.map(|row| render_row(row)));
+    lines.join("\n")
+}
+
+/// Converts org inline markup (links and emphasis) to its Markdown equivalent.
+pub fn inline_to_markdown(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let Some(end) = rest[start..].find("]]") else {
+            break;
+        };
+        output.push_str(&emphasis_to_markdown(&rest[..start]));
+        let inner = &rest[start + 2..start + end];
+        let (target, label) = inner.split_once("][").unwrap_or((inner, inner));
+        output.push_str(&format!("[{}]({})", emphasis_to_markdown(label), target));
+        rest = &rest[start + end + 2..];
+    }
+    output.push_str(&emphasis_to_markdown(rest));
+    output
+}
+
+fn emphasis_to_markdown(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let marker = chars[idx];
+        let replacement = match marker {
+            '*' => Some("**"),
+            '/' => Some("*"),
+            '=' | '~' => Some("`"),
+            '+' => Some("~~"),
+            _ => None,
+        };
+        let opens = replacement.is_some()
+            && (idx == 0 || is_emphasis_border(chars[idx - 1]))
+            && chars.get(idx + 1).is_some_and(|c| !c.is_whitespace());
+        if let (true, Some(replacement)) = (opens, replacement) {
+            let close = (idx + 2..chars.len()).find(|&pos| {
+                chars[pos] == marker
+                    && !chars[pos - 1].is_whitespace()
+                    && chars.get(pos + 1).is_none_or(|c| is_emphasis_border(*c))
+            });
+            if let Some(close) = close {
+                let inner: String = chars[idx + 1..close].iter().collect();
+                output.push_str(replacement);
+                output.push_str(&inner);
+                output.push_str(replacement);
+                idx = close + 1;
+                continue;
+            }
+        }
+        output.push(marker);
+        idx += 1;
+    }
+    output
+}
+
+/// Converts Markdown to org markup: ATX headings become `*` headlines, fenced code becomes
+/// src (or, without a language, example) blocks, and inline links and emphasis are rewritten.
+/// List items keep their checkboxes; `*`/`+` bullets become `-` so they are not read as
+/// headlines.
+pub fn markdown_to_org(text: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut fence: Option<&'static str> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if let Some(end_marker) = fence {
+            if trimmed.starts_with("```") {
+                lines.push(end_marker.to_string());
+                fence = None;
+            } else {
+                lines.push(line.to_string());
+            }
+            continue;
+        }
+        if let Some(info) = trimmed.strip_prefix("```") {
+            let language = info.split_whitespace().next().unwrap_or("");
+            if language.is_empty() {
+                lines.push("#+begin_example".to_string());
+                fence = Some("#+end_example");
+            } else {
+                lines.push(format!("#+begin_src {}", language));
+                fence = Some("#+end_src");
+            }
+            continue;
+        }
+
+        let hashes = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+            let title = line[hashes..].trim().trim_end_matches('#').trim_end();
+            lines.push(format!(
+                "{} {}",
+                "*".repeat(hashes),
+                markdown_inline_to_org(title)
+            ));
+            continue;
+        }
+        if matches!(trimmed, "---" | "***" | "___") {
+            lines.push("-----".to_string());
+            continue;
+        }
+
+        let indent = &line[..line.len() - trimmed.len()];
+        let bullet_rest = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .or_else(|| trimmed.strip_prefix("+ "));
+        match bullet_rest {
+            Some(rest) => lines.push(format!("{}- {}", indent, markdown_inline_to_org(rest))),
+            None => lines.push(format!("{}{}", indent, markdown_inline_to_org(trimmed))),
+        }
+    }
+    if let Some(end_marker) = fence {
+        lines.push(end_marker.to_string());
+    }
+
+    let mut output = lines.join("\n");
+    output.push('\n');
+    output
+}
+
+/// Converts Markdown inline links, code, and emphasis to org markup.
+fn markdown_inline_to_org(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let converted = match c {
+            '[' => markdown_link(rest),
+            '`' => wrap_span(rest, "`", "=", false),
+            '*' if rest.starts_with("**") => wrap_span(rest, "**", "*", true),
+            '~' if rest.starts_with("~~") => wrap_span(rest, "~~", "+", true),
+            '*' | '_'
+                if output
+                    .chars()
+                    .last()
+                    .is_none_or(|prev| !prev.is_alphanumeric()) =>
+            {
+                wrap_span(rest, &rest[..1], "/", true)
+            }
+            _ => None,
+        };
+        match converted {
+            Some((rendered, consumed)) => {
+                output.push_str(&rendered);
+                rest = &rest[consumed..];
+            }
+            None => {
+                output.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    output
+}
+
+/// Rewrites a `[label](url)` link at the start of `text` as `[[url][label]]`.
+fn markdown_link(text: &str) -> Option<(String, usize)> {
+    let label_end = text.find("](")?;
+    let url_end = label_end + 2 + text[label_end + 2..].find(')')?;
+    let label = &text[1..label_end];
+    let url = &text[label_end + 2..url_end];
+    if label.contains('[') || url.contains(char::is_whitespace) {
+        return None;
+    }
+    let rendered = if label.is_empty() || label == url {
+        format!("[[{}]]", url)
+    } else {
+        format!("[[{}][{}]]", url, markdown_inline_to_org(label))
+    };
+    Some((rendered, url_end + 1))
+}
+
+/// Rewrites a span delimited by `marker` at the start of `text` with org's `replacement`.
+fn wrap_span(text: &str, marker: &str, replacement: &str, nested: bool) -> Option<(String, usize)> {
+    let inner_start = marker.len();
+    let inner_len = text[inner_start..].find(marker)?;
+    let inner = &text[inner_start..inner_start + inner_len];
+    let end = inner_start + inner_len + marker.len();
+    let closes_word = text[end..]
+        .chars()
+        .next()
+        .is_none_or(|next| !next.is_alphanumeric());
+    if inner.is_empty() || inner.starts_with(' ') || inner.ends_with(' ') || !closes_word {
+        return None;
+    }
+    let inner = if nested {
+        markdown_inline_to_org(inner)
+    } else {
+        inner.to_string()
+    };
+    Some((format!("{}{}{}", replacement, inner, replacement), end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{document::OrgDocument, lexical::document_to_lexical};
+
+    #[test]
+    fn exports_headings_checkbox_lists_and_code_blocks() {
+        let raw = r#"#+TITLE: Export
+* TODO Plan *release* notes :work:
+SCHEDULED: <2025-11-03 Mon>
+See [[https://example.com][the /docs/]] and =cargo test=.
+- [ ] write changelog
+- [X] tag build
+  - nested detail
+#+BEGIN_SRC rust
+fn main() {}
+#+END_SRC
+"#;
+        let doc = OrgDocument::from_string("export.org", raw.to_string());
+        let markdown = lexical_to_markdown(&document_to_lexical(&doc));
+        assert_eq!(
+            markdown,
+            "# TODO Plan **release** notes\n\n\
+             See [the *docs*](https://example.com) and `cargo test`.\n\n\
+             - [ ] write changelog\n- [x] tag build\n  - nested detail\n\n\
+             ```rust\nfn main() {}\n```\n"
+        );
+    }
+
+    #[test]
+    fn converts_markdown_headings_lists_and_fences_to_org() {
+        let markdown = "# Project **Atlas**\nSee [the docs](https://example.com) and `cargo run`.\n\n## Tasks\n- [ ] write *intro*\n* [x] set_up CI\n  + nested\n\n```rust\n# not a heading\nfn main() {}\n```\n```\nplain\n```\n";
+        assert_eq!(
+            markdown_to_org(markdown),
+            "* Project *Atlas*\n\
+             See [[https://example.com][the docs]] and =cargo run=.\n\n\
+             ** Tasks\n\
+             - [ ] write /intro/\n\
+             - [x] set_up CI\n  - nested\n\n\
+             #+begin_src rust\n# not a heading\nfn main() {}\n#+end_src\n\
+             #+begin_example\nplain\n#+end_example\n"
+        );
+    }
+
+    #[test]
+    fn exports_extra_header_rows_below_the_markdown_header() {
+        let raw = "| Name | Qty |\n| (unit) |\n|---+---|\n| apple | 3 |\n";
+        let doc = OrgDocument::from_string("table.org", raw.to_string());
+        assert_eq!(
+            lexical_to_markdown(&document_to_lexical(&doc)),
+            "| Name | Qty |\n| --- | --- |\n| (unit) |  |\n| apple | 3 |\n"
+        );
+    }
+
+    #[test]
+    fn code_block_languages_export_through_aliases() {
+        let raw = "#+begin_src emacs-lisp\n(message \"hi\")\n#+end_src\n\n#+begin_src haskell\nmain = pure ()\n#+end_src\n";
+        let nodes = document_to_lexical(&OrgDocument::from_string("alias.org", raw.to_string()));
+        let fences: Vec<_> = nodes
+            .iter()
+            .filter_map(|node| match node {
+                LexicalNode::CodeBlock { fence_language, .. } => fence_language.as_deref(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fences, ["lisp", "haskell"]);
+
+        let markdown = lexical_to_markdown(&nodes);
+        assert!(markdown.contains("```lisp\n(message \"hi\")\n```"));
+        assert!(markdown.contains("```haskell\n"));
+
+        let custom = LanguageAliases::empty().with_alias("haskell", "hs");
+        let markdown = lexical_to_markdown_with(&nodes, &custom);
+        assert!(markdown.contains("```emacs-lisp\n"));
+        assert!(markdown.contains("```hs\n"));
+    }
+}
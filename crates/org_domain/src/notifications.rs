@@ -1,10 +1,18 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
 use chrono::{DateTime, Utc};
+use parking_lot::{Condvar, Mutex};
 use serde::{Deserialize, Serialize};
 
 use crate::{agenda::AgendaItem, habit::Habit};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationRequest {
+    /// Identifies the habit or agenda item the request is for; see
+    /// [`habit_notification_key`] and [`agenda_notification_key`].
+    pub key: String,
     pub title: String,
     pub body: String,
     pub scheduled_for: DateTime<Utc>,
@@ -13,6 +21,231 @@ pub struct NotificationRequest {
 /// Platform-specific notification adapters will implement this trait.
 pub trait NotificationSink: Send + Sync {
     fn schedule(&self, notification: NotificationRequest);
-    fn clear_for_habit(&self, habit: &Habit);
+    /// Cancels the pending reminders for `habit` in the document at `path`.
+    fn clear_for_habit(&self, path: &Path, habit: &Habit);
     fn clear_for_agenda_item(&self, item: &AgendaItem);
 }
+
+/// Title the service gives reminders for `habit`.
+pub fn habit_notification_title(habit: &Habit) -> String {
+    format!("Habit: {}", habit.title)
+}
+
+/// Key of the reminders for `habit` in the document at `path`.
+pub fn habit_notification_key(path: &Path, habit: &Habit) -> String {
+    format!("habit:{}:{}", path.display(), habit.title)
+}
+
+/// Key of the reminders for `item`: its file and headline line, so headlines that share a
+/// title keep separate reminders.
+pub fn agenda_notification_key(item: &AgendaItem) -> String {
+    format!("agenda:{}:{}", item.path.display(), item.headline_line)
+}
+
+type Deliver = dyn Fn(&NotificationRequest) + Send + Sync;
+
+/// A sink that hands each request to `deliver` at `scheduled_for` from a single scheduler
+/// thread, for platforms where the OS has no scheduled-notification API of its own.
+///
+/// Pending requests are tracked by their `key`. Clearing a habit or agenda item cancels every
+/// request under its key that has not fired.
+/// The scheduler thread sleeps until the earliest pending request and is woken early by new
+/// requests, cancellations, and dropping the sink.
+pub struct TimerNotificationSink {
+    shared: Arc<(Mutex<TimerQueue>, Condvar)>,
+}
+
+#[derive(Default)]
+struct TimerQueue {
+    pending: Vec<NotificationRequest>,
+    stopped: bool,
+}
+
+impl TimerNotificationSink {
+    pub fn new(deliver: impl Fn(&NotificationRequest) + Send + Sync + 'static) -> Self {
+        let shared = Arc::new((Mutex::new(TimerQueue::default()), Condvar::new()));
+        let deliver: Arc<Deliver> = Arc::new(deliver);
+        let worker = shared.clone();
+        thread::spawn(move || run_timer(&worker, deliver.as_ref()));
+        Self { shared }
+    }
+
+    /// Number of requests still waiting to fire.
+    pub fn pending_count(&self) -> usize {
+        self.shared.0.lock().pending.len()
+    }
+
+    fn cancel(&self, key: &str) {
+        let (queue, wake) = &*self.shared;
+        queue.lock().pending.retain(|request| request.key != key);
+        wake.notify_one();
+    }
+}
+
+impl Drop for TimerNotificationSink {
+    fn drop(&mut self) {
+        let (queue, wake) = &*self.shared;
+        queue.lock().stopped = true;
+        wake.notify_one();
+    }
+}
+
+/// The scheduler loop: delivers every due request outside the lock, then waits until the
+/// next one is due or the queue changes.
+fn run_timer(shared: &(Mutex<TimerQueue>, Condvar), deliver: &Deliver) {
+    let (queue, wake) = shared;
+    let mut guard = queue.lock();
+    while !guard.stopped {
+        let now = Utc::now();
+        let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut guard.pending)
+            .into_iter()
+            .partition(|request| request.scheduled_for <= now);
+        guard.pending = waiting;
+        if !due.is_empty() {
+            drop(guard);
+            for request in &due {
+                deliver(request);
+            }
+            guard = queue.lock();
+            continue;
+        }
+        match guard
+            .pending
+            .iter()
+            .map(|request| request.scheduled_for)
+            .min()
+        {
+            Some(next) => {
+                let delay = (next - now).to_std().unwrap_or_default();
+                wake.wait_for(&mut guard, delay);
+            }
+            None => wake.wait(&mut guard),
+        }
+    }
+}
+
+impl NotificationSink for TimerNotificationSink {
+    fn schedule(&self, notification: NotificationRequest) {
+        let (queue, wake) = &*self.shared;
+        queue.lock().pending.push(notification);
+        wake.notify_one();
+    }
+
+    fn clear_for_habit(&self, path: &Path, habit: &Habit) {
+        self.cancel(&habit_notification_key(path, habit));
+    }
+
+    fn clear_for_agenda_item(&self, item: &AgendaItem) {
+        self.cancel(&agenda_notification_key(item));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{agenda::build_agenda, document::OrgDocument};
+    use chrono::Duration;
+    use std::sync::mpsc;
+
+    fn request(key: &str, title: &str, in_ms: i64) -> NotificationRequest {
+        NotificationRequest {
+            key: key.to_string(),
+            title: title.to_string(),
+            body: String::new(),
+            scheduled_for: Utc::now() + Duration::milliseconds(in_ms),
+        }
+    }
+
+    #[test]
+    fn fires_due_requests_and_drops_cleared_ones() {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let sink = TimerNotificationSink::new(move |request| {
+            let _ = tx.lock().send(request.title.clone());
+        });
+        let habit = Habit {
+            title: "Stretch".to_string(),
+            scheduled: None,
+            description: String::new(),
+            repeater: None,
+            log_entries: Vec::new(),
+            last_repeat: None,
+        };
+
+        let path = Path::new("habits.org");
+        sink.schedule(request("rent", "Pay rent", -1_000));
+        sink.schedule(request(
+            &habit_notification_key(path, &habit),
+            &habit_notification_title(&habit),
+            300,
+        ));
+        sink.clear_for_habit(path, &habit);
+
+        let fired = rx
+            .recv_timeout(std::time::Duration::from_secs(2))
+            .expect("due notification");
+        assert_eq!(fired, "Pay rent");
+        assert!(rx
+            .recv_timeout(std::time::Duration::from_millis(600))
+            .is_err());
+        assert_eq!(sink.pending_count(), 0);
+    }
+
+    #[test]
+    fn new_due_requests_wake_a_scheduler_waiting_on_a_later_one() {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let sink = TimerNotificationSink::new(move |request| {
+            let _ = tx.lock().send(request.title.clone());
+        });
+
+        sink.schedule(request("later", "Next week", 7 * 24 * 3_600_000));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        sink.schedule(request("now", "Now", 0));
+
+        let fired = rx
+            .recv_timeout(std::time::Duration::from_secs(2))
+            .expect("due notification");
+        assert_eq!(fired, "Now");
+        assert_eq!(sink.pending_count(), 1);
+    }
+
+    #[test]
+    fn clearing_an_item_leaves_same_titled_items_and_habits_scheduled() {
+        let sink = TimerNotificationSink::new(|_| {});
+        let raw = "* TODO Habit: Stretch\nSCHEDULED: <2025-03-14 Fri>\n";
+        let docs: Vec<_> = ["a.org", "b.org"]
+            .into_iter()
+            .map(|name| {
+                let path = std::path::PathBuf::from(name);
+                let doc = OrgDocument::from_string(&path, raw.to_string());
+                (path, doc)
+            })
+            .collect();
+        let items = build_agenda(&docs);
+        let habit = Habit {
+            title: "Stretch".to_string(),
+            scheduled: None,
+            description: String::new(),
+            repeater: None,
+            log_entries: Vec::new(),
+            last_repeat: None,
+        };
+        let later = 7 * 24 * 3_600_000;
+        for item in &items {
+            sink.schedule(request(&agenda_notification_key(item), &item.title, later));
+        }
+        sink.schedule(request(
+            &habit_notification_key(Path::new("a.org"), &habit),
+            &habit_notification_title(&habit),
+            later,
+        ));
+
+        sink.clear_for_agenda_item(&items[0]);
+        assert_eq!(sink.pending_count(), 2);
+        sink.clear_for_habit(Path::new("b.org"), &habit);
+        assert_eq!(sink.pending_count(), 2);
+        sink.clear_for_habit(Path::new("a.org"), &habit);
+        assert_eq!(sink.pending_count(), 1);
+    }
+}
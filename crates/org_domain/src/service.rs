@@ -1,20 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
 
-use anyhow::{anyhow, Result};
-use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+use anyhow::Result;
+use chrono::{
+    DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use orgize::{Org, ParseConfig};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use crate::{
     agenda,
+    agenda_days::{self, DayGroup, DaySpan},
     document::OrgDocument,
+    error::OrgError,
     habit, lexical,
-    notifications::{NotificationRequest, NotificationSink},
+    markdown::{self, LanguageAliases},
+    notifications::{self, NotificationRequest, NotificationSink},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -23,16 +33,153 @@ pub struct AgendaSnapshot {
     pub habits: Vec<habit::Habit>,
 }
 
+/// Per-tag totals over the vault's headlines; see [`OrgService::stats_by_tag`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TagStats {
+    pub item_count: usize,
+    pub done_count: usize,
+    pub clocked: Duration,
+}
+
+/// A headline located somewhere in the loaded vault.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HeadlineRef {
+    pub path: PathBuf,
+    pub line: usize,
+    pub title: String,
+    pub todo_keyword: Option<String>,
+    #[serde(default)]
+    pub depth: u32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Lines below the headline that belong to its subtree, children included.
+    #[serde(default)]
+    pub body_range: Range<usize>,
+}
+
+/// Orders for [`OrgService::list_documents_sorted`]. Ties fall back to path order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentSort {
+    #[default]
+    Path,
+    /// Most recently modified first; documents with no known modification time come last.
+    ModifiedDesc,
+    ModifiedAsc,
+    /// By `#+TITLE:`, or the file stem when there is none, ignoring case.
+    TitleAsc,
+}
+
+/// Where an org link points: a document and, for links with a `::` search option such as
+/// `::*Heading` or `::#custom-id`, the line of the headline it names.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LinkTarget {
+    pub path: PathBuf,
+    pub headline_line: Option<usize>,
+}
+
+/// A line containing a full-text search match, with the headline it falls under.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    pub line: usize,
+    pub snippet: String,
+    pub headline: Option<String>,
+}
+
+/// The priority cookies headlines may carry, from `highest` to `lowest` (org's
+/// `org-priority-highest`/`-lowest`/`-default`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PriorityRange {
+    pub highest: char,
+    pub lowest: char,
+    pub default: char,
+}
+
+impl Default for PriorityRange {
+    fn default() -> Self {
+        Self {
+            highest: 'A',
+            lowest: 'C',
+            default: 'B',
+        }
+    }
+}
+
+/// Source formats `OrgService::import_text` can convert from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ImportFormat {
+    Markdown,
+}
+
+/// A filesystem change to an org document under one of the service roots.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChangeEvent {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Deleted(PathBuf),
+}
+
+impl ChangeEvent {
+    fn from_notify(
+        event: notify::Event,
+        extensions: &[String],
+    ) -> impl Iterator<Item = ChangeEvent> + '_ {
+        let make: Option<fn(PathBuf) -> ChangeEvent> = match event.kind {
+            notify::EventKind::Create(_) => Some(ChangeEvent::Created),
+            notify::EventKind::Modify(_) => Some(ChangeEvent::Modified),
+            notify::EventKind::Remove(_) => Some(ChangeEvent::Deleted),
+            _ => None,
+        };
+        event
+            .paths
+            .into_iter()
+            .filter(|path| extension_matches(extensions, path))
+            .filter_map(move |path| make.map(|make| make(path)))
+    }
+}
+
+/// How many earlier versions of each document `undo_document` can step back through.
+const UNDO_DEPTH: usize = 20;
+
+/// Days before a deadline without its own `-Nd` warning that reminders start, as in org's
+/// `org-deadline-warning-days`.
+const DEADLINE_WARNING_DAYS: u64 = 14;
+
 pub struct OrgService {
     roots: Vec<PathBuf>,
     documents: RwLock<HashMap<PathBuf, OrgDocument>>,
+    /// Raw contents replaced by `update_document`, most recent last.
+    history: RwLock<HashMap<PathBuf, VecDeque<String>>>,
+    /// Habits extracted per document, with the content hash they were extracted from.
+    habit_cache: RwLock<HashMap<PathBuf, (u64, Vec<habit::Habit>)>>,
+    habit_extractions: AtomicUsize,
+    loaded: AtomicBool,
     watcher: Option<RecommendedWatcher>,
     notification_sink: Option<Box<dyn NotificationSink>>,
+    priorities: PriorityRange,
+    extensions: Vec<String>,
+    /// Time of day reminders fire for entries without a time of their own.
+    reminder_time: NaiveTime,
+    language_aliases: LanguageAliases,
+    /// Directory of daily notes inside each root, relative to it.
+    dailies_dir: PathBuf,
 }
 
 pub struct OrgServiceBuilder {
     roots: Vec<PathBuf>,
     notification_sink: Option<Box<dyn NotificationSink>>,
+    priorities: PriorityRange,
+    extensions: Vec<String>,
+    reminder_time: NaiveTime,
+    language_aliases: LanguageAliases,
+    dailies_dir: PathBuf,
+}
+
+impl Default for OrgServiceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl OrgServiceBuilder {
@@ -40,6 +187,11 @@ impl OrgServiceBuilder {
         Self {
             roots: Vec::new(),
             notification_sink: None,
+            priorities: PriorityRange::default(),
+            extensions: vec!["org".to_string()],
+            reminder_time: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            language_aliases: LanguageAliases::default(),
+            dailies_dir: PathBuf::from("daily"),
         }
     }
 
@@ -48,7 +200,7 @@ impl OrgServiceBuilder {
     }
 
     pub fn add_document_root(mut self, path: impl AsRef<Path>) -> Self {
-        Self::push_unique(&mut self.roots, path.as_ref().to_path_buf());
+        Self::push_unique(&mut self.roots, canonical_path(path.as_ref()));
         self
     }
 
@@ -57,15 +209,68 @@ impl OrgServiceBuilder {
         self
     }
 
+    /// Also treats files with these extensions (for example `"txt"`) as org documents.
+    pub fn with_extensions(mut self, extensions: &[&str]) -> Self {
+        for extension in extensions {
+            let extension = extension.trim_start_matches('.').to_lowercase();
+            if !extension.is_empty() && !self.extensions.contains(&extension) {
+                self.extensions.push(extension);
+            }
+        }
+        self
+    }
+
+    /// Hour (0-23) at which habit and deadline reminders without a time fire; 9 by default.
+    pub fn with_reminder_hour(mut self, hour: u32) -> Self {
+        if let Some(time) = NaiveTime::from_hms_opt(hour, 0, 0) {
+            self.reminder_time = time;
+        }
+        self
+    }
+
+    pub fn with_priority_range(mut self, priorities: PriorityRange) -> Self {
+        self.priorities = priorities;
+        self
+    }
+
+    /// Fence languages used for source blocks by `lexical_nodes` and `export_markdown`.
+    pub fn with_language_aliases(mut self, aliases: LanguageAliases) -> Self {
+        self.language_aliases = aliases;
+        self
+    }
+
+    /// Directory inside a root that holds daily notes for `find_or_create_daily`; `daily` by
+    /// default, as in org-roam-dailies.
+    pub fn with_dailies_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.dailies_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
     pub fn build(self) -> Result<OrgService> {
-        let service = OrgService {
+        let service = self.build_lazy();
+        service.reload_all()?;
+        Ok(service)
+    }
+
+    /// Builds the service without scanning its roots. Documents are loaded by the first
+    /// agenda, habit, or document query, or by an explicit `reload_all`; until then
+    /// `list_documents` is empty.
+    pub fn build_lazy(self) -> OrgService {
+        OrgService {
             roots: self.roots,
             documents: RwLock::new(HashMap::new()),
+            history: RwLock::new(HashMap::new()),
+            habit_cache: RwLock::new(HashMap::new()),
+            habit_extractions: AtomicUsize::new(0),
+            loaded: AtomicBool::new(false),
             watcher: None,
             notification_sink: self.notification_sink,
-        };
-        service.reload_all()?;
-        Ok(service)
+            priorities: self.priorities,
+            extensions: self.extensions,
+            reminder_time: self.reminder_time,
+            language_aliases: self.language_aliases,
+            dailies_dir: self.dailies_dir,
+        }
     }
 
     fn push_unique(vec: &mut Vec<PathBuf>, path: PathBuf) {
@@ -87,147 +292,679 @@ impl OrgService {
     }
 
     pub fn add_document_root(&mut self, path: PathBuf) -> Result<()> {
+        let path = canonical_path(&path);
         if self.roots.contains(&path) {
             return Ok(());
         }
         self.roots.push(path.clone());
         {
             let mut docs = self.documents.write();
-            self.ingest_root(&mut docs, &path)?;
+            self.ingest_root(&mut docs, &path, &mut HashMap::new())?;
         }
         self.watch_path(&path)?;
         Ok(())
     }
 
+    /// Rescans every root, re-reading only files whose modification time changed.
     pub fn reload_all(&self) -> Result<()> {
+        self.reload(false)
+    }
+
+    /// Rescans every root. With `force` set every file is re-read even if its modification
+    /// time is unchanged, for callers that know the cache is stale.
+    pub fn reload(&self, force: bool) -> Result<()> {
         let mut docs = self.documents.write();
+        let mut cached = if force {
+            HashMap::new()
+        } else {
+            std::mem::take(&mut *docs)
+        };
         docs.clear();
         for root in self.unique_roots() {
-            self.ingest_root(&mut docs, &root)?;
+            self.ingest_root(&mut docs, &root, &mut cached)?;
+        }
+        self.loaded.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Brings the in-memory documents in line with disk before callers that need a consistent
+    /// view. Writes are synchronous and the watcher only logs, so this re-reads every file
+    /// under every root, whether or not its modification time changed.
+    pub fn flush(&self) -> Result<()> {
+        self.reload(true)
+    }
+
+    /// Re-reads a single file into the cache, dropping it when it no longer exists on disk.
+    pub fn reload_document(&self, path: &Path) -> Result<()> {
+        let path = canonical_path(path);
+        let mut docs = self.documents.write();
+        if path.is_file() && self.is_org_file(&path) {
+            docs.insert(path.clone(), OrgDocument::load(&path)?);
+        } else {
+            docs.remove(&path);
         }
         Ok(())
     }
 
+    /// Whether `path` has an extension this service reads as org, `.org` or one registered
+    /// with `OrgServiceBuilder::with_extensions`.
+    pub fn is_document_path(&self, path: &Path) -> bool {
+        self.is_org_file(path)
+    }
+
     pub fn list_documents(&self) -> Vec<PathBuf> {
+        self.list_documents_sorted(DocumentSort::Path)
+    }
+
+    /// The loaded documents in `order`, using the modification times and titles cached at
+    /// load rather than re-reading the files.
+    pub fn list_documents_sorted(&self, order: DocumentSort) -> Vec<PathBuf> {
         let docs = self.documents.read();
-        let mut entries: Vec<PathBuf> = docs
-            .keys()
-            .filter(|path| Self::path_in_roots(path, &self.roots))
-            .cloned()
+        let mut entries: Vec<(&PathBuf, &OrgDocument)> = docs
+            .iter()
+            .filter(|(path, _)| self.path_in_roots(path))
             .collect();
-        entries.sort();
-        entries
+        entries.sort_by_key(|(path, _)| *path);
+        match order {
+            DocumentSort::Path => {}
+            DocumentSort::ModifiedDesc => {
+                entries.sort_by_key(|(_, doc)| std::cmp::Reverse(doc.modified()))
+            }
+            DocumentSort::ModifiedAsc => entries.sort_by_key(|(_, doc)| doc.modified()),
+            DocumentSort::TitleAsc => entries.sort_by_cached_key(|(path, doc)| {
+                doc.title()
+                    .or_else(|| {
+                        path.file_stem()
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                    })
+                    .unwrap_or_default()
+                    .to_lowercase()
+            }),
+        }
+        entries.into_iter().map(|(path, _)| path.clone()).collect()
     }
 
     pub fn get_document(&self, path: impl AsRef<Path>) -> Result<OrgDocument> {
+        self.ensure_loaded()?;
         self.documents
             .read()
-            .get(path.as_ref())
+            .get(&canonical_path(path.as_ref()))
             .cloned()
-            .ok_or_else(|| anyhow!("document not loaded"))
+            .ok_or_else(|| OrgError::NotFound("document not loaded".to_string()).into())
     }
 
     pub fn update_document(&self, path: impl AsRef<Path>, contents: String) -> Result<()> {
+        self.write_document(path.as_ref(), contents, true)
+    }
+
+    /// Restores the contents `path` had before its most recent `update_document`.
+    pub fn undo_document(&self, path: &Path) -> Result<()> {
+        let key = canonical_path(path);
+        let previous = self
+            .history
+            .read()
+            .get(&key)
+            .and_then(VecDeque::back)
+            .cloned()
+            .ok_or_else(|| {
+                OrgError::InvalidInput(format!("nothing to undo for {}", path.display()))
+            })?;
+        // Only drop the step once it has been written, so a failed write can be retried.
+        self.write_document(path, previous, false)?;
+        if let Some(versions) = self.history.write().get_mut(&key) {
+            versions.pop_back();
+        }
+        Ok(())
+    }
+
+    fn write_document(&self, path: &Path, contents: String, record: bool) -> Result<()> {
+        self.ensure_loaded()?;
         let mut docs = self.documents.write();
-        let path_buf = path.as_ref().to_path_buf();
-        fs::write(&path_buf, &contents)?;
+        let path_buf = canonical_path(path);
         let doc = docs
             .get_mut(&path_buf)
-            .ok_or_else(|| anyhow!("document not loaded"))?;
+            .ok_or_else(|| OrgError::NotFound("document not loaded".to_string()))?;
+        // Edits are often assembled with `lines().join("\n")`, which drops the final newline
+        // and any CRLF terminators; restore both from the file's previous contents.
+        let mut contents = doc.line_ending().apply(&contents);
+        if doc.raw().ends_with('\n') && !contents.ends_with('\n') {
+            contents.push_str(&doc.line_ending().apply("\n"));
+        }
+        fs::write(&path_buf, &contents)?;
+        if record && doc.raw() != contents {
+            let mut history = self.history.write();
+            let versions = history.entry(path_buf.clone()).or_default();
+            if versions.len() == UNDO_DEPTH {
+                versions.pop_front();
+            }
+            versions.push_back(doc.raw().to_string());
+        }
         doc.replace_raw(contents.clone());
+        self.habit_cache.write().remove(&path_buf);
         if let Some(sink) = &self.notification_sink {
             let habits = habit::extract_habits(doc);
             for habit in habits {
-                let title = format!("Habit: {}", habit.title);
+                sink.clear_for_habit(doc.path(), &habit);
+                let title = notifications::habit_notification_title(&habit);
                 if let Some(date) = habit.scheduled {
                     let body = format!("Due on {}", date);
-                    let naive_dt = date.and_time(NaiveTime::from_hms_opt(9, 0, 0).unwrap());
-                    let when: DateTime<Utc> = Utc.from_utc_datetime(&naive_dt);
+                    let when = local_reminder_time(date.and_time(self.reminder_time));
                     sink.schedule(NotificationRequest {
+                        key: notifications::habit_notification_key(doc.path(), &habit),
                         title,
                         body,
                         scheduled_for: when,
                     });
                 }
             }
+            self.schedule_deadline_reminders(sink.as_ref(), doc);
         }
         Ok(())
     }
 
+    /// Re-schedules reminders for the unfinished deadlines in `doc` whose warning period has
+    /// started. Every item's earlier reminder is cleared before any is scheduled, since a
+    /// headline with both SCHEDULED and DEADLINE yields two items under one key.
+    fn schedule_deadline_reminders(&self, sink: &dyn NotificationSink, doc: &OrgDocument) {
+        let today = Local::now().date_naive();
+        let items = agenda::build_agenda(&[(doc.path().to_path_buf(), doc.clone())]);
+        for item in &items {
+            sink.clear_for_agenda_item(item);
+        }
+        for item in items {
+            if item.kind != agenda::AgendaKind::Deadline || item.is_done() {
+                continue;
+            }
+            let Some(date) = item.date.filter(|date| *date >= today) else {
+                continue;
+            };
+            let window_start = match item.warning {
+                Some(warning) => warning.window_start(date),
+                None => date.checked_sub_days(Days::new(DEADLINE_WARNING_DAYS)),
+            };
+            if window_start.is_some_and(|start| today < start) {
+                continue;
+            }
+            let naive_dt = date.and_time(item.time.unwrap_or(self.reminder_time));
+            sink.schedule(NotificationRequest {
+                key: notifications::agenda_notification_key(&item),
+                title: item.title.clone(),
+                body: format!("Deadline on {}", date),
+                scheduled_for: local_reminder_time(naive_dt),
+            });
+        }
+    }
+
+    /// Removes a loaded document from disk and from the cache.
+    pub fn delete_document(&self, path: &Path) -> Result<()> {
+        self.ensure_loaded()?;
+        let key = canonical_path(path);
+        let mut docs = self.documents.write();
+        if !docs.contains_key(&key) {
+            return Err(
+                OrgError::NotFound(format!("document not loaded: {}", path.display())).into(),
+            );
+        }
+        fs::remove_file(&key)?;
+        docs.remove(&key);
+        self.history.write().remove(&key);
+        self.habit_cache.write().remove(&key);
+        Ok(())
+    }
+
+    /// Moves a loaded document to `to`, which must be a new org path inside the roots.
+    pub fn rename_document(&self, from: &Path, to: &Path) -> Result<()> {
+        self.ensure_loaded()?;
+        let from = &canonical_path(from);
+        let to = &canonical_path(to);
+        let mut docs = self.documents.write();
+        if !docs.contains_key(from) {
+            return Err(
+                OrgError::NotFound(format!("document not loaded: {}", from.display())).into(),
+            );
+        }
+        if !self.is_org_file(to) || !self.path_in_roots(to) {
+            return Err(OrgError::InvalidInput(format!(
+                "{} is not an org document path inside the roots",
+                to.display()
+            ))
+            .into());
+        }
+        if to.exists() {
+            return Err(OrgError::AlreadyExists(format!("{} already exists", to.display())).into());
+        }
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(from, to)?;
+        docs.remove(from);
+        self.habit_cache.write().remove(from);
+        docs.insert(to.to_path_buf(), OrgDocument::load(to)?);
+        let mut history = self.history.write();
+        if let Some(versions) = history.remove(from) {
+            history.insert(to.to_path_buf(), versions);
+        }
+        Ok(())
+    }
+
+    /// Habits from every document. Each document's habits are cached against its content
+    /// hash, so only documents that changed since the last call are parsed again.
     pub fn habits(&self) -> Result<Vec<habit::Habit>> {
-        let docs_lock = self.documents.read();
-        let docs: Vec<OrgDocument> = docs_lock
-            .iter()
-            .filter(|(path, _)| Self::path_in_roots(path, &self.roots))
-            .map(|(_, doc)| doc.clone())
-            .collect();
+        self.ensure_loaded()?;
+        let docs = self.documents.read();
+        let cached: Vec<_> = {
+            let cache = self.habit_cache.read();
+            docs.iter()
+                .filter(|(path, _)| self.path_in_roots(path))
+                .map(|(path, doc)| {
+                    let hash = doc.content_hash();
+                    let habits = cache
+                        .get(path)
+                        .filter(|(cached, _)| *cached == hash)
+                        .map(|(_, habits)| habits.clone());
+                    (path, doc, hash, habits)
+                })
+                .collect()
+        };
+        // Parse outside the cache lock so a slow document does not block invalidations.
+        let mut fresh = HashMap::with_capacity(cached.len());
         let mut habits_all = Vec::new();
-        for doc in docs {
-            habits_all.extend(habit::extract_habits(&doc));
+        for (path, doc, hash, habits) in cached {
+            let habits = habits.unwrap_or_else(|| {
+                self.habit_extractions.fetch_add(1, Ordering::Relaxed);
+                habit::extract_habits(doc)
+            });
+            habits_all.extend(habits.iter().cloned());
+            fresh.insert(path.clone(), (hash, habits));
         }
+        *self.habit_cache.write() = fresh;
         Ok(habits_all)
     }
 
+    /// How many times `habits` has parsed a document rather than reusing its cached habits.
+    #[doc(hidden)]
+    pub fn habit_extractions(&self) -> usize {
+        self.habit_extractions.load(Ordering::Relaxed)
+    }
+
     pub fn agenda(&self) -> Result<Vec<agenda::AgendaItem>> {
+        self.ensure_loaded()?;
         let docs_lock = self.documents.read();
         let docs: Vec<(PathBuf, OrgDocument)> = docs_lock
             .iter()
-            .filter(|(path, _)| Self::path_in_roots(path, &self.roots))
+            .filter(|(path, _)| self.path_in_roots(path))
             .map(|(path, doc)| (path.clone(), doc.clone()))
             .collect();
-        Ok(agenda::build_agenda(&docs))
+        // Keep the first item for each file, headline, kind, and date in case overlapping
+        // roots ever list a file twice.
+        let mut seen = HashSet::new();
+        let mut items = agenda::build_agenda(&docs);
+        items.retain(|item| {
+            seen.insert((
+                item.path.clone(),
+                item.headline_line,
+                item.kind.clone(),
+                item.date,
+            ))
+        });
+        Ok(items)
+    }
+
+    /// Agenda items bucketed into one group per day of `span`. When `span.today` is the local
+    /// date and no `now` was given, today's group carries the current local time.
+    pub fn agenda_days(&self, span: &DaySpan) -> Result<Vec<DayGroup>> {
+        let mut span = *span;
+        let local = Local::now().naive_local();
+        if span.now.is_none() && span.today == local.date() {
+            span.now = Some(local.time());
+        }
+        Ok(agenda_days::group_by_day(&self.agenda()?, &span))
+    }
+
+    /// Agenda for the `days` days starting at `today`, with every dated item on the day it
+    /// actually falls on.
+    pub fn agenda_upcoming(&self, today: NaiveDate, days: u32) -> Result<Vec<DayGroup>> {
+        self.agenda_days(&DaySpan::upcoming(today, days))
+    }
+
+    /// Every date between `from` and `to` (inclusive) that a scheduled or deadline item falls
+    /// on, following its repeater. Undated and floating items have none.
+    pub fn occurrence_dates(
+        &self,
+        item: &agenda::AgendaItem,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        match item.date {
+            Some(base) if item.kind != agenda::AgendaKind::Floating => {
+                agenda_days::occurrences_between(item, base, from, to)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// One agenda item per headline whose schedule or deadline repeats, for managing
+    /// recurring tasks. A headline that repeats in both keeps its earlier-dated entry.
+    pub fn recurring_tasks(&self) -> Vec<agenda::AgendaItem> {
+        let mut seen = HashSet::new();
+        self.agenda()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| item.repeater.is_some())
+            .filter(|item| seen.insert((item.path.clone(), item.headline_line)))
+            .collect()
+    }
+
+    /// Headline counts and clocked time per tag, counting inherited and `#+FILETAGS` tags.
+    /// A headline with both a schedule and a deadline is counted once.
+    pub fn stats_by_tag(&self) -> Result<BTreeMap<String, TagStats>> {
+        let mut stats: BTreeMap<String, TagStats> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        for item in self.agenda()? {
+            if !seen.insert((item.path.clone(), item.headline_line)) {
+                continue;
+            }
+            for tag in &item.tags {
+                let entry = stats.entry(tag.clone()).or_default();
+                entry.item_count += 1;
+                entry.done_count += usize::from(item.is_done());
+                entry.clocked += Duration::from_secs(item.clocked_minutes * 60);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Number of unfinished scheduled or deadline items that fell due before `today`.
+    pub fn overdue_count(&self, today: NaiveDate) -> Result<usize> {
+        Ok(self
+            .agenda()?
+            .iter()
+            .filter(|item| agenda_days::is_overdue(item, today))
+            .count())
+    }
+
+    /// Headlines closed between `start` and `end` (inclusive), one entry per headline, in
+    /// the order they were completed.
+    pub fn completed_between(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<agenda::AgendaItem>> {
+        let mut completed: Vec<agenda::AgendaItem> = Vec::new();
+        for item in self.agenda()? {
+            let Some(closed_at) = item.closed_at else {
+                continue;
+            };
+            let in_range = (start..=end).contains(&closed_at.date());
+            let seen = completed
+                .iter()
+                .any(|other| other.path == item.path && other.headline_line == item.headline_line);
+            if in_range && !seen {
+                completed.push(item);
+            }
+        }
+        completed.sort_by_key(|item| item.closed_at);
+        Ok(completed)
     }
 
     pub fn complete_agenda_item(&self, item: &agenda::AgendaItem) -> Result<()> {
+        self.complete_agenda_item_as(item, None)
+    }
+
+    /// Moves `item` to `done_keyword`, or to its file's first done state when `None`. Items
+    /// already in any done state are left alone; a keyword the file does not declare as a
+    /// done state is rejected.
+    ///
+    /// A headline with repeating timestamps is not closed: its dates move to the next
+    /// occurrence and `:LAST_REPEAT:` is stamped, as in Org. Once the next occurrence would
+    /// fall after a `:REPEAT_UNTIL:` date the headline is marked done instead.
+    pub fn complete_agenda_item_as(
+        &self,
+        item: &agenda::AgendaItem,
+        done_keyword: Option<&str>,
+    ) -> Result<()> {
         let doc = self.get_document(&item.path)?;
         let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
         let idx = item.headline_line;
         let line = lines
-            .get_mut(idx)
-            .ok_or_else(|| anyhow!("unable to locate agenda headline"))?;
+            .get(idx)
+            .ok_or_else(|| OrgError::NotFound("unable to locate agenda headline".to_string()))?;
 
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
+        let target = done_keyword.unwrap_or(keywords.first_done());
+        if !keywords.is_done(target) {
+            return Err(OrgError::InvalidInput(format!(
+                "{} is not a done keyword in this file",
+                target
+            ))
+            .into());
+        }
         let trimmed = line.trim_start_matches('*');
         let leading_len = line.len() - trimmed.len();
         let prefix = &line[..leading_len];
         let rest = trimmed.trim_start();
 
-        let mut new_rest = if rest.starts_with("DONE") {
-            rest.to_string()
-        } else if rest.starts_with("TODO") {
-            rest.replacen("TODO", "DONE", 1)
-        } else if let Some(keyword) = &item.todo_keyword {
-            rest.replacen(keyword, "DONE", 1)
+        let first = rest.split_whitespace().next().unwrap_or("");
+        if keywords.is_done(first) {
+            return Ok(());
+        }
+        let new_rest = if keywords.contains(first) {
+            format!("{}{}", target, &rest[first.len()..])
         } else {
-            format!("DONE {}", rest)
+            format!("{} {}", target, rest)
         };
+        let closed_line = realign_tags(line, format!("{} {}", prefix, new_rest));
 
-        if !new_rest.starts_with("DONE") {
-            new_rest = format!("DONE {}", new_rest.trim_start());
+        let now = Local::now();
+        let advances = repeat_advances(&lines, idx, now.date_naive());
+        let until = headline_property(&lines, idx, "REPEAT_UNTIL")
+            .and_then(|value| leading_date(value.trim_matches(['<', '>', '[', ']'])));
+        let within_limit = until.is_none_or(|until| advances.iter().all(|a| a.date <= until));
+        if !advances.is_empty() && within_limit {
+            for advance in advances.into_iter().rev() {
+                lines[advance.line].replace_range(advance.range, &advance.stamp);
+            }
+            let stamp = now.format("[%Y-%m-%d %a %H:%M]").to_string();
+            set_headline_property(&mut lines, idx, "LAST_REPEAT", &stamp);
+        } else {
+            lines[idx] = closed_line;
         }
-
-        *line = format!("{}{}", prefix, new_rest);
-        let new_contents = lines.join(
-            "
-",
-        );
+        let new_contents = lines.join("\n");
         self.update_document(&item.path, new_contents)?;
         Ok(())
     }
 
     pub fn complete_headline(&self, path: impl AsRef<Path>, headline_line: usize) -> Result<()> {
+        self.complete_headline_as(path, headline_line, None)
+    }
+
+    /// `complete_headline` moving the headline to `done_keyword` rather than the first done
+    /// state; see `complete_agenda_item_as`.
+    pub fn complete_headline_as(
+        &self,
+        path: impl AsRef<Path>,
+        headline_line: usize,
+        done_keyword: Option<&str>,
+    ) -> Result<()> {
         let target = path.as_ref().to_path_buf();
         let agenda_items = self.agenda()?;
         let Some(item) = agenda_items
             .into_iter()
             .find(|candidate| candidate.path == target && candidate.headline_line == headline_line)
         else {
-            return Err(anyhow!(
+            return Err(OrgError::NotFound(format!(
                 "unable to locate agenda headline at {}:{}",
                 target.display(),
                 headline_line
-            ));
+            ))
+            .into());
+        };
+        self.complete_agenda_item_as(&item, done_keyword)
+    }
+
+    /// Returns the exact org text of `item`'s subtree: headline, planning, drawers, body, and
+    /// any child headlines.
+    pub fn agenda_item_source(&self, item: &agenda::AgendaItem) -> Result<String> {
+        let doc = self.get_document(&item.path)?;
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let range = agenda::subtree_range(&lines, item.headline_line)
+            .ok_or_else(|| OrgError::NotFound("unable to locate agenda headline".to_string()))?;
+        Ok(lines[range].join("\n"))
+    }
+
+    /// Every headline in the document at `path`, in document order. Keyword, title, and tags
+    /// come from the orgize parse, using the document's own `#+TODO:` keywords.
+    pub fn headlines(&self, path: &Path) -> Result<Vec<HeadlineRef>> {
+        let doc = self.get_document(path)?;
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
+        let config = ParseConfig {
+            todo_keywords: (keywords.active, keywords.done),
+        };
+        let org = Org::parse_custom(doc.raw(), &config);
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let headline_lines = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| agenda::headline_depth(line).is_some())
+            .map(|(idx, _)| idx);
+        Ok(org
+            .headlines()
+            .zip(headline_lines)
+            .map(|(headline, line)| {
+                let title = headline.title(&org);
+                HeadlineRef {
+                    path: path.to_path_buf(),
+                    line,
+                    title: title.raw.to_string(),
+                    todo_keyword: title.keyword.as_ref().map(|keyword| keyword.to_string()),
+                    depth: headline.level() as u32,
+                    tags: title.tags.iter().map(|tag| tag.to_string()).collect(),
+                    body_range: subtree_body(&lines, line),
+                }
+            })
+            .collect())
+    }
+
+    /// Finds headlines across all documents whose title matches `query`, best matches first.
+    ///
+    /// Exact prefix matches rank ahead of word-prefix matches, then substring matches, then
+    /// fuzzy (in-order subsequence) matches.
+    pub fn headlines_matching(&self, query: &str) -> Vec<HeadlineRef> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() || self.ensure_loaded().is_err() {
+            return Vec::new();
+        }
+        let docs = self.documents.read();
+        let mut ranked = Vec::new();
+        for (path, doc) in docs.iter().filter(|(path, _)| self.path_in_roots(path)) {
+            let keywords = agenda::TodoKeywords::from_document(doc.raw());
+            let lines: Vec<&str> = doc.raw().lines().collect();
+            for (idx, line) in lines.iter().enumerate() {
+                if agenda::headline_depth(line).is_none() {
+                    continue;
+                }
+                let (todo_keyword, title, tags) = agenda::parse_headline(line, &keywords);
+                if let Some(rank) = headline_match_rank(&title.to_lowercase(), &needle) {
+                    ranked.push((
+                        rank,
+                        HeadlineRef {
+                            path: path.clone(),
+                            line: idx,
+                            title,
+                            todo_keyword,
+                            depth: agenda::headline_depth(line).unwrap_or_default() as u32,
+                            tags,
+                            body_range: subtree_body(&lines, idx),
+                        },
+                    ));
+                }
+            }
+        }
+        ranked.sort_by(|(a_rank, a), (b_rank, b)| {
+            a_rank
+                .cmp(b_rank)
+                .then_with(|| a.title.len().cmp(&b.title.len()))
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        ranked.into_iter().map(|(_, headline)| headline).collect()
+    }
+
+    /// Resolves a `file:` link (bracketed or bare) written in the document at `from`. Relative
+    /// paths are taken from `from`'s directory and a link with no file part, such as
+    /// `[[*Heading]]`, points into `from` itself.
+    pub fn resolve_link(&self, from: impl AsRef<Path>, link: &str) -> Result<LinkTarget> {
+        let from = from.as_ref();
+        let inner = link.trim().trim_start_matches("[[").trim_end_matches("]]");
+        let target = inner.split("][").next().unwrap_or(inner).trim();
+        let target = target
+            .get(..5)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("file:"))
+            .map_or(target, |_| &target[5..]);
+        let (file, search) = match target.split_once("::") {
+            Some((file, search)) => (file, Some(search.trim())),
+            None if target.starts_with(['*', '#']) => ("", Some(target)),
+            None => (target, None),
+        };
+
+        let path = if file.is_empty() {
+            from.to_path_buf()
+        } else {
+            let joined = from.parent().unwrap_or(Path::new("")).join(file);
+            normalize_lexically(&joined)
+        };
+        let doc = self.get_document(&path).map_err(|_| {
+            OrgError::NotFound(format!("link target {} is not loaded", path.display()))
+        })?;
+        let headline_line = match search.filter(|search| !search.is_empty()) {
+            Some(search) => Some(find_link_headline(doc.raw(), search).ok_or_else(|| {
+                OrgError::NotFound(format!(
+                    "no headline matching {} in {}",
+                    search,
+                    path.display()
+                ))
+            })?),
+            None => None,
         };
-        self.complete_agenda_item(&item)
+        Ok(LinkTarget {
+            path,
+            headline_line,
+        })
+    }
+
+    /// Case-insensitive substring search over every loaded document, ordered by path and line.
+    /// Each hit is attributed to the nearest preceding headline.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() || self.ensure_loaded().is_err() {
+            return Vec::new();
+        }
+        let docs = self.documents.read();
+        let mut paths: Vec<&PathBuf> = docs
+            .keys()
+            .filter(|path| self.path_in_roots(path))
+            .collect();
+        paths.sort();
+
+        let mut hits = Vec::new();
+        for path in paths {
+            let mut headline = None;
+            let keywords = agenda::TodoKeywords::from_document(docs[path].raw());
+            for (idx, line) in docs[path].raw().lines().enumerate() {
+                if agenda::headline_depth(line).is_some() {
+                    headline = Some(agenda::parse_headline(line, &keywords).1);
+                }
+                if line.to_lowercase().contains(&needle) {
+                    hits.push(SearchHit {
+                        path: path.clone(),
+                        line: idx,
+                        snippet: line.trim().to_string(),
+                        headline: headline.clone(),
+                    });
+                }
+            }
+        }
+        hits
     }
 
     pub fn agenda_snapshot(&self) -> Result<AgendaSnapshot> {
@@ -237,8 +974,24 @@ impl OrgService {
         })
     }
 
+    /// The agenda snapshot with items narrowed by `filter`. Habits are returned unfiltered.
+    pub fn agenda_filtered(&self, filter: &agenda::AgendaFilter) -> Result<AgendaSnapshot> {
+        let mut snapshot = self.agenda_snapshot()?;
+        snapshot.items.retain(|item| filter.matches(item));
+        Ok(snapshot)
+    }
+
+    /// Appends `content` to the file at `path`, creating it and its directories if needed.
+    /// The target must resolve to a location inside one of the roots.
     pub fn append_to_document(&self, path: impl AsRef<Path>, content: &str) -> Result<()> {
-        let path_buf = path.as_ref().to_path_buf();
+        let path_buf = resolve_write_target(path.as_ref())
+            .filter(|target| !self.roots.is_empty() && self.path_in_roots(target))
+            .ok_or_else(|| {
+                OrgError::InvalidInput(format!(
+                    "{} is outside the configured roots",
+                    path.as_ref().display()
+                ))
+            })?;
         if let Some(parent) = path_buf.parent() {
             if !parent.as_os_str().is_empty() {
                 fs::create_dir_all(parent)?;
@@ -252,12 +1005,70 @@ impl OrgService {
         if !payload.ends_with('\n') {
             payload.push('\n');
         }
+        if let Some(doc) = self.documents.read().get(&canonical_path(&path_buf)) {
+            payload = doc.line_ending().apply(&payload);
+        }
         file.write_all(payload.as_bytes())?;
 
-        let refreshed = OrgDocument::load(&path_buf)?;
-        let mut docs = self.documents.write();
-        docs.insert(path_buf, refreshed);
-        Ok(())
+        self.reload_document(&path_buf)
+    }
+
+    /// Files `content` at the end of the subtree of the first headline titled `headline_title`,
+    /// after its existing children. A missing headline is added as a top-level headline at the
+    /// end of the file, which is created if needed; either way the file is written once.
+    pub fn append_under_headline(
+        &self,
+        path: &Path,
+        headline_title: &str,
+        content: &str,
+    ) -> Result<()> {
+        let title = headline_title.trim();
+        if title.is_empty() {
+            return Err(
+                OrgError::InvalidInput("target headline title is empty".to_string()).into(),
+            );
+        }
+        let doc = match self.get_document(path) {
+            Ok(doc) => Some(doc),
+            Err(_) if !path.exists() => None,
+            Err(err) => return Err(err),
+        };
+        let existing = match &doc {
+            Some(_) => self
+                .headlines(path)?
+                .into_iter()
+                .find(|headline| headline.title.trim() == title)
+                .map(|headline| headline.line),
+            None => None,
+        };
+
+        let raw = doc.as_ref().map(OrgDocument::raw).unwrap_or_default();
+        let mut lines: Vec<&str> = raw.lines().collect();
+        let created = format!("* {title}");
+        let headline_line = existing.unwrap_or_else(|| {
+            lines.push(&created);
+            lines.len() - 1
+        });
+        let range = agenda::subtree_range(&lines, headline_line).ok_or_else(|| {
+            OrgError::NotFound(format!("line {} is not a headline", headline_line))
+        })?;
+        // Keep blank lines that separate the subtree from the next headline after the entry.
+        let mut at = range.end;
+        while at > range.start + 1 && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+
+        let mut updated: Vec<&str> = lines[..at].to_vec();
+        updated.extend(content.trim_end_matches('\n').lines());
+        updated.extend_from_slice(&lines[at..]);
+        let mut contents = updated.join("\n");
+        if raw.ends_with('\n') || at == lines.len() {
+            contents.push('\n');
+        }
+        match doc {
+            Some(_) => self.update_document(path, contents),
+            None => self.append_to_document(path, &contents),
+        }
     }
 
     pub fn set_headline_status(
@@ -270,20 +1081,26 @@ impl OrgService {
         let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
         let line = lines
             .get_mut(headline_line)
-            .ok_or_else(|| anyhow!("unable to locate headline"))?;
+            .ok_or_else(|| OrgError::NotFound("unable to locate headline".to_string()))?;
 
         let trimmed = line.trim_start_matches('*');
         let leading_len = line.len() - trimmed.len();
         let prefix = &line[..leading_len];
         let rest = trimmed.trim_start();
 
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
         let mut parts = rest.splitn(2, ' ');
         let first = parts.next().unwrap_or("");
         let remainder = parts.next().unwrap_or("");
         let new_rest = if first.eq_ignore_ascii_case(status) {
             rest.to_string()
         } else {
-            let tail = remainder.trim_start();
+            // Only replace the first word when it is a keyword; otherwise it is the title.
+            let tail = if keywords.contains(first) {
+                remainder.trim_start()
+            } else {
+                rest
+            };
             if tail.is_empty() {
                 status.trim().to_string()
             } else {
@@ -291,47 +1108,598 @@ impl OrgService {
             }
         };
 
-        *line = format!("{}{}", prefix, new_rest);
+        *line = realign_tags(line, format!("{} {}", prefix, new_rest));
         let new_contents = lines.join("\n");
         self.update_document(path, new_contents)?;
         Ok(())
     }
 
-    pub fn lexical_nodes(&self, path: impl AsRef<Path>) -> Result<Vec<lexical::LexicalNode>> {
+    /// Moves every subtree in `path` whose keyword is one of the file's done states to the
+    /// sibling `<file>_archive`, stamping each with an `:ARCHIVE_TIME:` property. Returns the
+    /// number of subtrees moved. If `path` cannot be rewritten, the archive is truncated back
+    /// so the subtrees are not archived twice by a retry.
+    pub fn archive_done(&self, path: &Path) -> Result<usize> {
         let doc = self.get_document(path)?;
-        Ok(lexical::document_to_lexical(&doc))
+        let stamp = Local::now().format("%Y-%m-%d %a %H:%M").to_string();
+        let (kept, archived, count) = split_done_subtrees(doc.raw(), &stamp);
+        if count == 0 {
+            return Ok(0);
+        }
+        let mut archive_name = path.as_os_str().to_os_string();
+        archive_name.push("_archive");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(PathBuf::from(archive_name))?;
+        let previous_len = file.metadata()?.len();
+        let written = file
+            .write_all(archived.as_bytes())
+            .map_err(anyhow::Error::from)
+            .and_then(|()| self.update_document(path, kept));
+        if let Err(err) = written {
+            file.set_len(previous_len)?;
+            return Err(err);
+        }
+        Ok(count)
     }
 
-    pub fn add_agenda_entry(
+    /// Appends `content` to the body of the headline at `headline_line`, after its drawers
+    /// and existing text but before its first child headline.
+    pub fn append_to_headline_body(
         &self,
-        target: impl AsRef<Path>,
-        title: &str,
-        date: NaiveDate,
+        path: &Path,
+        headline_line: usize,
+        content: &str,
     ) -> Result<()> {
-        let target_path = target.as_ref();
-        let doc = self.get_document(target_path)?;
-        let mut contents = doc.raw().to_string();
-        if !contents.is_empty() && !contents.ends_with('\n') {
+        let doc = self.get_document(path)?;
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let range = agenda::subtree_range(&lines, headline_line).ok_or_else(|| {
+            OrgError::NotFound(format!("line {} is not a headline", headline_line))
+        })?;
+        let body_end = (headline_line + 1..range.end)
+            .find(|&idx| agenda::headline_depth(lines[idx]).is_some())
+            .unwrap_or(range.end);
+        let mut at = body_end;
+        while at > headline_line + 1 && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+
+        let mut updated: Vec<&str> = lines[..at].to_vec();
+        updated.extend(content.trim_end_matches('\n').lines());
+        updated.extend_from_slice(&lines[at..]);
+        let mut contents = updated.join("\n");
+        if doc.raw().ends_with('\n') || at == lines.len() {
             contents.push('\n');
         }
-        contents.push_str(&format!(
-            "* TODO {}\nSCHEDULED: <{}>\n\n",
-            title,
-            date.format("%Y-%m-%d")
-        ));
-        self.update_document(target_path, contents)
+        self.update_document(path, contents)
     }
-    pub fn watch(&mut self) -> Result<()> {
+
+    /// Updates the `[n/m]` and `[n%]` statistics cookies on the headline at `headline_line`,
+    /// as org's `C-c C-c` does. Its top-level body checkboxes are counted or, when it has
+    /// none, its direct child TODO headlines. With `recursive`, every headline in the
+    /// subtree is updated.
+    pub fn recompute_statistics(
+        &self,
+        path: &Path,
+        headline_line: usize,
+        recursive: bool,
+    ) -> Result<()> {
+        let doc = self.get_document(path)?;
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let range = agenda::subtree_range(&lines, headline_line).ok_or_else(|| {
+            OrgError::NotFound(format!("line {} is not a headline", headline_line))
+        })?;
+        let targets: Vec<usize> = if recursive {
+            range
+                .filter(|&idx| agenda::headline_depth(lines[idx]).is_some())
+                .collect()
+        } else {
+            vec![headline_line]
+        };
+
+        let mut updated: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        for idx in targets {
+            let (done, total) = headline_statistics(&lines, idx, &keywords);
+            updated[idx] = with_statistics(lines[idx], done, total);
+        }
+        if updated.iter().zip(&lines).all(|(new, old)| new == old) {
+            return Ok(());
+        }
+        let mut contents = updated.join("\n");
+        if doc.raw().ends_with('\n') {
+            contents.push('\n');
+        }
+        self.update_document(path, contents)
+    }
+
+    /// Inserts a headline one level below the one at `parent_line`, after the parent's body
+    /// and any existing children, so it becomes the last child.
+    pub fn add_child_headline(
+        &self,
+        path: &Path,
+        parent_line: usize,
+        title: &str,
+        keyword: Option<&str>,
+    ) -> Result<()> {
+        let doc = self.get_document(path)?;
+        let lines: Vec<&str> = doc.raw().lines().collect();
+        let range = agenda::subtree_range(&lines, parent_line)
+            .ok_or_else(|| OrgError::NotFound(format!("line {} is not a headline", parent_line)))?;
+        let depth = agenda::headline_depth(lines[parent_line]).unwrap_or(1);
+        // Keep blank lines that separate the subtree from the next headline after the child.
+        let mut at = range.end;
+        while at > range.start + 1 && lines[at - 1].trim().is_empty() {
+            at -= 1;
+        }
+
+        let mut headline = "*".repeat(depth + 1);
+        if let Some(keyword) = keyword.map(str::trim).filter(|k| !k.is_empty()) {
+            headline.push(' ');
+            headline.push_str(keyword);
+        }
+        headline.push(' ');
+        headline.push_str(title.trim());
+
+        let mut updated: Vec<&str> = lines[..at].to_vec();
+        updated.push(&headline);
+        updated.extend_from_slice(&lines[at..]);
+        let mut contents = updated.join("\n");
+        if doc.raw().ends_with('\n') || at == lines.len() {
+            contents.push('\n');
+        }
+        self.update_document(path, contents)
+    }
+
+    /// Moves the headline at `headline_line` of `source`, with its whole subtree, to the end
+    /// of `target`.
+    pub fn refile_headline(
+        &self,
+        source: &Path,
+        headline_line: usize,
+        target: &Path,
+    ) -> Result<()> {
+        if source == target {
+            return Err(OrgError::InvalidInput(
+                "cannot refile a headline into its own file".to_string(),
+            )
+            .into());
+        }
+        let source_doc = self.get_document(source)?;
+        let target_doc = self.get_document(target)?;
+        let lines: Vec<&str> = source_doc.raw().lines().collect();
+        let range = agenda::subtree_range(&lines, headline_line).ok_or_else(|| {
+            OrgError::NotFound(format!("line {} is not a headline", headline_line))
+        })?;
+
+        let mut appended = target_doc.raw().to_string();
+        if !appended.is_empty() && !appended.ends_with('\n') {
+            appended.push('\n');
+        }
+        for line in &lines[range.clone()] {
+            appended.push_str(line);
+            appended.push('\n');
+        }
+
+        let mut kept: Vec<&str> = lines[..range.start].to_vec();
+        kept.extend_from_slice(&lines[range.end..]);
+        let mut remaining = kept.join("\n");
+        if source_doc.raw().ends_with('\n') && !remaining.is_empty() {
+            remaining.push('\n');
+        }
+
+        self.update_document(target, appended)?;
+        self.update_document(source, remaining)
+    }
+
+    /// Merges `src` into `dest`: `src`'s content is appended to `dest` under a new top-level
+    /// headline named after its title, links elsewhere in the vault that point at `src` are
+    /// pointed at `dest`, and `src` is deleted.
+    ///
+    /// `src`'s file-level properties, including its `:ID:`, move onto the new headline so `id:`
+    /// links keep resolving, and `#+FILETAGS` become the headline's tags. A headline `:ID:`
+    /// from `src` that `dest` already uses gets a numeric suffix.
+    pub fn merge_documents(&self, src: &Path, dest: &Path) -> Result<()> {
+        let (src, dest) = (canonical_path(src), canonical_path(dest));
+        let (src, dest) = (src.as_path(), dest.as_path());
+        if src == dest {
+            return Err(
+                OrgError::InvalidInput("cannot merge a document into itself".to_string()).into(),
+            );
+        }
+        let src_doc = self.get_document(src)?;
+        let dest_doc = self.get_document(dest)?;
+        let src_dir = src.parent().unwrap_or(Path::new(""));
+        let dest_dir = dest.parent().unwrap_or(Path::new(""));
+
+        let dest_lines: Vec<&str> = dest_doc.raw().lines().collect();
+        let mut taken_ids: HashSet<String> = dest_lines
+            .iter()
+            .filter_map(|line| id_property(line))
+            .collect();
+        let src_lines: Vec<&str> = src_doc.raw().lines().collect();
+        let first_headline = src_lines
+            .iter()
+            .position(|line| agenda::headline_depth(line).is_some())
+            .unwrap_or(src_lines.len());
+
+        let title = src_doc.title().unwrap_or_else(|| {
+            src.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let tags = agenda::parse_file_tags(src_doc.raw());
+        let mut section = vec![if tags.is_empty() {
+            format!("* {title}")
+        } else {
+            format!("* {title} :{}:", tags.join(":"))
+        }];
+        let mut properties = Vec::new();
+        let mut body = Vec::new();
+        let mut in_properties = false;
+        for line in &src_lines[..first_headline] {
+            let trimmed = line.trim();
+            if trimmed.eq_ignore_ascii_case(":PROPERTIES:") {
+                in_properties = true;
+            } else if in_properties && trimmed.eq_ignore_ascii_case(":END:") {
+                in_properties = false;
+            } else if in_properties {
+                // The file node's ID is the same node once merged, so only keep it if new.
+                if id_property(line).is_some_and(|id| !taken_ids.insert(id)) {
+                    continue;
+                }
+                properties.push(trimmed.to_string());
+            } else if trimmed
+                .strip_prefix("#+")
+                .and_then(|rest| rest.split_once(':'))
+                .is_some_and(|(keyword, _)| {
+                    keyword.eq_ignore_ascii_case("TITLE")
+                        || keyword.eq_ignore_ascii_case("FILETAGS")
+                })
+            {
+                continue;
+            } else {
+                body.push(line.to_string());
+            }
+        }
+        if !properties.is_empty() {
+            section.push(":PROPERTIES:".to_string());
+            section.extend(properties);
+            section.push(":END:".to_string());
+        }
+        section.extend(body.into_iter().skip_while(|line| line.trim().is_empty()));
+        for line in &src_lines[first_headline..] {
+            if let Some(id) = id_property(line) {
+                if !taken_ids.insert(id.clone()) {
+                    let unique = (2..)
+                        .map(|n| format!("{id}-{n}"))
+                        .find(|candidate| !taken_ids.contains(candidate))
+                        .unwrap_or_default();
+                    taken_ids.insert(unique.clone());
+                    section.push(line.replacen(&id, &unique, 1));
+                    continue;
+                }
+            }
+            if agenda::headline_depth(line).is_some() {
+                section.push(format!("*{line}"));
+            } else {
+                section.push(line.to_string());
+            }
+        }
+        let section = retarget_links(&section.join("\n"), src_dir, dest_dir, src, dest);
+
+        let mut merged = retarget_links(dest_doc.raw(), dest_dir, dest_dir, src, dest);
+        if !merged.is_empty() && !merged.ends_with('\n') {
+            merged.push('\n');
+        }
+        merged.push_str(&section);
+        merged.push('\n');
+        self.update_document(dest, merged)?;
+
+        for path in self.list_documents() {
+            if path == src || path == dest {
+                continue;
+            }
+            let doc = self.get_document(&path)?;
+            let dir = path.parent().unwrap_or(Path::new(""));
+            let contents = retarget_links(doc.raw(), dir, dir, src, dest);
+            if contents != doc.raw() {
+                self.update_document(&path, contents)?;
+            }
+        }
+        self.delete_document(src)
+    }
+
+    /// Renames `old` to `new` on every headline and `#+FILETAGS` line across the vault,
+    /// writing each changed file once. Where `new` is already present `old` is dropped rather
+    /// than duplicated. Returns the number of tag occurrences changed.
+    pub fn rename_tag(&self, old: &str, new: &str) -> Result<usize> {
+        let old = old.trim().trim_matches(':');
+        let new = new.trim().trim_matches(':');
+        if old.is_empty() || new.is_empty() || new.contains([':', ' ']) {
+            return Err(OrgError::InvalidInput(
+                "tag names must be non-empty and contain no ':' or spaces".to_string(),
+            )
+            .into());
+        }
+        if old == new {
+            return Ok(0);
+        }
+        let mut changed = 0;
+        for path in self.list_documents() {
+            let doc = self.get_document(&path)?;
+            let (contents, count) = rename_tag_in(doc.raw(), old, new);
+            if count > 0 {
+                self.update_document(&path, contents)?;
+                changed += count;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Moves a scheduled or deadline item's timestamp to `date`, keeping its time and warning.
+    /// The repeater is kept unless `drop_repeater`, which turns a repeating item into a
+    /// one-off.
+    pub fn reschedule(
+        &self,
+        item: &agenda::AgendaItem,
+        date: NaiveDate,
+        drop_repeater: bool,
+    ) -> Result<()> {
+        let doc = self.get_document(&item.path)?;
+        let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
+        retime_item(&mut lines, item, date, drop_repeater)?;
+
+        let mut contents = lines.join("\n");
+        if doc.raw().ends_with('\n') {
+            contents.push('\n');
+        }
+        self.update_document(&item.path, contents)
+    }
+
+    /// Moves every overdue, non-repeating scheduled item onto `today`, writing each file once.
+    /// Repeating items are left alone since completing them advances the date anyway. Returns
+    /// the number of items moved.
+    pub fn reschedule_overdue_to_today(&self, today: NaiveDate) -> Result<usize> {
+        let mut by_file: BTreeMap<PathBuf, Vec<agenda::AgendaItem>> = BTreeMap::new();
+        for item in self.agenda()? {
+            if item.kind == agenda::AgendaKind::Scheduled
+                && item.repeater.is_none()
+                && agenda_days::is_overdue(&item, today)
+            {
+                by_file.entry(item.path.clone()).or_default().push(item);
+            }
+        }
+
+        let mut moved = 0;
+        for (path, items) in by_file {
+            let doc = self.get_document(&path)?;
+            let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
+            for item in &items {
+                retime_item(&mut lines, item, today, false)?;
+            }
+            let mut contents = lines.join("\n");
+            if doc.raw().ends_with('\n') {
+                contents.push('\n');
+            }
+            self.update_document(&path, contents)?;
+            moved += items.len();
+        }
+        Ok(moved)
+    }
+
+    /// Sets or, with `None`, removes the `[#X]` priority cookie on a headline.
+    pub fn set_priority(
+        &self,
+        path: impl AsRef<Path>,
+        headline_line: usize,
+        priority: Option<char>,
+    ) -> Result<()> {
+        if let Some(priority) = priority {
+            if !self.priority_in_range(priority) {
+                return Err(OrgError::InvalidInput(format!(
+                    "priority {} is outside the configured range",
+                    priority
+                ))
+                .into());
+            }
+        }
+        let doc = self.get_document(&path)?;
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
+        let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
+        let line = lines
+            .get_mut(headline_line)
+            .filter(|line| agenda::headline_depth(line).is_some())
+            .ok_or_else(|| OrgError::NotFound("unable to locate headline".to_string()))?;
+        *line = realign_tags(line, with_priority(line, priority, &keywords));
+        self.update_document(path, lines.join("\n"))
+    }
+
+    /// Writes `minutes` as the headline's `:EFFORT:` property in `H:MM` form, creating the
+    /// property drawer if needed.
+    pub fn set_effort(
+        &self,
+        path: impl AsRef<Path>,
+        headline_line: usize,
+        minutes: u32,
+    ) -> Result<()> {
+        let doc = self.get_document(&path)?;
+        let mut lines: Vec<String> = doc.raw().lines().map(|l| l.to_string()).collect();
+        if lines
+            .get(headline_line)
+            .and_then(|line| agenda::headline_depth(line))
+            .is_none()
+        {
+            return Err(OrgError::NotFound("unable to locate headline".to_string()).into());
+        }
+        let effort = format!("{}:{:02}", minutes / 60, minutes % 60);
+        set_headline_property(&mut lines, headline_line, "EFFORT", &effort);
+        self.update_document(path, lines.join("\n"))
+    }
+
+    /// Moves a headline's priority one step towards `highest`, inserting the default cookie
+    /// when it has none.
+    pub fn raise_priority(&self, path: impl AsRef<Path>, headline_line: usize) -> Result<()> {
+        let next = match self.headline_priority(&path, headline_line)? {
+            Some(current) if current > self.priorities.highest => {
+                char::from_u32(current as u32 - 1).unwrap_or(current)
+            }
+            Some(current) => current,
+            None => self.priorities.default,
+        };
+        self.set_priority(path, headline_line, Some(next))
+    }
+
+    /// Moves a headline's priority one step towards `lowest`, inserting the default cookie
+    /// when it has none and removing the cookie when lowered past `lowest`.
+    pub fn lower_priority(&self, path: impl AsRef<Path>, headline_line: usize) -> Result<()> {
+        let next = match self.headline_priority(&path, headline_line)? {
+            Some(current) if current >= self.priorities.lowest => None,
+            Some(current) => char::from_u32(current as u32 + 1),
+            None => Some(self.priorities.default),
+        };
+        self.set_priority(path, headline_line, next)
+    }
+
+    /// Lexical blocks of the document at `path`, with code block fence languages mapped through
+    /// the service's [`LanguageAliases`].
+    pub fn lexical_nodes(&self, path: impl AsRef<Path>) -> Result<Vec<lexical::LexicalNode>> {
+        let doc = self.get_document(path)?;
+        let mut nodes = lexical::document_to_lexical(&doc);
+        for node in &mut nodes {
+            if let lexical::LexicalNode::CodeBlock {
+                language: Some(language),
+                fence_language,
+                ..
+            } = node
+            {
+                *fence_language = Some(self.language_aliases.fence_language(language).to_string());
+            }
+        }
+        Ok(nodes)
+    }
+
+    pub fn export_markdown(&self, path: impl AsRef<Path>) -> Result<String> {
+        let nodes = self.lexical_nodes(path)?;
+        Ok(markdown::lexical_to_markdown_with(
+            &nodes,
+            &self.language_aliases,
+        ))
+    }
+
+    /// Converts `text` to org and writes it as a new document at `dest`, which must be an
+    /// `.org` path that does not exist yet.
+    pub fn import_text(
+        &self,
+        dest: impl AsRef<Path>,
+        text: &str,
+        format: ImportFormat,
+    ) -> Result<()> {
+        let contents = match format {
+            ImportFormat::Markdown => markdown::markdown_to_org(text),
+        };
+        self.write_new_document(dest.as_ref(), &contents)
+    }
+
+    /// Creates a new org file at `relative_path` under the first document root with `initial`
+    /// as its contents, returning the absolute path. Existing files are never overwritten.
+    pub fn create_document(&self, relative_path: &Path, initial: &str) -> Result<PathBuf> {
+        let root = self
+            .roots
+            .first()
+            .filter(|root| !self.root_is_file(root))
+            .ok_or_else(|| {
+                OrgError::InvalidInput("no document root directory configured".to_string())
+            })?;
+        ensure_inside_root(relative_path)?;
+        let dest = root.join(relative_path);
+        self.write_new_document(&dest, initial)?;
+        Ok(dest)
+    }
+
+    /// The daily note for `date` in the dailies directory of `root`, which must be one of the
+    /// service's root directories: an existing file whose name starts with the `YYYY-MM-DD`
+    /// date, or a new `YYYY-MM-DD.org` titled with the date, as org-roam-dailies lays them out.
+    pub fn find_or_create_daily(&self, root: impl AsRef<Path>, date: NaiveDate) -> Result<PathBuf> {
+        let root = canonical_path(root.as_ref());
+        if !self.roots.contains(&root) || self.root_is_file(&root) {
+            return Err(OrgError::InvalidInput(format!(
+                "{} is not a document root directory",
+                root.display()
+            ))
+            .into());
+        }
+        ensure_inside_root(&self.dailies_dir)?;
+        let dailies_dir = &root.join(&self.dailies_dir);
+        let stamp = date.format("%Y-%m-%d").to_string();
+        if dailies_dir.is_dir() {
+            let mut existing: Vec<PathBuf> = fs::read_dir(dailies_dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && self.is_org_file(path))
+                .filter(|path| {
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .is_some_and(|stem| stem.starts_with(&stamp))
+                })
+                .collect();
+            existing.sort();
+            if let Some(path) = existing.into_iter().next() {
+                return Ok(path);
+            }
+        }
+        let dest = dailies_dir.join(format!("{stamp}.org"));
+        self.write_new_document(&dest, &format!("#+TITLE: {stamp}\n"))?;
+        Ok(dest)
+    }
+
+    pub fn add_agenda_entry(
+        &self,
+        target: impl AsRef<Path>,
+        title: &str,
+        date: NaiveDate,
+    ) -> Result<()> {
+        let target_path = target.as_ref();
+        let doc = self.get_document(target_path)?;
+        let mut contents = doc.raw().to_string();
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&format!(
+            "* TODO {}\nSCHEDULED: <{}>\n\n",
+            title,
+            date.format("%Y-%m-%d")
+        ));
+        self.update_document(target_path, contents)
+    }
+    pub fn watch(&mut self) -> Result<()> {
         if self.watcher.is_some() {
             return Ok(());
         }
-        let mut watcher = notify::recommended_watcher(|res: notify::Result<notify::Event>| {
+        let watcher = notify::recommended_watcher(|res: notify::Result<notify::Event>| {
             if let Ok(event) = res {
                 tracing::debug!(?event, "filesystem change detected");
             }
         })?;
+        self.install_watcher(watcher)
+    }
+
+    /// Watches every root and calls `callback` for each change to an org document. Any watcher
+    /// already installed, including the logging one from `watch`, is replaced.
+    pub fn watch_with(&mut self, callback: impl Fn(ChangeEvent) + Send + 'static) -> Result<()> {
+        let extensions = self.extensions.clone();
+        let watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) => ChangeEvent::from_notify(event, &extensions).for_each(&callback),
+                Err(err) => tracing::warn!(?err, "filesystem watch error"),
+            })?;
+        self.install_watcher(watcher)
+    }
+}
+
+impl OrgService {
+    fn install_watcher(&mut self, mut watcher: RecommendedWatcher) -> Result<()> {
         for root in self.unique_roots() {
-            let mode = if Self::root_is_file(&root) {
+            let mode = if self.root_is_file(&root) {
                 RecursiveMode::NonRecursive
             } else {
                 RecursiveMode::Recursive
@@ -341,16 +1709,65 @@ impl OrgService {
         self.watcher = Some(watcher);
         Ok(())
     }
-}
 
-impl OrgService {
+    fn headline_priority(
+        &self,
+        path: impl AsRef<Path>,
+        headline_line: usize,
+    ) -> Result<Option<char>> {
+        let doc = self.get_document(path)?;
+        let line = doc
+            .raw()
+            .lines()
+            .nth(headline_line)
+            .ok_or_else(|| OrgError::NotFound("unable to locate headline".to_string()))?;
+        let keywords = agenda::TodoKeywords::from_document(doc.raw());
+        Ok(split_priority(line, &keywords).1)
+    }
+
+    fn priority_in_range(&self, priority: char) -> bool {
+        (self.priorities.highest..=self.priorities.lowest).contains(&priority)
+    }
+
+    fn write_new_document(&self, dest: &Path, contents: &str) -> Result<()> {
+        if !self.is_org_file(dest) {
+            return Err(
+                OrgError::InvalidInput(format!("{} is not an .org file", dest.display())).into(),
+            );
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // `create_new` fails atomically if the file appears between the caller's checks and
+        // this write, so an existing file is never overwritten.
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(dest) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                return Err(
+                    OrgError::AlreadyExists(format!("{} already exists", dest.display())).into(),
+                );
+            }
+            Err(err) => return Err(err.into()),
+        };
+        file.write_all(contents.as_bytes())?;
+        self.reload_document(dest)
+    }
+
+    /// Performs the initial scan for services created with `build_lazy`.
+    fn ensure_loaded(&self) -> Result<()> {
+        if self.loaded.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        self.reload_all()
+    }
+
     fn watch_path(&mut self, path: &Path) -> Result<()> {
+        let mode = if self.root_is_file(path) {
+            RecursiveMode::NonRecursive
+        } else {
+            RecursiveMode::Recursive
+        };
         if let Some(watcher) = &mut self.watcher {
-            let mode = if Self::root_is_file(path) {
-                RecursiveMode::NonRecursive
-            } else {
-                RecursiveMode::Recursive
-            };
             watcher.watch(path, mode)?;
         }
         Ok(())
@@ -360,57 +1777,802 @@ impl OrgService {
         self.roots.clone()
     }
 
-    fn ingest_root(&self, docs: &mut HashMap<PathBuf, OrgDocument>, path: &Path) -> Result<()> {
-        if path.is_file() || Self::root_is_file(path) {
-            if Self::is_org_file(path) {
-                let doc = OrgDocument::load(path)?;
-                docs.insert(path.to_path_buf(), doc);
+    fn ingest_root(
+        &self,
+        docs: &mut HashMap<PathBuf, OrgDocument>,
+        path: &Path,
+        cached: &mut HashMap<PathBuf, OrgDocument>,
+    ) -> Result<()> {
+        let mut paths = Vec::new();
+        for entry_path in self.collect_org_paths(path)? {
+            match cached.remove(&entry_path) {
+                Some(doc) if doc.is_current_on_disk() => {
+                    docs.insert(entry_path, doc);
+                }
+                _ => paths.push(entry_path),
             }
-            return Ok(());
+        }
+        let workers = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+        let chunk_size = paths.len().div_ceil(workers).max(1);
+        let loaded = thread::scope(|scope| {
+            let handles: Vec<_> = paths
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|entry_path| {
+                                Ok((entry_path.clone(), OrgDocument::load(entry_path)?))
+                            })
+                            .collect::<Result<Vec<_>>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().map_err(|_| {
+                        OrgError::Internal("document loader thread panicked".to_string())
+                    })?
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+        docs.extend(loaded.into_iter().flatten());
+        Ok(())
+    }
+
+    fn collect_org_paths(&self, path: &Path) -> Result<Vec<PathBuf>> {
+        if path.is_file() || self.root_is_file(path) {
+            if self.is_org_file(path) {
+                return Ok(vec![path.to_path_buf()]);
+            }
+            return Ok(Vec::new());
         }
 
+        let mut paths = Vec::new();
         if path.is_dir() {
             for entry in WalkDir::new(path) {
                 let entry = entry?;
                 let entry_path = entry.path();
-                if entry.file_type().is_file() && Self::is_org_file(entry_path) {
-                    let doc = OrgDocument::load(entry_path)?;
-                    docs.insert(entry_path.to_path_buf(), doc);
+                if entry.file_type().is_file() && self.is_org_file(entry_path) {
+                    paths.push(entry_path.to_path_buf());
                 }
             }
         }
-        Ok(())
+        Ok(paths)
     }
 
-    fn path_in_roots(path: &Path, roots: &[PathBuf]) -> bool {
-        if roots.is_empty() {
+    fn path_in_roots(&self, path: &Path) -> bool {
+        if self.roots.is_empty() {
             return true;
         }
-        roots
+        self.roots
             .iter()
-            .any(|root| Self::root_contains_path(root, path))
+            .any(|root| self.root_contains_path(root, path))
     }
 
-    fn root_contains_path(root: &Path, path: &Path) -> bool {
-        if Self::root_is_file(root) {
+    fn root_contains_path(&self, root: &Path, path: &Path) -> bool {
+        if self.root_is_file(root) {
             path == root
         } else {
             path.starts_with(root)
         }
     }
 
-    fn root_is_file(path: &Path) -> bool {
-        Self::extension_is_org(path) || path.is_file()
+    fn root_is_file(&self, path: &Path) -> bool {
+        self.is_org_file(path) || path.is_file()
+    }
+
+    fn is_org_file(&self, path: &Path) -> bool {
+        extension_matches(&self.extensions, path)
     }
+}
+
+fn extension_matches(extensions: &[String], path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// The lines after `headline_line` up to the end of its subtree.
+fn subtree_body(lines: &[&str], headline_line: usize) -> Range<usize> {
+    agenda::subtree_range(lines, headline_line)
+        .map(|range| headline_line + 1..range.end)
+        .unwrap_or_default()
+}
 
-    fn is_org_file(path: &Path) -> bool {
-        Self::extension_is_org(path)
+/// Ranks how well `title` matches `needle` (both lowercase); lower is better.
+fn headline_match_rank(title: &str, needle: &str) -> Option<(u8, usize)> {
+    if title.starts_with(needle) {
+        return Some((0, 0));
+    }
+    if let Some(pos) = title.find(needle) {
+        let at_word_start = title[..pos].ends_with(|c: char| !c.is_alphanumeric());
+        return Some((if at_word_start { 1 } else { 2 }, pos));
+    }
+    let mut chars = title.char_indices();
+    let mut first = None;
+    let mut last = 0;
+    for wanted in needle.chars() {
+        let (pos, _) = chars.find(|(_, c)| *c == wanted)?;
+        first.get_or_insert(pos);
+        last = pos;
     }
+    Some((3, last - first.unwrap_or(0)))
+}
 
-    fn extension_is_org(path: &Path) -> bool {
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.eq_ignore_ascii_case("org"))
-            .unwrap_or(false)
+/// `path` with symlinks, `.` and `..` resolved, the form roots and document keys are stored
+/// in. A path that does not exist yet is resolved through its parent directory, or else kept
+/// as given.
+/// Rejects `relative_path` unless it names a path below a root, with no absolute, `.`, or `..`
+/// components.
+fn ensure_inside_root(relative_path: &Path) -> Result<()> {
+    let escapes_root = relative_path
+        .components()
+        .any(|component| !matches!(component, std::path::Component::Normal(_)));
+    if escapes_root {
+        return Err(OrgError::InvalidInput(format!(
+            "{} must be a relative path inside the root",
+            relative_path.display()
+        ))
+        .into());
     }
+    Ok(())
+}
+
+/// The instant a reminder for local wall-clock time `naive` fires. A time repeated by a DST
+/// change takes its earlier instant; one skipped by a DST change moves an hour later.
+fn local_reminder_time(naive: NaiveDateTime) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(naive + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map_or_else(
+            || Utc.from_utc_datetime(&naive),
+            |local| local.with_timezone(&Utc),
+        )
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = fs::canonicalize(path) {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => fs::canonicalize(parent)
+            .map(|dir| dir.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Resolves `path` for writing: its deepest existing ancestor is canonicalized and the
+/// components that do not exist yet are appended as written. Returns `None` when one of
+/// those components is `..`, which could climb out of wherever the ancestor resolved to.
+fn resolve_write_target(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(mut resolved) = fs::canonicalize(existing) {
+            resolved.extend(missing.iter().rev());
+            return Some(resolved);
+        }
+        missing.push(existing.file_name()?);
+        existing = match existing.parent()? {
+            parent if parent.as_os_str().is_empty() => Path::new("."),
+            parent => parent,
+        };
+    }
+}
+
+/// Folds `.` and `..` components out of `path` without touching the filesystem, so link
+/// targets match the keys documents were loaded under.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The line of the headline a link search option names: `*Title` matches the headline text
+/// without keyword, priority, or tags; `#id` matches a `:CUSTOM_ID:` property; anything else
+/// is tried as a headline title.
+fn find_link_headline(raw: &str, search: &str) -> Option<usize> {
+    let keywords = agenda::TodoKeywords::from_document(raw);
+    if let Some(custom_id) = search.strip_prefix('#') {
+        let mut headline = None;
+        for (idx, line) in raw.lines().enumerate() {
+            if agenda::headline_depth(line).is_some() {
+                headline = Some(idx);
+                continue;
+            }
+            let Some((key, value)) = line
+                .trim()
+                .strip_prefix(':')
+                .and_then(|rest| rest.split_once(':'))
+            else {
+                continue;
+            };
+            if key.eq_ignore_ascii_case("CUSTOM_ID") && value.trim() == custom_id {
+                return headline;
+            }
+        }
+        return None;
+    }
+    let wanted = search.strip_prefix('*').unwrap_or(search).trim();
+    raw.lines().enumerate().find_map(|(idx, line)| {
+        agenda::headline_depth(line)?;
+        let (_, title, _) = agenda::parse_headline(line, &keywords);
+        let title = agenda::split_priority_cookie(&title)
+            .map(|(_, rest)| rest)
+            .unwrap_or(&title)
+            .trim();
+        (title == wanted).then_some(idx)
+    })
+}
+
+/// Re-pads a rewritten headline so its trailing tags end in the same column as they did in
+/// `original`. Lines whose tags were not padded (a single space before them) are left alone.
+fn realign_tags(original: &str, updated: String) -> String {
+    let Some((original_head, _)) = split_tags(original) else {
+        return updated;
+    };
+    let padding = original[original_head.len()..]
+        .chars()
+        .take_while(|c| *c == ' ')
+        .count();
+    if padding < 2 {
+        return updated;
+    }
+    let Some((head, tags)) = split_tags(&updated) else {
+        return updated;
+    };
+    let target_end = original.trim_end().chars().count();
+    let used = head.chars().count() + tags.chars().count();
+    let pad = target_end.saturating_sub(used).max(1);
+    format!("{}{}{}", head, " ".repeat(pad), tags)
+}
+
+/// Splits `raw` into the text to keep and the DONE subtrees to archive, adding an
+/// `:ARCHIVE_TIME:` property to each archived headline's drawer.
+fn split_done_subtrees(raw: &str, stamp: &str) -> (String, String, usize) {
+    let lines: Vec<&str> = raw.lines().collect();
+    let keywords = agenda::TodoKeywords::from_document(raw);
+    let mut kept = Vec::new();
+    let mut archived = String::new();
+    let mut count = 0;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx];
+        let is_done = agenda::headline_depth(line).is_some()
+            && agenda::parse_headline(line, &keywords)
+                .0
+                .is_some_and(|keyword| keywords.is_done(&keyword));
+        let Some(range) = agenda::subtree_range(&lines, idx).filter(|_| is_done) else {
+            kept.push(line);
+            idx += 1;
+            continue;
+        };
+        for entry in with_archive_time(&lines[range.clone()], stamp) {
+            archived.push_str(&entry);
+            archived.push('\n');
+        }
+        count += 1;
+        idx = range.end;
+    }
+    let mut kept = kept.join("\n");
+    if raw.ends_with('\n') && !kept.is_empty() {
+        kept.push('\n');
+    }
+    (kept, archived, count)
+}
+
+/// Returns `subtree` with `:ARCHIVE_TIME:` added to the headline's property drawer, creating
+/// the drawer after any planning line when the headline has none.
+fn with_archive_time(subtree: &[&str], stamp: &str) -> Vec<String> {
+    let mut out: Vec<String> = subtree.iter().map(|line| line.to_string()).collect();
+    set_headline_property(&mut out, 0, "ARCHIVE_TIME", stamp);
+    out
+}
+
+/// Rewrites the SCHEDULED or DEADLINE timestamp of `item` in `lines` to `date`.
+fn retime_item(
+    lines: &mut [String],
+    item: &agenda::AgendaItem,
+    date: NaiveDate,
+    drop_repeater: bool,
+) -> Result<()> {
+    let keyword = match item.kind {
+        agenda::AgendaKind::Scheduled => "SCHEDULED:",
+        agenda::AgendaKind::Deadline => "DEADLINE:",
+        agenda::AgendaKind::Appointment | agenda::AgendaKind::Floating => {
+            return Err(OrgError::InvalidInput(
+                "only scheduled or deadline items can be rescheduled".to_string(),
+            )
+            .into())
+        }
+    };
+    let planning = lines
+        .iter()
+        .enumerate()
+        .skip(item.headline_line + 1)
+        .take_while(|(_, line)| agenda::is_planning_line(line.trim()))
+        .find_map(|(idx, line)| {
+            let at = line.find(keyword)?;
+            let open = at + line[at..].find('<')?;
+            let close = open + line[open..].find('>')?;
+            Some((idx, open, close))
+        });
+    let Some((idx, open, close)) = planning else {
+        return Err(OrgError::NotFound(format!("unable to locate {} timestamp", keyword)).into());
+    };
+    let stamp = retimestamp(&lines[idx][open + 1..close], date, drop_repeater);
+    lines[idx].replace_range(open + 1..close, &stamp);
+    Ok(())
+}
+
+/// A repeating timestamp on a headline's planning lines and the occurrence it moves to.
+struct RepeatAdvance {
+    line: usize,
+    /// Byte range of the text between `<` and `>`.
+    range: std::ops::Range<usize>,
+    stamp: String,
+    date: NaiveDate,
+}
+
+/// The next occurrence of every repeating active timestamp in the planning lines under the
+/// headline at `headline_line`, in line order. `+` repeaters shift once, `++` shift until
+/// past `today`, and `.+` restart from `today`.
+fn repeat_advances(lines: &[String], headline_line: usize, today: NaiveDate) -> Vec<RepeatAdvance> {
+    let mut advances = Vec::new();
+    let planning = lines
+        .iter()
+        .enumerate()
+        .skip(headline_line + 1)
+        .take_while(|(_, line)| agenda::is_planning_line(line.trim()));
+    for (line_idx, line) in planning {
+        let mut offset = 0;
+        while let Some(open) = line[offset..].find('<').map(|pos| offset + pos) {
+            let Some(close) = line[open..].find('>').map(|pos| open + pos) else {
+                break;
+            };
+            offset = close + 1;
+            let inner = &line[open + 1..close];
+            let Some(date) = leading_date(inner) else {
+                continue;
+            };
+            let Some(token) = inner
+                .split_whitespace()
+                .find(|token| token.starts_with('+') || token.starts_with(".+"))
+            else {
+                continue;
+            };
+            let Some(repeater) = agenda::parse_repeater(token) else {
+                continue;
+            };
+            let next = if token.starts_with(".+") {
+                repeater.advance_once(today)
+            } else if token.starts_with("++") {
+                let mut next = repeater.advance_once(date);
+                while let Some(candidate) = next.filter(|candidate| *candidate <= today) {
+                    next = repeater.advance_once(candidate);
+                }
+                next
+            } else {
+                repeater.advance_once(date)
+            };
+            let Some(next) = next else {
+                continue;
+            };
+            advances.push(RepeatAdvance {
+                line: line_idx,
+                range: open + 1..close,
+                stamp: retimestamp(inner, next, false),
+                date: next,
+            });
+        }
+    }
+    advances
+}
+
+/// The `YYYY-MM-DD` date at the start of a timestamp's contents.
+fn leading_date(inner: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(inner.split_whitespace().next()?, "%Y-%m-%d").ok()
+}
+
+/// Index of the first line after the headline at `headline_line` and its planning lines,
+/// where a property drawer belongs.
+fn property_drawer_start<S: AsRef<str>>(lines: &[S], headline_line: usize) -> usize {
+    let mut at = headline_line + 1;
+    while lines
+        .get(at)
+        .is_some_and(|line| agenda::is_planning_line(line.as_ref().trim()))
+    {
+        at += 1;
+    }
+    at
+}
+
+/// The value of `key` in the property drawer of the headline at `headline_line`.
+fn headline_property(lines: &[String], headline_line: usize, key: &str) -> Option<String> {
+    let start = property_drawer_start(lines, headline_line);
+    if lines.get(start)?.trim() != ":PROPERTIES:" {
+        return None;
+    }
+    lines[start + 1..]
+        .iter()
+        .map(|line| line.trim())
+        .take_while(|line| *line != ":END:")
+        .find_map(|line| {
+            let (name, value) = line.strip_prefix(':')?.split_once(':')?;
+            name.eq_ignore_ascii_case(key)
+                .then(|| value.trim().to_string())
+        })
+}
+
+/// Sets `key` in the property drawer of the headline at `headline_line`, replacing an
+/// existing value and creating the drawer after any planning lines when there is none.
+fn set_headline_property(lines: &mut Vec<String>, headline_line: usize, key: &str, value: &str) {
+    let at = property_drawer_start(lines, headline_line);
+    let property = format!(":{}: {}", key, value);
+    if lines
+        .get(at)
+        .is_some_and(|line| line.trim() == ":PROPERTIES:")
+    {
+        let indent: String = lines[at]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let end = lines[at..]
+            .iter()
+            .position(|line| line.trim() == ":END:")
+            .map(|offset| at + offset);
+        if let Some(end) = end {
+            let existing = (at + 1..end).find(|idx| {
+                lines[*idx]
+                    .trim()
+                    .strip_prefix(':')
+                    .and_then(|rest| rest.split_once(':'))
+                    .is_some_and(|(name, _)| name.eq_ignore_ascii_case(key))
+            });
+            match existing {
+                Some(idx) => lines[idx] = format!("{}{}", indent, property),
+                None => lines.insert(end, format!("{}{}", indent, property)),
+            }
+            return;
+        }
+    }
+    lines.splice(
+        at..at,
+        [":PROPERTIES:".to_string(), property, ":END:".to_string()],
+    );
+}
+
+/// Rewrites the inside of a `<...>` timestamp for `date`, recomputing the weekday and keeping
+/// time, warning, and (unless `drop_repeater`) repeater tokens.
+fn retimestamp(inner: &str, date: NaiveDate, drop_repeater: bool) -> String {
+    let mut parts = vec![date.format("%Y-%m-%d %a").to_string()];
+    for token in inner.split_whitespace().skip(1) {
+        let is_weekday = token.chars().all(char::is_alphabetic);
+        let is_repeater = token.starts_with('+') || token.starts_with(".+");
+        let dropped = is_weekday || (drop_repeater && is_repeater);
+        if !dropped {
+            parts.push(token.to_string());
+        }
+    }
+    parts.join(" ")
+}
+
+/// The value of an `:ID:` property line.
+fn id_property(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    trimmed
+        .get(..4)
+        .filter(|key| key.eq_ignore_ascii_case(":ID:"))
+        .map(|_| trimmed[4..].trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Rewrites the `[[...]]` links in `text`, written in a file in `from_dir`, for a file in
+/// `to_dir`: `file:` links and bare `[[name]]` links to `src` are pointed at `dest`, and other
+/// relative `file:` links are rebased when the directory changes.
+fn retarget_links(text: &str, from_dir: &Path, to_dir: &Path, src: &Path, dest: &Path) -> String {
+    let stem = |path: &Path| {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let retarget = |target: &str| -> Option<String> {
+        let Some(rest) = target
+            .get(..5)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("file:"))
+            .map(|_| &target[5..])
+        else {
+            if target == stem(src) {
+                return Some(stem(dest));
+            }
+            return (target == name(src)).then(|| name(dest));
+        };
+        let (file, search) = match rest.split_once("::") {
+            Some((file, search)) => (file, Some(search)),
+            None => (rest, None),
+        };
+        if file.is_empty() {
+            return None;
+        }
+        let resolved = normalize_lexically(&from_dir.join(file));
+        let moved = resolved == src;
+        if !moved && from_dir == to_dir {
+            return None;
+        }
+        let resolved = if moved { dest.to_path_buf() } else { resolved };
+        let file = if Path::new(file).is_absolute() {
+            resolved
+        } else {
+            relative_path(to_dir, &resolved)
+        };
+        let mut link = format!("{}{}", &target[..5], file.display());
+        if let Some(search) = search {
+            link.push_str("::");
+            link.push_str(search);
+        }
+        Some(link)
+    };
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("[[") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("]]") else {
+            break;
+        };
+        let inner = &after[..end];
+        let target_len = inner.find("][").unwrap_or(inner.len());
+        out.push_str(&rest[..start + 2]);
+        match retarget(&inner[..target_len]) {
+            Some(target) => out.push_str(&target),
+            None => out.push_str(&inner[..target_len]),
+        }
+        out.push_str(&inner[target_len..]);
+        rest = &after[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `to` expressed relative to the directory `from_dir`, using `..` where needed.
+fn relative_path(from_dir: &Path, to: &Path) -> PathBuf {
+    let from: Vec<_> = from_dir.components().collect();
+    let target: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &target[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+/// Rewrites tag `old` as `new` in headline tag blocks and `#+FILETAGS` lines of `raw`.
+fn rename_tag_in(raw: &str, old: &str, new: &str) -> (String, usize) {
+    let rename = |tags: Vec<&str>| -> Option<Vec<String>> {
+        if !tags.contains(&old) {
+            return None;
+        }
+        let mut renamed: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = if tag == old { new } else { tag };
+            if !renamed.iter().any(|existing| existing == tag) {
+                renamed.push(tag.to_string());
+            }
+        }
+        Some(renamed)
+    };
+
+    let mut changed = 0;
+    let mut lines: Vec<String> = Vec::new();
+    for line in raw.lines() {
+        let updated = if agenda::headline_depth(line).is_some() {
+            split_tags(line).and_then(|(head, block)| {
+                let tags = rename(block.trim_matches(':').split(':').collect())?;
+                Some(realign_tags(line, format!("{} :{}:", head, tags.join(":"))))
+            })
+        } else {
+            file_tags_value(line).and_then(|(prefix, value)| {
+                let colon_style = value.contains(':');
+                let tags = rename(
+                    value
+                        .split(|c: char| c == ':' || c.is_whitespace())
+                        .filter(|tag| !tag.is_empty())
+                        .collect(),
+                )?;
+                Some(if colon_style {
+                    format!("{} :{}:", prefix, tags.join(":"))
+                } else {
+                    format!("{} {}", prefix, tags.join(" "))
+                })
+            })
+        };
+        match updated {
+            Some(updated) => {
+                changed += 1;
+                lines.push(updated);
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+    let mut contents = lines.join("\n");
+    if raw.ends_with('\n') {
+        contents.push('\n');
+    }
+    (contents, changed)
+}
+
+/// Splits a `#+FILETAGS:` line into its keyword prefix and the tag value.
+fn file_tags_value(line: &str) -> Option<(&str, &str)> {
+    let offset = line.len() - line.trim_start().len();
+    let keyword = line.get(offset..offset + 11)?;
+    keyword
+        .eq_ignore_ascii_case("#+FILETAGS:")
+        .then(|| (&line[..offset + 11], line[offset + 11..].trim()))
+}
+
+/// Splits a headline after its stars and TODO keyword, returning that prefix, the priority
+/// cookie if present, and the remaining title text.
+fn split_priority<'a>(
+    line: &'a str,
+    keywords: &agenda::TodoKeywords,
+) -> (String, Option<char>, &'a str) {
+    let stars = line.chars().take_while(|c| *c == '*').count();
+    let rest = line[stars..].trim_start();
+    let first = rest.split_whitespace().next().unwrap_or("");
+    let (keyword, rest) = if keywords.contains(first) {
+        (Some(first), rest[first.len()..].trim_start())
+    } else {
+        (None, rest)
+    };
+    let (cookie, title) = match agenda::split_priority_cookie(rest) {
+        Some((priority, title)) => (Some(priority), title.trim_start()),
+        None => (None, rest),
+    };
+    let mut prefix = "*".repeat(stars);
+    if let Some(keyword) = keyword {
+        prefix.push(' ');
+        prefix.push_str(keyword);
+    }
+    (prefix, cookie, title)
+}
+
+/// Done and total counts for a headline's statistics cookie: its least-indented body
+/// checkboxes, or its direct child headlines that carry a TODO keyword.
+fn headline_statistics(
+    lines: &[&str],
+    headline_line: usize,
+    keywords: &agenda::TodoKeywords,
+) -> (usize, usize) {
+    let Some(range) = agenda::subtree_range(lines, headline_line) else {
+        return (0, 0);
+    };
+    let depth = agenda::headline_depth(lines[headline_line]).unwrap_or(1);
+    let body_end = (headline_line + 1..range.end)
+        .find(|&idx| agenda::headline_depth(lines[idx]).is_some())
+        .unwrap_or(range.end);
+
+    let boxes: Vec<(usize, bool)> = lines[headline_line + 1..body_end]
+        .iter()
+        .filter_map(|line| checkbox_state(line))
+        .collect();
+    if let Some(top) = boxes.iter().map(|(indent, _)| *indent).min() {
+        let top_level = boxes.iter().filter(|(indent, _)| *indent == top);
+        let total = top_level.clone().count();
+        let done = top_level.filter(|(_, checked)| *checked).count();
+        return (done, total);
+    }
+
+    let mut done = 0;
+    let mut total = 0;
+    for line in &lines[body_end..range.end] {
+        if agenda::headline_depth(line) != Some(depth + 1) {
+            continue;
+        }
+        if let (Some(keyword), _, _) = agenda::parse_headline(line, keywords) {
+            total += 1;
+            done += usize::from(keywords.done.contains(&keyword));
+        }
+    }
+    (done, total)
+}
+
+/// The indentation and checked state of a `- [ ]` / `- [X]` list item. A partial `[-]` box
+/// counts as unchecked.
+fn checkbox_state(line: &str) -> Option<(usize, bool)> {
+    let trimmed = line.trim_start();
+    let (_, rest) = lexical::split_list_marker(trimmed)?;
+    let checked = if rest.starts_with("[X]") || rest.starts_with("[x]") {
+        true
+    } else if rest.starts_with("[ ]") || rest.starts_with("[-]") {
+        false
+    } else {
+        return None;
+    };
+    Some((line.len() - trimmed.len(), checked))
+}
+
+/// Rewrites every `[n/m]` and `[n%]` cookie in `line` for `done` of `total`.
+fn with_statistics(line: &str, done: usize, total: usize) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let tail = &rest[open..];
+        let Some(close) = tail.find(']') else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        let inner = &tail[1..close];
+        let is_fraction = inner.split_once('/').is_some_and(|(a, b)| {
+            a.chars().all(|c| c.is_ascii_digit()) && b.chars().all(|c| c.is_ascii_digit())
+        });
+        let is_percent = inner
+            .strip_suffix('%')
+            .is_some_and(|digits| digits.chars().all(|c| c.is_ascii_digit()));
+        if is_fraction {
+            out.push_str(&format!("[{}/{}]", done, total));
+        } else if is_percent {
+            out.push_str(&format!(
+                "[{}%]",
+                (done * 100).checked_div(total).unwrap_or(0)
+            ));
+        } else {
+            out.push_str(&tail[..=close]);
+        }
+        rest = &tail[close + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn with_priority(line: &str, priority: Option<char>, keywords: &agenda::TodoKeywords) -> String {
+    let (mut updated, _, title) = split_priority(line, keywords);
+    if let Some(priority) = priority {
+        updated.push_str(&format!(" [#{}]", priority));
+    }
+    if !title.is_empty() {
+        updated.push(' ');
+        updated.push_str(title);
+    }
+    updated
+}
+
+/// Splits a headline into the text before its tag block and the `:tag:` block itself.
+fn split_tags(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_end();
+    let (head, tags) = trimmed.rsplit_once(' ')?;
+    let is_tag_block = tags.len() > 2
+        && tags.starts_with(':')
+        && tags.ends_with(':')
+        && !tags.contains(char::is_whitespace);
+    is_tag_block.then(|| (head.trim_end(), tags))
 }
@@ -86,4 +86,23 @@ fn agenda_habits_and_capture_round_trip() {
     assert!(nodes
         .iter()
         .any(|node| node.id.contains("20250102090000-review")));
+
+    let dot = graph.to_dot();
+    let body = dot
+        .strip_prefix("digraph roam {\n")
+        .and_then(|rest| rest.strip_suffix("}\n"))
+        .expect("dot output is a digraph block");
+    let statements: Vec<&str> = body.lines().map(str::trim).collect();
+    assert!(statements.iter().all(|line| line.ends_with(';')));
+    let node_lines = statements
+        .iter()
+        .filter(|line| line.contains("[label="))
+        .count();
+    let edge_lines = statements
+        .iter()
+        .filter(|line| line.contains(" -> "))
+        .count();
+    assert_eq!(node_lines, nodes.len());
+    assert_eq!(edge_lines, 2);
+    assert!(dot.contains(r#""20250101090000-daily" -> "20250102090000-review";"#));
 }
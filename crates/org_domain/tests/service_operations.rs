@@ -0,0 +1,1729 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{Days, Local, NaiveDate};
+use org_domain::agenda::{AgendaFilter, AgendaItem};
+use org_domain::habit::Habit;
+use org_domain::lexical::LexicalNode;
+use org_domain::markdown::LanguageAliases;
+use org_domain::notifications::{
+    agenda_notification_key, habit_notification_key, NotificationRequest, NotificationSink,
+    TimerNotificationSink,
+};
+use org_domain::service::{ChangeEvent, DocumentSort, ImportFormat, OrgService};
+use org_domain::OrgError;
+use tempfile::tempdir;
+
+fn write_file(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent dirs");
+    }
+    fs::write(path, contents).expect("write fixture");
+}
+
+fn service_for(root: &Path) -> OrgService {
+    OrgService::builder()
+        .add_root(root)
+        .build()
+        .expect("build org service")
+}
+
+#[test]
+fn headlines_matching_ranks_exact_prefix_first_across_files() {
+    let temp = tempdir().expect("tempdir");
+    let inbox = temp.path().join("inbox.org");
+    let projects = temp.path().join("projects/work.org");
+    write_file(&inbox, "* Weekly review\n* TODO Groceries\n");
+    write_file(
+        &projects,
+        "* TODO Review quarterly goals\n** Peer reviews\n* Retro view\n",
+    );
+    let service = service_for(temp.path());
+
+    let matches = service.headlines_matching("review");
+    let titles: Vec<&str> = matches.iter().map(|m| m.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec![
+            "Review quarterly goals",
+            "Peer reviews",
+            "Weekly review",
+            "Retro view"
+        ]
+    );
+    assert_eq!(matches[0].path, projects);
+    assert_eq!(matches[0].todo_keyword.as_deref(), Some("TODO"));
+    assert_eq!(matches[2].path, inbox);
+    assert_eq!(matches[2].line, 0);
+    assert!(service.headlines_matching("   ").is_empty());
+}
+
+#[test]
+fn reload_document_refreshes_only_the_given_file() {
+    let temp = tempdir().expect("tempdir");
+    let first = temp.path().join("first.org");
+    let second = temp.path().join("second.org");
+    write_file(&first, "* TODO Original\n");
+    write_file(&second, "* TODO Untouched\n");
+    let service = service_for(temp.path());
+
+    write_file(&first, "* TODO Edited outside the app\n");
+    write_file(&second, "* TODO Also edited\n");
+    service.reload_document(&first).expect("reload first");
+    let first_doc = service.get_document(&first).expect("first loaded");
+    let second_doc = service.get_document(&second).expect("second loaded");
+    assert!(first_doc.raw().contains("Edited outside"));
+    assert!(second_doc.raw().contains("Untouched"));
+
+    let created = temp.path().join("new.org");
+    write_file(&created, "* Fresh\n");
+    service.reload_document(&created).expect("reload new file");
+    assert!(service.list_documents().contains(&created));
+
+    fs::remove_file(&first).expect("remove first");
+    service
+        .reload_document(&first)
+        .expect("reload removed file");
+    assert!(!service.list_documents().contains(&first));
+}
+
+#[test]
+fn flush_makes_external_writes_visible() {
+    let temp = tempdir().expect("tempdir");
+    let notes = temp.path().join("notes.org");
+    write_file(&notes, "* Draft\n");
+    let service = service_for(temp.path());
+    let modified = fs::metadata(&notes).unwrap().modified().unwrap();
+
+    // Same-length rewrite landing in the same mtime tick.
+    write_file(&notes, "* Redo\n");
+    fs::File::options()
+        .write(true)
+        .open(&notes)
+        .and_then(|file| file.set_modified(modified))
+        .expect("restore mtime");
+    service.flush().expect("flush");
+    assert_eq!(service.get_document(&notes).unwrap().raw(), "* Redo\n");
+
+    write_file(&notes, "* Draft\nWritten by another editor.\n");
+    let added = temp.path().join("later.org");
+    write_file(&added, "* Added later\n");
+    service.flush().expect("flush");
+
+    let doc = service.get_document(&notes).expect("notes loaded");
+    assert!(doc.raw().contains("another editor"));
+    assert!(service.list_documents().contains(&added));
+}
+
+#[test]
+fn reload_skips_files_with_unchanged_modified_time_unless_forced() {
+    let temp = tempdir().expect("tempdir");
+    let notes = temp.path().join("notes.org");
+    write_file(&notes, "* Original\n");
+    let service = service_for(temp.path());
+    let original_mtime = fs::metadata(&notes)
+        .and_then(|meta| meta.modified())
+        .expect("mtime");
+
+    // Same length as before, so only the mtime could reveal the change.
+    write_file(&notes, "* Replaced\n");
+    fs::File::options()
+        .write(true)
+        .open(&notes)
+        .and_then(|file| file.set_modified(original_mtime))
+        .expect("restore mtime");
+
+    service.reload_all().expect("reload");
+    let doc = service.get_document(&notes).expect("loaded");
+    assert!(doc.raw().contains("Original"), "unchanged mtime is skipped");
+
+    service.reload(true).expect("forced reload");
+    let doc = service.get_document(&notes).expect("loaded");
+    assert!(doc.raw().contains("Replaced"));
+}
+
+#[test]
+fn completing_keeps_padded_tags_in_their_column() {
+    let temp = tempdir().expect("tempdir");
+    let tasks = temp.path().join("tasks.org");
+    let original = "* WAITING Call plumber                 :home:\n* TODO Tight :work:\n";
+    write_file(&tasks, original);
+    let service = service_for(temp.path());
+
+    service
+        .complete_headline(&tasks, 0)
+        .expect("complete aligned");
+    service
+        .complete_headline(&tasks, 1)
+        .expect("complete tight");
+    let contents = fs::read_to_string(&tasks).expect("read tasks");
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines[0], "* DONE Call plumber                    :home:");
+    assert_eq!(lines[0].len(), original.lines().next().unwrap().len());
+    assert_eq!(lines[1], "* DONE Tight :work:");
+}
+
+#[test]
+fn agenda_item_source_returns_the_full_subtree_text() {
+    let temp = tempdir().expect("tempdir");
+    let tasks = temp.path().join("tasks.org");
+    write_file(
+        &tasks,
+        "* Projects\n** TODO Draft proposal :work:\nSCHEDULED: <2025-11-03 Mon>\n:PROPERTIES:\n:EFFORT: 1:00\n:END:\nOutline the budget first.\n*** Notes\nChild detail.\n** TODO Other task\n",
+    );
+    let service = service_for(temp.path());
+    let item = service
+        .agenda()
+        .expect("agenda")
+        .into_iter()
+        .find(|item| item.title == "Draft proposal")
+        .expect("scheduled item");
+
+    let source = service.agenda_item_source(&item).expect("source text");
+    assert_eq!(
+        source,
+        "** TODO Draft proposal :work:\nSCHEDULED: <2025-11-03 Mon>\n:PROPERTIES:\n:EFFORT: 1:00\n:END:\nOutline the budget first.\n*** Notes\nChild detail."
+    );
+}
+
+#[test]
+fn build_lazy_defers_scanning_until_first_query() {
+    let temp = tempdir().expect("tempdir");
+    for idx in 0..200 {
+        write_file(
+            &temp.path().join(format!("notes/{idx:03}.org")),
+            &format!("* TODO Task {idx}\nSCHEDULED: <2025-11-03 Mon>\n"),
+        );
+    }
+
+    let started = Instant::now();
+    let service = OrgService::builder().add_root(temp.path()).build_lazy();
+    assert!(started.elapsed() < Duration::from_millis(50));
+    assert!(service.list_documents().is_empty());
+
+    assert_eq!(service.agenda().expect("agenda").len(), 200);
+    assert_eq!(service.list_documents().len(), 200);
+
+    let explicit = OrgService::builder().add_root(temp.path()).build_lazy();
+    explicit.reload_all().expect("reload");
+    assert_eq!(explicit.list_documents().len(), 200);
+}
+
+#[test]
+fn watch_with_reports_modified_org_files() {
+    let temp = tempdir().expect("tempdir");
+    let notes = temp.path().join("notes.org");
+    write_file(&notes, "* Draft\n");
+    write_file(&temp.path().join("ignored.txt"), "plain text\n");
+    let mut service = service_for(temp.path());
+    // The callback watcher replaces the logging one rather than being ignored.
+    service.watch().expect("logging watch");
+
+    let (sender, receiver) = mpsc::channel();
+    service
+        .watch_with(move |event| {
+            let _ = sender.send(event);
+        })
+        .expect("watch");
+    write_file(&temp.path().join("ignored.txt"), "still plain\n");
+    write_file(&notes, "* Draft\nEdited.\n");
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut seen = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok(event) => {
+                let done = event == ChangeEvent::Modified(notes.clone());
+                seen.push(event);
+                if done {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    assert!(
+        seen.contains(&ChangeEvent::Modified(notes.clone())),
+        "events: {seen:?}"
+    );
+    assert!(seen.iter().all(|event| match event {
+        ChangeEvent::Created(path) | ChangeEvent::Modified(path) | ChangeEvent::Deleted(path) =>
+            path.extension().is_some_and(|ext| ext == "org"),
+    }));
+}
+
+#[test]
+fn search_finds_body_text_under_the_nearest_headline() {
+    let temp = tempdir().expect("tempdir");
+    let alpha = temp.path().join("alpha.org");
+    let beta = temp.path().join("beta.org");
+    write_file(
+        &alpha,
+        "#+TITLE: Alpha\nIntro mentions Ripgrep.\n* Tools\n** TODO Try ripgrep flags :cli:\n   Compare RIPGREP with grep.\n",
+    );
+    write_file(&beta, "* Unrelated\nNothing here.\n");
+    let service = service_for(temp.path());
+
+    let hits = service.search("ripgrep");
+    let summary: Vec<(usize, &str, Option<&str>)> = hits
+        .iter()
+        .map(|hit| (hit.line, hit.snippet.as_str(), hit.headline.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1, "Intro mentions Ripgrep.", None),
+            (
+                3,
+                "** TODO Try ripgrep flags :cli:",
+                Some("Try ripgrep flags")
+            ),
+            (4, "Compare RIPGREP with grep.", Some("Try ripgrep flags")),
+        ]
+    );
+    assert!(hits.iter().all(|hit| hit.path == alpha));
+    assert!(service.search("").is_empty());
+}
+
+#[test]
+fn raising_and_lowering_priority_cycles_the_cookie() {
+    let temp = tempdir().expect("tempdir");
+    let tasks = temp.path().join("tasks.org");
+    write_file(
+        &tasks,
+        "* TODO [#B] Write report :work:\n* [#C] Tidy desk\n* TODO Plain task\n",
+    );
+    let service = service_for(temp.path());
+
+    service.raise_priority(&tasks, 0).expect("raise B");
+    service.raise_priority(&tasks, 0).expect("raise A stays");
+    service.lower_priority(&tasks, 1).expect("lower C");
+    service.lower_priority(&tasks, 2).expect("lower none");
+
+    let contents = fs::read_to_string(&tasks).expect("read tasks");
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines[0], "* TODO [#A] Write report :work:");
+    assert_eq!(lines[1], "* Tidy desk");
+    assert_eq!(lines[2], "* TODO [#B] Plain task");
+    assert!(service.set_priority(&tasks, 2, Some('Z')).is_err());
+}
+
+#[test]
+fn import_text_writes_markdown_as_an_org_document() {
+    let temp = tempdir().expect("tempdir");
+    let service = service_for(temp.path());
+    let dest = temp.path().join("imported/notes.org");
+
+    service
+        .import_text(
+            &dest,
+            "# Reading list\n## Books\n- [ ] Dune\n```python\nprint('hi')\n```\n",
+            ImportFormat::Markdown,
+        )
+        .expect("import");
+
+    let doc = service
+        .get_document(&dest)
+        .expect("imported document loaded");
+    assert_eq!(
+        doc.raw(),
+        "* Reading list\n** Books\n- [ ] Dune\n#+begin_src python\nprint('hi')\n#+end_src\n"
+    );
+    assert!(service
+        .import_text(&dest, "# Again\n", ImportFormat::Markdown)
+        .is_err());
+}
+
+#[test]
+fn overdue_count_includes_only_unfinished_past_items() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("tasks.org"),
+        "* TODO Late\nDEADLINE: <2025-11-01 Sat>\n* TODO Upcoming\nSCHEDULED: <2025-11-20 Thu>\n* DONE Finished\nSCHEDULED: <2025-11-02 Sun>\n",
+    );
+    let service = service_for(temp.path());
+    let today = NaiveDate::from_ymd_opt(2025, 11, 10).expect("date");
+
+    assert_eq!(service.overdue_count(today).expect("count"), 1);
+}
+
+#[test]
+fn completed_between_lists_headlines_closed_in_range() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("review.org"),
+        "* DONE Last week\nCLOSED: [2025-11-02 Sun 09:00]\n* DONE Tuesday\nCLOSED: [2025-11-04 Tue 18:00] SCHEDULED: <2025-11-04 Tue> DEADLINE: <2025-11-05 Wed>\n* DONE Monday\nCLOSED: [2025-11-03 Mon 08:15]\n* TODO Still open\nSCHEDULED: <2025-11-04 Tue>\n",
+    );
+    let service = service_for(temp.path());
+    let date = |day| NaiveDate::from_ymd_opt(2025, 11, day).expect("date");
+
+    let titles: Vec<String> = service
+        .completed_between(date(3), date(9))
+        .expect("completed")
+        .into_iter()
+        .map(|item| item.title)
+        .collect();
+    assert_eq!(titles, vec!["Monday", "Tuesday"]);
+}
+
+#[test]
+fn create_document_writes_under_the_first_root_without_overwriting() {
+    let temp = tempdir().expect("tempdir");
+    write_file(&temp.path().join("inbox.org"), "* Inbox\n");
+    let service = service_for(temp.path());
+
+    let created = service
+        .create_document(Path::new("projects/new.org"), "#+TITLE: New\n* First\n")
+        .expect("create");
+    assert_eq!(created, temp.path().join("projects/new.org"));
+    assert_eq!(
+        fs::read_to_string(&created).expect("read"),
+        "#+TITLE: New\n* First\n"
+    );
+    assert!(service.list_documents().contains(&created));
+
+    assert!(service
+        .create_document(Path::new("projects/new.org"), "* Clobber\n")
+        .is_err());
+    assert!(service
+        .create_document(Path::new("../outside.org"), "* Escape\n")
+        .is_err());
+    assert!(service
+        .create_document(Path::new("notes.txt"), "plain\n")
+        .is_err());
+}
+
+#[test]
+fn registered_extensions_are_ingested_and_writable() {
+    let temp = tempdir().expect("tempdir");
+    let journal = temp.path().join("journal.txt");
+    write_file(
+        &journal,
+        "* TODO Morning pages\nSCHEDULED: <2025-11-03 Mon>\n",
+    );
+    write_file(&temp.path().join("notes.org"), "* Notes\n");
+    write_file(&temp.path().join("readme.md"), "# Not org\n");
+
+    let service = OrgService::builder()
+        .add_root(temp.path())
+        .with_extensions(&[".TXT"])
+        .build()
+        .expect("build");
+    let documents = service.list_documents();
+    assert_eq!(documents.len(), 2);
+    assert!(documents.contains(&journal));
+    assert!(service
+        .agenda()
+        .expect("agenda")
+        .iter()
+        .any(|item| item.title == "Morning pages"));
+
+    service.complete_headline(&journal, 0).expect("complete");
+    assert!(fs::read_to_string(&journal)
+        .expect("read journal")
+        .starts_with("* DONE Morning pages"));
+    assert_eq!(service_for(temp.path()).list_documents().len(), 1);
+}
+
+#[test]
+fn rename_tag_rewrites_headlines_and_filetags_without_duplicates() {
+    let temp = tempdir().expect("tempdir");
+    let work = temp.path().join("work.org");
+    let home = temp.path().join("home.org");
+    write_file(
+        &work,
+        "#+filetags: :proj:misc:\n* TODO Plan release                  :proj:urgent:\n* Notes :misc:\n",
+    );
+    write_file(&home, "#+FILETAGS: proj\n* Garden :proj:project:\n");
+    let service = service_for(temp.path());
+
+    let changed = service.rename_tag("proj", "project").expect("rename");
+    assert_eq!(changed, 4);
+    assert_eq!(
+        fs::read_to_string(&work).expect("read work"),
+        "#+filetags: :project:misc:\n* TODO Plan release               :project:urgent:\n* Notes :misc:\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&home).expect("read home"),
+        "#+FILETAGS: project\n* Garden :project:\n"
+    );
+    assert_eq!(service.rename_tag("proj", "project").expect("rerun"), 0);
+}
+
+#[test]
+fn delete_and_rename_keep_disk_and_cache_in_step() {
+    let temp = tempdir().expect("tempdir");
+    let draft = temp.path().join("draft.org");
+    let scratch = temp.path().join("scratch.org");
+    let existing = temp.path().join("existing.org");
+    write_file(&draft, "* Draft\n");
+    write_file(&scratch, "* Scratch\n");
+    write_file(&existing, "* Existing\n");
+    let service = service_for(temp.path());
+
+    let archived = temp.path().join("archive/draft.org");
+    service.rename_document(&draft, &archived).expect("rename");
+    assert!(!draft.exists());
+    assert_eq!(
+        service.get_document(&archived).expect("renamed").raw(),
+        "* Draft\n"
+    );
+    assert!(!service.list_documents().contains(&draft));
+    assert!(service.rename_document(&archived, &existing).is_err());
+    assert!(service.rename_document(&draft, &scratch).is_err());
+
+    service.delete_document(&scratch).expect("delete");
+    assert!(!scratch.exists());
+    assert!(!service.list_documents().contains(&scratch));
+    assert!(service.delete_document(&scratch).is_err());
+}
+
+#[test]
+fn archive_done_moves_done_subtrees_to_archive_file() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("tasks.org");
+    write_file(
+        &path,
+        "* TODO Keep\n** DONE Nested\nCLOSED: [2024-05-01 Wed 10:00]\n:PROPERTIES:\n:ID: nested\n:END:\nnotes\n*** Child\n** TODO Sibling\n* DONE Top\nbody\n",
+    );
+    let service = service_for(temp.path());
+
+    assert_eq!(service.archive_done(&path).expect("archive"), 2);
+
+    let kept = fs::read_to_string(&path).expect("kept");
+    assert_eq!(kept, "* TODO Keep\n** TODO Sibling\n");
+    assert_eq!(service.get_document(&path).expect("doc").raw(), kept);
+
+    let archived = fs::read_to_string(temp.path().join("tasks.org_archive")).expect("archive");
+    let lines: Vec<&str> = archived.lines().collect();
+    assert_eq!(
+        lines[..5],
+        [
+            "** DONE Nested",
+            "CLOSED: [2024-05-01 Wed 10:00]",
+            ":PROPERTIES:",
+            ":ID: nested",
+            lines[4]
+        ]
+    );
+    assert!(lines[4].starts_with(":ARCHIVE_TIME: "));
+    assert_eq!(lines[5..9], [":END:", "notes", "*** Child", "* DONE Top"]);
+    assert_eq!(lines[9], ":PROPERTIES:");
+    assert!(lines[10].starts_with(":ARCHIVE_TIME: "));
+    assert_eq!(lines[11..], [":END:", "body"]);
+
+    assert_eq!(service.archive_done(&path).expect("second pass"), 0);
+}
+
+#[test]
+fn occurrence_dates_follow_weekly_repeater_across_window() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("weekly.org"),
+        "* TODO Review\nSCHEDULED: <2024-05-06 Mon +1w>\n",
+    );
+    let service = service_for(temp.path());
+    let item = service.agenda().expect("agenda").remove(0);
+
+    let from = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+    let to = NaiveDate::from_ymd_opt(2024, 5, 28).unwrap();
+    let dates: Vec<String> = service
+        .occurrence_dates(&item, from, to)
+        .iter()
+        .map(|date| date.to_string())
+        .collect();
+    assert_eq!(
+        dates,
+        ["2024-05-06", "2024-05-13", "2024-05-20", "2024-05-27"]
+    );
+}
+
+#[test]
+fn refile_headline_moves_subtree_between_files() {
+    let temp = tempdir().expect("tempdir");
+    let inbox = temp.path().join("inbox.org");
+    let project = temp.path().join("project.org");
+    write_file(
+        &inbox,
+        "* TODO Stay\n* TODO Move me\n:PROPERTIES:\n:ID: move\n:END:\n** Child\nnotes\n\n* Later\n",
+    );
+    write_file(&project, "#+TITLE: Project\n* Existing");
+    let service = service_for(temp.path());
+
+    assert!(service.refile_headline(&inbox, 2, &project).is_err());
+    service
+        .refile_headline(&inbox, 1, &project)
+        .expect("refile");
+
+    assert_eq!(
+        fs::read_to_string(&inbox).expect("inbox"),
+        "* TODO Stay\n* Later\n"
+    );
+    assert_eq!(
+        service.get_document(&project).expect("project").raw(),
+        "#+TITLE: Project\n* Existing\n* TODO Move me\n:PROPERTIES:\n:ID: move\n:END:\n** Child\nnotes\n\n"
+    );
+}
+
+#[test]
+fn add_child_headline_appends_after_existing_children() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("plan.org");
+    write_file(
+        &path,
+        "* Project\n:PROPERTIES:\n:ID: p\n:END:\nbody\n** TODO First\n\n* Next\n",
+    );
+    let service = service_for(temp.path());
+
+    service
+        .add_child_headline(&path, 0, "Second", Some("TODO"))
+        .expect("child");
+    service
+        .add_child_headline(&path, 5, "Detail", None)
+        .expect("grandchild");
+    assert!(service.add_child_headline(&path, 1, "Nope", None).is_err());
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("plan"),
+        "* Project\n:PROPERTIES:\n:ID: p\n:END:\nbody\n** TODO First\n*** Detail\n** TODO Second\n\n* Next\n"
+    );
+}
+
+#[test]
+fn append_to_headline_body_lands_before_child_headlines() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("notes.org");
+    write_file(
+        &path,
+        "* TODO Task\nSCHEDULED: <2024-05-06 Mon>\nfirst note\n\n** Child\n* Other\n",
+    );
+    let service = service_for(temp.path());
+
+    service
+        .append_to_headline_body(&path, 0, "second note\nthird line\n")
+        .expect("append");
+    service
+        .append_to_headline_body(&path, 7, "other note")
+        .expect("append other");
+    assert!(service.append_to_headline_body(&path, 1, "x").is_err());
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("notes"),
+        "* TODO Task\nSCHEDULED: <2024-05-06 Mon>\nfirst note\nsecond note\nthird line\n\n** Child\n* Other\nother note\n"
+    );
+}
+
+#[test]
+fn undo_document_steps_back_through_bounded_history() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("undo.org");
+    write_file(&path, "v0\n");
+    let service = service_for(temp.path());
+
+    assert!(service.undo_document(&path).is_err());
+    for version in 1..=25 {
+        service
+            .update_document(&path, format!("v{}\n", version))
+            .expect("update");
+    }
+
+    service.undo_document(&path).expect("undo");
+    assert_eq!(fs::read_to_string(&path).expect("read"), "v24\n");
+    assert_eq!(service.get_document(&path).expect("doc").raw(), "v24\n");
+
+    for _ in 0..19 {
+        service.undo_document(&path).expect("undo");
+    }
+    assert_eq!(fs::read_to_string(&path).expect("read"), "v5\n");
+    assert!(service.undo_document(&path).is_err());
+}
+
+#[test]
+fn habit_headlines_are_listed_as_habits_not_agenda_items() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("habits.org"),
+        "* TODO Stretch\nSCHEDULED: <2024-05-06 Mon .+1d>\n:PROPERTIES:\n:STYLE: habit\n:END:\n* TODO Pay rent\nSCHEDULED: <2024-05-06 Mon>\n",
+    );
+    let service = service_for(temp.path());
+
+    let habits = service.habits().expect("habits");
+    assert_eq!(habits.len(), 1);
+    assert!(habits[0].title.contains("Stretch"));
+
+    let titles: Vec<String> = service
+        .agenda()
+        .expect("agenda")
+        .into_iter()
+        .map(|item| item.title)
+        .collect();
+    assert_eq!(titles, ["Pay rent"]);
+}
+
+#[test]
+fn completing_uses_the_files_first_done_keyword() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("flow.org");
+    write_file(
+        &path,
+        "#+TODO: NEXT WAITING | FINISHED ABANDONED\n* WAITING Reply\nSCHEDULED: <2024-05-06 Mon>\n* API redesign\nSCHEDULED: <2024-05-07 Tue>\n",
+    );
+    let service = service_for(temp.path());
+
+    for item in service.agenda().expect("agenda") {
+        service.complete_agenda_item(&item).expect("complete");
+    }
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("flow"),
+        "#+TODO: NEXT WAITING | FINISHED ABANDONED\n* FINISHED Reply\nSCHEDULED: <2024-05-06 Mon>\n* FINISHED API redesign\nSCHEDULED: <2024-05-07 Tue>\n"
+    );
+}
+
+#[test]
+fn completing_can_target_cancelled_and_skips_done_items() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("cancel.org");
+    write_file(
+        &path,
+        "#+TODO: TODO NEXT | DONE CANCELLED\n* NEXT Drop this\nSCHEDULED: <2024-05-06 Mon>\n* CANCELLED Old plan\nSCHEDULED: <2024-05-07 Tue>\n",
+    );
+    let service = service_for(temp.path());
+
+    let items = service.agenda().expect("agenda");
+    let done: Vec<bool> = items.iter().map(|item| item.is_done()).collect();
+    assert_eq!(done, [false, true]);
+
+    assert!(service
+        .complete_agenda_item_as(&items[0], Some("WAITING"))
+        .is_err());
+    service
+        .complete_agenda_item_as(&items[0], Some("CANCELLED"))
+        .expect("cancel");
+    service
+        .complete_agenda_item(&items[1])
+        .expect("already done");
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("cancel"),
+        "#+TODO: TODO NEXT | DONE CANCELLED\n* CANCELLED Drop this\nSCHEDULED: <2024-05-06 Mon>\n* CANCELLED Old plan\nSCHEDULED: <2024-05-07 Tue>\n"
+    );
+    assert!(service
+        .agenda()
+        .expect("agenda")
+        .iter()
+        .all(|item| item.is_done()));
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    scheduled: Arc<Mutex<Vec<NotificationRequest>>>,
+    cleared: Arc<Mutex<Vec<String>>>,
+}
+
+impl NotificationSink for RecordingSink {
+    fn schedule(&self, notification: NotificationRequest) {
+        self.scheduled.lock().unwrap().push(notification);
+    }
+
+    fn clear_for_habit(&self, path: &Path, habit: &Habit) {
+        self.cleared
+            .lock()
+            .unwrap()
+            .push(habit_notification_key(path, habit));
+    }
+
+    fn clear_for_agenda_item(&self, item: &AgendaItem) {
+        self.cleared
+            .lock()
+            .unwrap()
+            .push(agenda_notification_key(item));
+    }
+}
+
+#[test]
+fn updating_a_document_schedules_reminders_for_deadlines_in_their_warning_window() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("deadlines.org");
+    write_file(&path, "");
+    let sink = RecordingSink::default();
+    let scheduled = sink.scheduled.clone();
+    let cleared = sink.cleared.clone();
+    let service = OrgService::builder()
+        .add_root(temp.path())
+        .with_notification_sink(Box::new(sink))
+        .with_reminder_hour(8)
+        .build()
+        .expect("build org service");
+
+    let today = Local::now().date_naive();
+    let day = |offset: u64| {
+        today
+            .checked_add_days(Days::new(offset))
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+    let contents = format!(
+        "* TODO Soon\nDEADLINE: <{}>\n* TODO Timed\nDEADLINE: <{} 14:30>\n* TODO Far off\nDEADLINE: <{}>\n* TODO Early warning\nDEADLINE: <{} -6w>\n* DONE Finished\nDEADLINE: <{}>\n* TODO Only scheduled\nSCHEDULED: <{}>\n",
+        day(3),
+        day(5),
+        day(30),
+        day(30),
+        day(2),
+        day(1),
+    );
+    service
+        .update_document(&path, contents.clone())
+        .expect("update");
+
+    let reminders: Vec<(String, String)> = scheduled
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|request| {
+            (
+                request.title.clone(),
+                request
+                    .scheduled_for
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        reminders,
+        [
+            ("Soon".to_string(), format!("{} 08:00", day(3))),
+            ("Timed".to_string(), format!("{} 14:30", day(5))),
+            ("Early warning".to_string(), format!("{} 08:00", day(30))),
+        ]
+    );
+    assert_eq!(cleared.lock().unwrap().len(), 6);
+
+    service
+        .update_document(&path, contents)
+        .expect("update again");
+    assert_eq!(scheduled.lock().unwrap().len(), 6);
+    assert_eq!(cleared.lock().unwrap().len(), 12);
+}
+
+struct SharedSink(Arc<TimerNotificationSink>);
+
+impl NotificationSink for SharedSink {
+    fn schedule(&self, notification: NotificationRequest) {
+        self.0.schedule(notification);
+    }
+
+    fn clear_for_habit(&self, path: &Path, habit: &Habit) {
+        self.0.clear_for_habit(path, habit);
+    }
+
+    fn clear_for_agenda_item(&self, item: &AgendaItem) {
+        self.0.clear_for_agenda_item(item);
+    }
+}
+
+#[test]
+fn a_later_headline_with_the_same_title_keeps_an_earlier_deadline_reminder() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("review.org");
+    write_file(&path, "");
+    let sink = Arc::new(TimerNotificationSink::new(|_| {}));
+    let service = OrgService::builder()
+        .add_root(temp.path())
+        .with_notification_sink(Box::new(SharedSink(sink.clone())))
+        .build()
+        .expect("build org service");
+
+    let day = |offset: u64| {
+        Local::now()
+            .date_naive()
+            .checked_add_days(Days::new(offset))
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string()
+    };
+    let contents = format!(
+        "* TODO Review\nDEADLINE: <{}>\n* TODO Review\nSCHEDULED: <{}>\n* TODO Both\nSCHEDULED: <{}> DEADLINE: <{}>\n",
+        day(2),
+        day(3),
+        day(1),
+        day(2),
+    );
+    service.update_document(&path, contents).expect("update");
+
+    assert_eq!(sink.pending_count(), 2);
+}
+
+#[test]
+fn reschedule_moves_the_date_and_optionally_drops_the_repeater() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("weekly.org");
+    write_file(
+        &path,
+        "* TODO Review\nSCHEDULED: <2024-05-06 Mon 10:00 +1w> DEADLINE: <2024-05-10 Fri ++1m -2d>\n",
+    );
+    let service = service_for(temp.path());
+    let items = service.agenda().expect("agenda");
+
+    let new_date = NaiveDate::from_ymd_opt(2024, 5, 8).unwrap();
+    service
+        .reschedule(&items[0], new_date, true)
+        .expect("reschedule scheduled");
+    service
+        .reschedule(
+            &items[1],
+            NaiveDate::from_ymd_opt(2024, 5, 17).unwrap(),
+            false,
+        )
+        .expect("reschedule deadline");
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("weekly"),
+        "* TODO Review\nSCHEDULED: <2024-05-08 Wed 10:00> DEADLINE: <2024-05-17 Fri ++1m -2d>\n"
+    );
+    let scheduled = service.agenda().expect("agenda").remove(0);
+    assert_eq!(scheduled.date, Some(new_date));
+    assert_eq!(scheduled.repeater, None);
+}
+
+#[test]
+fn reschedule_overdue_moves_one_off_items_to_today() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("late.org");
+    write_file(
+        &path,
+        "* TODO Call bank\nSCHEDULED: <2024-05-01 Wed>\n* TODO File taxes\nSCHEDULED: <2024-05-03 Fri 09:00>\n* TODO Water plants\nSCHEDULED: <2024-05-02 Thu +1w>\n",
+    );
+    let service = service_for(temp.path());
+
+    let today = NaiveDate::from_ymd_opt(2024, 5, 10).unwrap();
+    let moved = service
+        .reschedule_overdue_to_today(today)
+        .expect("reschedule overdue");
+
+    assert_eq!(moved, 2);
+    assert_eq!(
+        fs::read_to_string(&path).expect("late"),
+        "* TODO Call bank\nSCHEDULED: <2024-05-10 Fri>\n* TODO File taxes\nSCHEDULED: <2024-05-10 Fri 09:00>\n* TODO Water plants\nSCHEDULED: <2024-05-02 Thu +1w>\n"
+    );
+}
+
+#[test]
+fn agenda_categories_prefer_the_nearest_subtree_property() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("plan.org"),
+        "#+CATEGORY: Work\n* TODO Quarterly report\n* Side project\n:PROPERTIES:\n:CATEGORY: Hobby\n:END:\n** TODO Sketch ideas\n*** TODO Pick colours\n* TODO Expense claim\n",
+    );
+    let service = service_for(temp.path());
+    let items = service.agenda().expect("agenda");
+    let category_of = |title: &str| {
+        items
+            .iter()
+            .find(|item| item.title == title)
+            .and_then(|item| item.category.clone())
+            .unwrap_or_default()
+    };
+
+    assert_eq!(category_of("Sketch ideas"), "Hobby");
+    assert_eq!(category_of("Pick colours"), "Hobby");
+    assert_eq!(category_of("Quarterly report"), "Work");
+    assert_eq!(category_of("Expense claim"), "Work");
+}
+
+#[test]
+fn agenda_filtered_applies_tag_and_keyword_predicates() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("work.org"),
+        "#+FILETAGS: :work:\n* TODO Draft memo :writing:\n* NEXT Review memo :writing:urgent:\n* DONE Send memo :writing:\n* TODO Book room\n",
+    );
+    let service = service_for(temp.path());
+    let titles = |filter: AgendaFilter| -> Vec<String> {
+        service
+            .agenda_filtered(&filter)
+            .expect("filtered agenda")
+            .items
+            .into_iter()
+            .map(|item| item.title)
+            .collect()
+    };
+
+    assert_eq!(
+        titles(AgendaFilter {
+            tags_any: vec!["writing".into(), "missing".into()],
+            exclude_done: true,
+            ..AgendaFilter::default()
+        }),
+        vec!["Draft memo", "Review memo"]
+    );
+    assert_eq!(
+        titles(AgendaFilter {
+            tags_all: vec!["work".into(), "urgent".into()],
+            ..AgendaFilter::default()
+        }),
+        vec!["Review memo"]
+    );
+    assert_eq!(
+        titles(AgendaFilter {
+            keywords: vec!["TODO".into()],
+            ..AgendaFilter::default()
+        }),
+        vec!["Book room", "Draft memo"]
+    );
+    assert_eq!(titles(AgendaFilter::default()).len(), 4);
+}
+
+#[test]
+fn completing_an_item_keeps_crlf_line_endings() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("windows.org");
+    write_file(
+        &path,
+        "#+TITLE: Chores\r\n* TODO Mow lawn\r\nSCHEDULED: <2024-05-06 Mon>\r\n* TODO Wash car\r\n",
+    );
+    let service = service_for(temp.path());
+    let item = service
+        .agenda()
+        .expect("agenda")
+        .into_iter()
+        .find(|item| item.title == "Mow lawn")
+        .expect("item");
+
+    service.complete_agenda_item(&item).expect("complete");
+
+    assert_eq!(
+        fs::read_to_string(&path).expect("windows"),
+        "#+TITLE: Chores\r\n* DONE Mow lawn\r\nSCHEDULED: <2024-05-06 Mon>\r\n* TODO Wash car\r\n"
+    );
+}
+
+#[test]
+fn stats_by_tag_counts_items_and_clocked_time() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("work.org"),
+        "* Client :acme:\n** DONE Kickoff call :meeting:\nSCHEDULED: <2024-05-06 Mon> DEADLINE: <2024-05-07 Tue>\n:LOGBOOK:\nCLOCK: [2024-05-06 Mon 09:00]--[2024-05-06 Mon 10:30] =>  1:30\n:END:\n** TODO Draft estimate\n:LOGBOOK:\nCLOCK: [2024-05-07 Tue 13:00]--[2024-05-07 Tue 13:45]\nCLOCK: [2024-05-08 Wed 08:00]\n:END:\n* TODO Team sync :meeting:\nCLOCK: [2024-05-09 Thu 11:00]--[2024-05-09 Thu 11:20] =>  0:20\n",
+    );
+    let service = service_for(temp.path());
+
+    let stats = service.stats_by_tag().expect("stats");
+
+    assert_eq!(stats.keys().collect::<Vec<_>>(), vec!["acme", "meeting"]);
+    let acme = &stats["acme"];
+    assert_eq!((acme.item_count, acme.done_count), (3, 1));
+    assert_eq!(acme.clocked, Duration::from_secs((90 + 45) * 60));
+    let meeting = &stats["meeting"];
+    assert_eq!((meeting.item_count, meeting.done_count), (2, 1));
+    assert_eq!(meeting.clocked, Duration::from_secs((90 + 20) * 60));
+}
+
+#[test]
+fn edits_keep_the_files_trailing_newline() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("posix.org");
+    write_file(&path, "* TODO Pack bags\nSCHEDULED: <2024-05-06 Mon>\n");
+    let service = service_for(temp.path());
+    let item = service.agenda().expect("agenda").remove(0);
+
+    service.complete_agenda_item(&item).expect("complete");
+    assert_eq!(
+        fs::read_to_string(&path).expect("posix"),
+        "* DONE Pack bags\nSCHEDULED: <2024-05-06 Mon>\n"
+    );
+
+    service
+        .update_document(&path, "* DONE Pack bags".to_string())
+        .expect("update");
+    assert_eq!(
+        fs::read_to_string(&path).expect("posix"),
+        "* DONE Pack bags\n"
+    );
+}
+
+#[test]
+fn resolve_link_finds_headline_search_targets() {
+    let temp = tempdir().expect("tempdir");
+    let notes = temp.path().join("notes.org");
+    let index = temp.path().join("journal").join("index.org");
+    write_file(
+        &notes,
+        "#+TITLE: Notes\n* Inbox\n** TODO [#A] Some Heading :work:\n* Archive\n:PROPERTIES:\n:CUSTOM_ID: old_stuff\n:END:\n",
+    );
+    write_file(
+        &index,
+        "See [[file:../notes.org::*Some Heading][the heading]].\n* Local\n",
+    );
+    let service = service_for(temp.path());
+
+    let heading = service
+        .resolve_link(&index, "[[file:../notes.org::*Some Heading][the heading]]")
+        .expect("heading link");
+    assert_eq!(heading.path, notes);
+    assert_eq!(heading.headline_line, Some(2));
+
+    let custom = service
+        .resolve_link(&index, "file:../notes.org::#old_stuff")
+        .expect("custom id link");
+    assert_eq!(custom.headline_line, Some(3));
+
+    let whole_file = service
+        .resolve_link(&index, "file:../notes.org")
+        .expect("file link");
+    assert_eq!(whole_file.headline_line, None);
+
+    let local = service
+        .resolve_link(&index, "[[*Local]]")
+        .expect("local link");
+    assert_eq!((local.path, local.headline_line), (index.clone(), Some(1)));
+
+    assert!(service
+        .resolve_link(&index, "file:../notes.org::*Missing")
+        .is_err());
+}
+
+#[test]
+fn recurring_tasks_lists_each_repeating_headline_once() {
+    let temp = tempdir().expect("tempdir");
+    write_file(
+        &temp.path().join("routine.org"),
+        "* TODO Water plants\nSCHEDULED: <2024-05-06 Mon +3d> DEADLINE: <2024-05-08 Wed +3d>\n* TODO Pay rent\nDEADLINE: <2024-05-01 Wed ++1m>\n* TODO Renew passport\nSCHEDULED: <2024-06-01 Sat>\n",
+    );
+    let service = service_for(temp.path());
+
+    let mut titles: Vec<String> = service
+        .recurring_tasks()
+        .into_iter()
+        .map(|item| item.title)
+        .collect();
+    titles.sort();
+
+    assert_eq!(titles, vec!["Pay rent", "Water plants"]);
+}
+
+#[test]
+fn completing_a_repeating_item_advances_it_until_repeat_until() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("chores.org");
+    write_file(
+        &path,
+        "* TODO Take out bins\nSCHEDULED: <2025-12-16 Tue +1w>\n:PROPERTIES:\n:REPEAT_UNTIL: 2025-12-31\n:END:\n",
+    );
+    let service = service_for(temp.path());
+
+    let item = service.agenda().expect("agenda").remove(0);
+    service
+        .complete_agenda_item(&item)
+        .expect("first completion");
+    let raw = fs::read_to_string(&path).expect("chores");
+    assert!(raw.starts_with("* TODO Take out bins\nSCHEDULED: <2025-12-23 Tue +1w>\n"));
+    assert!(raw.contains(":LAST_REPEAT: ["));
+
+    // The next occurrence, 2025-12-30, is still within the limit.
+    let item = service.agenda().expect("agenda").remove(0);
+    service
+        .complete_agenda_item(&item)
+        .expect("second completion");
+    let item = service.agenda().expect("agenda").remove(0);
+    assert_eq!(item.date, NaiveDate::from_ymd_opt(2025, 12, 30));
+    assert_eq!(item.todo_keyword.as_deref(), Some("TODO"));
+
+    // Advancing again would pass 2025-12-31, so the item is closed instead.
+    service
+        .complete_agenda_item(&item)
+        .expect("final completion");
+    let raw = fs::read_to_string(&path).expect("chores");
+    assert!(raw.starts_with("* DONE Take out bins\nSCHEDULED: <2025-12-30 Tue +1w>\n"));
+}
+
+#[test]
+fn headlines_returns_structured_subtrees() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("projects.org");
+    write_file(
+        &path,
+        "#+TODO: PLAN | SHIPPED\nIntro\n* PLAN [#A] Launch :work:urgent:\nNotes\n** SHIPPED Beta\n* Backlog\n",
+    );
+    let service = service_for(temp.path());
+
+    let headlines = service.headlines(&path).expect("headlines");
+    assert_eq!(headlines.len(), 3);
+
+    let launch = &headlines[0];
+    assert_eq!(launch.line, 2);
+    assert_eq!(launch.depth, 1);
+    assert_eq!(launch.todo_keyword.as_deref(), Some("PLAN"));
+    assert_eq!(launch.title, "Launch");
+    assert_eq!(launch.tags, vec!["work".to_string(), "urgent".to_string()]);
+    assert_eq!(launch.body_range, 3..5);
+
+    let beta = &headlines[1];
+    assert_eq!((beta.line, beta.depth), (4, 2));
+    assert_eq!(beta.todo_keyword.as_deref(), Some("SHIPPED"));
+    assert_eq!(beta.body_range, 5..5);
+
+    assert_eq!(headlines[2].line, 5);
+    assert_eq!(headlines[2].todo_keyword, None);
+    assert_eq!(headlines[2].body_range, 6..6);
+}
+
+#[test]
+fn find_or_create_daily_reuses_the_file_for_a_date() {
+    let temp = tempdir().expect("tempdir");
+    let root = fs::canonicalize(temp.path()).expect("canonical root");
+    let dailies = root.join("daily");
+    write_file(&dailies.join("2025-11-09-notes.org"), "#+TITLE: Sunday\n");
+    let service = service_for(&root);
+    let date = NaiveDate::from_ymd_opt(2025, 11, 10).unwrap();
+
+    let created = service
+        .find_or_create_daily(&root, date)
+        .expect("create daily");
+    assert_eq!(created, dailies.join("2025-11-10.org"));
+    assert_eq!(
+        fs::read_to_string(&created).expect("daily"),
+        "#+TITLE: 2025-11-10\n"
+    );
+    assert!(service.get_document(&created).is_ok());
+
+    fs::write(&created, "#+TITLE: 2025-11-10\n* Standup\n").expect("edit daily");
+    let again = service
+        .find_or_create_daily(&root, date)
+        .expect("find daily");
+    assert_eq!(again, created);
+    assert!(fs::read_to_string(&again)
+        .expect("daily")
+        .contains("* Standup"));
+
+    let previous = service
+        .find_or_create_daily(&root, NaiveDate::from_ymd_opt(2025, 11, 9).unwrap())
+        .expect("find earlier daily");
+    assert_eq!(previous, dailies.join("2025-11-09-notes.org"));
+    assert_eq!(fs::read_dir(&dailies).expect("dailies").count(), 2);
+}
+
+#[test]
+fn find_or_create_daily_stays_inside_the_roots() {
+    let temp = tempdir().expect("tempdir");
+    let root = temp.path().join("notes");
+    let outside = temp.path().join("elsewhere");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    let date = NaiveDate::from_ymd_opt(2025, 1, 2).unwrap();
+
+    let service = service_for(&root);
+    let err = service.find_or_create_daily(&outside, date).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<OrgError>(),
+        Some(OrgError::InvalidInput(_))
+    ));
+    assert!(!outside.join("daily").exists());
+
+    let escaping = OrgService::builder()
+        .add_root(&root)
+        .with_dailies_dir("../elsewhere")
+        .build()
+        .expect("build org service");
+    assert!(escaping.find_or_create_daily(&root, date).is_err());
+    assert_eq!(fs::read_dir(&outside).unwrap().count(), 0);
+
+    let journal = OrgService::builder()
+        .add_root(&root)
+        .with_dailies_dir("journal/days")
+        .build()
+        .expect("build org service");
+    let created = journal
+        .find_or_create_daily(&root, date)
+        .expect("create daily");
+    assert!(created.ends_with("notes/journal/days/2025-01-02.org"));
+}
+
+#[test]
+fn merge_documents_moves_content_and_links() {
+    let temp = tempdir().expect("tempdir");
+    let src = temp.path().join("notes/rust.org");
+    let dest = temp.path().join("notes/programming.org");
+    let other = temp.path().join("journal/today.org");
+    write_file(
+        &src,
+        "#+TITLE: Rust\n#+FILETAGS: :lang:\n:PROPERTIES:\n:ID: rust-node\n:END:\nOwnership notes.\n* Lifetimes\n:PROPERTIES:\n:ID: shared-id\n:END:\nSee [[file:../journal/today.org][today]].\n",
+    );
+    write_file(
+        &dest,
+        "#+TITLE: Programming\n* Overview\n:PROPERTIES:\n:ID: shared-id\n:END:\nRead [[file:rust.org][the rust note]].\n",
+    );
+    write_file(
+        &other,
+        "* Log\nWorked on [[file:../notes/rust.org::*Lifetimes][lifetimes]], see [[rust]] and [[id:rust-node]].\n",
+    );
+    let service = service_for(temp.path());
+
+    service.merge_documents(&src, &dest).expect("merge");
+
+    assert!(!src.exists());
+    assert!(service.get_document(&src).is_err());
+    let merged = fs::read_to_string(&dest).expect("dest");
+    assert_eq!(
+        merged,
+        "#+TITLE: Programming\n* Overview\n:PROPERTIES:\n:ID: shared-id\n:END:\nRead [[file:programming.org][the rust note]].\n* Rust :lang:\n:PROPERTIES:\n:ID: rust-node\n:END:\nOwnership notes.\n** Lifetimes\n:PROPERTIES:\n:ID: shared-id-2\n:END:\nSee [[file:../journal/today.org][today]].\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&other).expect("other"),
+        "* Log\nWorked on [[file:../notes/programming.org::*Lifetimes][lifetimes]], see [[programming]] and [[id:rust-node]].\n"
+    );
+    let target = service
+        .resolve_link(&other, "[[file:../notes/programming.org::*Lifetimes]]")
+        .expect("link resolves");
+    assert_eq!(target.headline_line, Some(11));
+}
+
+#[test]
+fn merge_documents_rejects_an_aliased_spelling_of_the_same_file() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("notes/a.org");
+    write_file(&path, "* Keep me\n");
+    fs::create_dir_all(temp.path().join("notes/sub")).expect("sub dir");
+    let service = service_for(temp.path());
+
+    let alias = temp.path().join("notes/sub/../a.org");
+    let err = service.merge_documents(&path, &alias).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<OrgError>(),
+        Some(OrgError::InvalidInput(_))
+    ));
+    assert_eq!(fs::read_to_string(&path).expect("read"), "* Keep me\n");
+}
+
+#[test]
+fn agenda_lists_each_headline_once_across_overlapping_roots() {
+    let temp = tempdir().unwrap();
+    fs::create_dir_all(temp.path().join("roam")).unwrap();
+    write_file(
+        &temp.path().join("tasks.org"),
+        "* TODO Pay rent\nSCHEDULED: <2024-05-01 Wed>\n* Standup\n<2024-05-02 Thu 09:00>\n<2024-05-03 Fri 09:00>\n",
+    );
+    let service = OrgService::builder()
+        .add_document_root(temp.path())
+        .add_document_root(temp.path().join("roam").join(".."))
+        .build()
+        .unwrap();
+
+    let titles: Vec<String> = service
+        .agenda()
+        .unwrap()
+        .into_iter()
+        .map(|item| item.title)
+        .collect();
+    assert_eq!(titles, vec!["Pay rent", "Standup", "Standup"]);
+}
+
+#[test]
+fn recompute_statistics_counts_checkboxes_or_child_todos() {
+    let temp = tempdir().unwrap();
+    let path = temp.path().join("plan.org");
+    write_file(
+        &path,
+        "* Trip [0/0]\n- [X] Book flights\n- [ ] Pack [1/1]\n  - [ ] Socks\n* Release [%]\n** DONE Tag\n** TODO Publish [/]\n- [X] Crate\n- [-] Docs\n*** TODO Deep\n** Notes\n",
+    );
+    let service = service_for(temp.path());
+
+    service.recompute_statistics(&path, 0, false).unwrap();
+    service.recompute_statistics(&path, 4, true).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "* Trip [1/2]\n- [X] Book flights\n- [ ] Pack [1/1]\n  - [ ] Socks\n* Release [50%]\n** DONE Tag\n** TODO Publish [1/2]\n- [X] Crate\n- [-] Docs\n*** TODO Deep\n** Notes\n"
+    );
+    assert!(service.recompute_statistics(&path, 1, false).is_err());
+}
+
+#[test]
+fn equivalent_root_spellings_load_each_document_once() {
+    let temp = tempdir().unwrap();
+    let notes = temp.path().join("notes");
+    fs::create_dir_all(notes.join("daily")).unwrap();
+    write_file(&notes.join("inbox.org"), "* TODO Call back\n");
+    let service = OrgService::builder()
+        .add_root(&notes)
+        .add_root(notes.join("daily").join(".."))
+        .add_root(format!("{}/", notes.display()))
+        .build()
+        .unwrap();
+
+    assert_eq!(service.roots().len(), 1);
+    assert_eq!(service.list_documents().len(), 1);
+    assert_eq!(service.agenda().unwrap().len(), 1);
+    let aliased = notes.join("daily").join("..").join("inbox.org");
+    assert!(service.get_document(&aliased).is_ok());
+    service
+        .update_document(&aliased, "* DONE Call back\n".to_string())
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(notes.join("inbox.org")).unwrap(),
+        "* DONE Call back\n"
+    );
+}
+
+#[test]
+fn list_documents_sorted_orders_by_modified_time_and_title() {
+    let temp = tempdir().unwrap();
+    let base = std::time::SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    for (name, contents, age) in [
+        ("a.org", "#+TITLE: Zebra\n", 30),
+        ("b.org", "* Untitled\n", 10),
+        ("c.org", "#+TITLE: apple\n", 20),
+        ("d.org", "#+TITLE: Mango\n", 10),
+    ] {
+        let path = temp.path().join(name);
+        write_file(&path, contents);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(base - Duration::from_secs(age))
+            .unwrap();
+    }
+    let service = service_for(temp.path());
+    let names = |order| -> Vec<String> {
+        service
+            .list_documents_sorted(order)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    };
+
+    assert_eq!(
+        names(DocumentSort::Path),
+        ["a.org", "b.org", "c.org", "d.org"]
+    );
+    assert_eq!(
+        names(DocumentSort::ModifiedDesc),
+        ["b.org", "d.org", "c.org", "a.org"]
+    );
+    assert_eq!(
+        names(DocumentSort::ModifiedAsc),
+        ["a.org", "c.org", "b.org", "d.org"]
+    );
+    assert_eq!(
+        names(DocumentSort::TitleAsc),
+        ["c.org", "b.org", "d.org", "a.org"]
+    );
+
+    service
+        .update_document(
+            temp.path().join("a.org"),
+            "#+TITLE: Zebra\nEdited\n".to_string(),
+        )
+        .unwrap();
+    assert_eq!(names(DocumentSort::ModifiedDesc)[0], "a.org");
+}
+
+#[test]
+fn set_effort_writes_hours_and_minutes_into_the_drawer() {
+    let temp = tempdir().unwrap();
+    let path = temp.path().join("tasks.org");
+    write_file(
+        &path,
+        "* TODO Write report\nSCHEDULED: <2024-05-01 Wed>\n* TODO Review\n:PROPERTIES:\n:EFFORT: 0:15\n:END:\n",
+    );
+    let service = service_for(temp.path());
+
+    service.set_effort(&path, 0, 90).unwrap();
+    service.set_effort(&path, 5, 5).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "* TODO Write report\nSCHEDULED: <2024-05-01 Wed>\n:PROPERTIES:\n:EFFORT: 1:30\n:END:\n* TODO Review\n:PROPERTIES:\n:EFFORT: 0:05\n:END:\n"
+    );
+    assert!(service.set_effort(&path, 1, 30).is_err());
+}
+
+#[test]
+fn append_to_document_rejects_targets_outside_the_roots() {
+    let temp = tempdir().unwrap();
+    let notes = temp.path().join("notes");
+    write_file(&notes.join("inbox.org"), "* Inbox\n");
+    let service = service_for(&notes);
+
+    let escaping = notes.join("../../etc/x.org");
+    let err = service
+        .append_to_document(&escaping, "* TODO Sneaky\n")
+        .expect_err("escaping target");
+    assert!(err.to_string().contains("outside the configured roots"));
+    assert!(service
+        .append_to_document(temp.path().join("sibling.org"), "* TODO Sneaky\n")
+        .is_err());
+    assert!(!temp.path().join("sibling.org").exists());
+
+    let nested = notes.join("captures/new/today.org");
+    service
+        .append_to_document(&nested, "* TODO Allowed")
+        .expect("append inside root");
+    assert_eq!(fs::read_to_string(&nested).unwrap(), "* TODO Allowed\n");
+    assert!(service.get_document(&nested).is_ok());
+}
+
+#[test]
+fn habits_are_only_re_extracted_from_changed_documents() {
+    let temp = tempdir().unwrap();
+    let habit = |title: &str| {
+        format!(
+            "* TODO {title}\nSCHEDULED: <2024-05-01 Wed .+1d>\n:PROPERTIES:\n:STYLE: habit\n:END:\n"
+        )
+    };
+    let first = temp.path().join("first.org");
+    let second = temp.path().join("second.org");
+    let third = temp.path().join("third.org");
+    write_file(&first, &habit("Stretch"));
+    write_file(&second, &habit("Read"));
+    write_file(&third, "* TODO Not a habit\n");
+    let service = service_for(temp.path());
+
+    assert_eq!(service.habits().unwrap().len(), 2);
+    assert_eq!(service.habit_extractions(), 3);
+    assert_eq!(service.habits().unwrap().len(), 2);
+    assert_eq!(service.habit_extractions(), 3);
+
+    service
+        .update_document(&first, habit("Stretch longer"))
+        .unwrap();
+    let habits = service.habits().unwrap();
+    assert_eq!(service.habit_extractions(), 4);
+    assert!(habits
+        .iter()
+        .any(|habit| habit.title.ends_with("Stretch longer")));
+    assert!(habits.iter().any(|habit| habit.title.ends_with("Read")));
+}
+
+#[test]
+fn append_under_headline_files_after_children_and_creates_missing_targets() {
+    let temp = tempdir().unwrap();
+    let inbox = temp.path().join("inbox.org");
+    write_file(
+        &inbox,
+        "* Inbox\n** TODO Existing\nnotes\n\n* Projects\n** TODO Ship\n",
+    );
+    let service = service_for(temp.path());
+
+    service
+        .append_under_headline(&inbox, "Inbox", "** TODO Captured\n")
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&inbox).unwrap(),
+        "* Inbox\n** TODO Existing\nnotes\n** TODO Captured\n\n* Projects\n** TODO Ship\n"
+    );
+
+    service
+        .append_under_headline(&inbox, "Someday", "** Learn the cello")
+        .unwrap();
+    assert!(fs::read_to_string(&inbox)
+        .unwrap()
+        .ends_with("** TODO Ship\n* Someday\n** Learn the cello\n"));
+
+    let stray = temp.path().join("stray.org");
+    write_file(&stray, "*\tStray\n* Inbox\n** TODO Old\n");
+    service.reload_document(&stray).unwrap();
+    service
+        .append_under_headline(&stray, "Inbox", "** TODO New")
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&stray).unwrap(),
+        "*\tStray\n* Inbox\n** TODO Old\n** TODO New\n"
+    );
+
+    let fresh = temp.path().join("fresh.org");
+    service
+        .append_under_headline(&fresh, "Inbox", "** TODO First")
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&fresh).unwrap(),
+        "* Inbox\n** TODO First\n"
+    );
+}
+
+#[test]
+fn set_priority_handles_multibyte_cookies_and_configured_keywords() {
+    let temp = tempdir().expect("tempdir");
+    let tasks = temp.path().join("tasks.org");
+    write_file(
+        &tasks,
+        "#+TODO: PLAN | SHIPPED\n* PLAN [#é] Title\n* API redesign\n",
+    );
+    let service = service_for(temp.path());
+
+    service.set_priority(&tasks, 1, Some('A')).expect("set A");
+    service.set_priority(&tasks, 2, Some('B')).expect("set B");
+
+    let contents = fs::read_to_string(&tasks).expect("read tasks");
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines[1], "* PLAN [#A] Title");
+    assert_eq!(lines[2], "* [#B] API redesign");
+}
+
+#[test]
+fn headlines_keep_their_lines_past_tab_starred_lines_and_blocks() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("odd.org");
+    write_file(
+        &path,
+        "* One\n*\tTabbed\n#+begin_example\n* inside\n#+end_example\n* Two\n",
+    );
+    let service = service_for(temp.path());
+
+    let lines: Vec<(String, usize)> = service
+        .headlines(&path)
+        .expect("headlines")
+        .into_iter()
+        .map(|headline| (headline.title, headline.line))
+        .collect();
+    // Like orgize and Emacs, a `*\t` line is body text and a starred line inside a block
+    // still starts a headline.
+    assert_eq!(
+        lines,
+        vec![
+            ("One".to_string(), 0),
+            ("inside".to_string(), 3),
+            ("Two".to_string(), 5),
+        ]
+    );
+}
+
+#[test]
+fn saving_a_habit_clears_its_reminder_before_rescheduling() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("habits.org");
+    write_file(&path, "");
+    let sink = RecordingSink::default();
+    let scheduled = sink.scheduled.clone();
+    let cleared = sink.cleared.clone();
+    let service = OrgService::builder()
+        .add_root(temp.path())
+        .with_notification_sink(Box::new(sink))
+        .build()
+        .expect("build org service");
+    let contents =
+        "* TODO Stretch\nSCHEDULED: <2030-01-01 Tue .+1d>\n:PROPERTIES:\n:STYLE: habit\n:END:\n";
+
+    for saves in 1..=2 {
+        service
+            .update_document(&path, contents.to_string())
+            .expect("update");
+        let habit_keys = |keys: Vec<String>| {
+            keys.into_iter()
+                .filter(|key| key.starts_with("habit:"))
+                .count()
+        };
+        let scheduled_keys = scheduled
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| request.key.clone())
+            .collect();
+        assert_eq!(habit_keys(scheduled_keys), saves);
+        assert_eq!(habit_keys(cleared.lock().unwrap().clone()), saves);
+    }
+}
+
+#[test]
+fn failures_carry_a_typed_org_error() {
+    let temp = tempdir().expect("tempdir");
+    write_file(&temp.path().join("notes.org"), "* Draft\n");
+    let service = service_for(temp.path());
+
+    let missing = service
+        .get_document(temp.path().join("missing.org"))
+        .unwrap_err();
+    assert!(matches!(
+        missing.downcast_ref::<OrgError>(),
+        Some(OrgError::NotFound(_))
+    ));
+    let taken = service
+        .create_document(Path::new("notes.org"), "")
+        .unwrap_err();
+    assert!(matches!(
+        taken.downcast_ref::<OrgError>(),
+        Some(OrgError::AlreadyExists(_))
+    ));
+}
+
+#[test]
+fn archive_done_uses_file_done_keywords_and_rolls_back_on_failure() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("ship.org");
+    let archive = temp.path().join("ship.org_archive");
+    let raw = "#+TODO: PLAN | SHIPPED\n* PLAN Next\n* SHIPPED Launch\n";
+    write_file(&path, raw);
+    let service = service_for(temp.path());
+
+    // Swap the source for a directory so rewriting it fails after the archive append.
+    fs::remove_file(&path).unwrap();
+    fs::create_dir(&path).unwrap();
+    assert!(service.archive_done(&path).is_err());
+    assert_eq!(fs::read_to_string(&archive).unwrap(), "");
+
+    fs::remove_dir(&path).unwrap();
+    write_file(&path, raw);
+    assert_eq!(service.archive_done(&path).expect("archive"), 1);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "#+TODO: PLAN | SHIPPED\n* PLAN Next\n"
+    );
+    assert!(fs::read_to_string(&archive)
+        .unwrap()
+        .starts_with("* SHIPPED Launch\n"));
+}
+
+#[test]
+fn failed_undo_keeps_the_step_for_a_retry() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("undo.org");
+    write_file(&path, "v0\n");
+    let service = service_for(temp.path());
+    service
+        .update_document(&path, "v1\n".to_string())
+        .expect("update");
+
+    fs::remove_file(&path).unwrap();
+    fs::create_dir(&path).unwrap();
+    assert!(service.undo_document(&path).is_err());
+
+    fs::remove_dir(&path).unwrap();
+    write_file(&path, "v1\n");
+    service.undo_document(&path).expect("retried undo");
+    assert_eq!(fs::read_to_string(&path).expect("read"), "v0\n");
+}
+
+#[test]
+fn lexical_code_blocks_carry_the_configured_fence_language() {
+    let temp = tempdir().expect("tempdir");
+    let path = temp.path().join("code.org");
+    write_file(
+        &path,
+        "#+begin_src haskell\nmain = pure ()\n#+end_src\n#+begin_src example\n#+end_src\n",
+    );
+    let service = OrgService::builder()
+        .add_root(temp.path())
+        .with_language_aliases(LanguageAliases::default().with_alias("haskell", "hs"))
+        .build()
+        .expect("build org service");
+
+    let fences: Vec<_> = service
+        .lexical_nodes(&path)
+        .expect("lexical nodes")
+        .into_iter()
+        .filter_map(|node| match node {
+            LexicalNode::CodeBlock { fence_language, .. } => Some(fence_language),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        fences,
+        [Some("hs".to_string()), Some("example".to_string())]
+    );
+}
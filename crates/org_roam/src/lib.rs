@@ -1,5 +1,5 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use org_domain::{document::OrgDocument, service::OrgService};
@@ -11,6 +11,8 @@ use tracing::instrument;
 pub struct RoamNode {
     pub id: String,
     pub title: String,
+    #[serde(default)]
+    pub subtitle: Option<String>,
     pub path: PathBuf,
     pub tags: Vec<String>,
 }
@@ -19,6 +21,10 @@ pub struct RoamNode {
 pub struct RoamLink {
     pub source: String,
     pub target: String,
+    /// The `::` search option of the link, e.g. `*Heading` or `#custom-id`, naming a headline
+    /// inside the target; `OrgService::resolve_link` turns it into a line.
+    #[serde(default)]
+    pub anchor: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +32,7 @@ struct RoamDocumentMetadata {
     id: String,
     aliases: Vec<String>,
     title: String,
+    subtitle: Option<String>,
     tags: Vec<String>,
 }
 
@@ -57,19 +64,122 @@ impl OrgRoamGraph {
             .filter_map(|neighbor| self.graph.node_weight(neighbor))
             .collect()
     }
+
+    pub fn forward_links_for(&self, node_id: &str) -> Vec<&RoamNode> {
+        let Some(&idx) = self.index_by_id.get(node_id) else {
+            return Vec::new();
+        };
+        self.graph
+            .neighbors_directed(idx, petgraph::Outgoing)
+            .filter_map(|neighbor| self.graph.node_weight(neighbor))
+            .collect()
+    }
+
+    /// Returns `(incoming, outgoing)` edge counts, or `(0, 0)` for an unknown node.
+    pub fn link_counts(&self, node_id: &str) -> (usize, usize) {
+        let Some(&idx) = self.index_by_id.get(node_id) else {
+            return (0, 0);
+        };
+        (
+            self.graph.edges_directed(idx, petgraph::Incoming).count(),
+            self.graph.edges_directed(idx, petgraph::Outgoing).count(),
+        )
+    }
+
+    /// Serialises the graph as a GraphViz digraph keyed by node id and labelled by title,
+    /// with any tags in a `tags` attribute.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph roam {\n");
+        for node in self.graph.node_weights() {
+            let tags = if node.tags.is_empty() {
+                String::new()
+            } else {
+                format!(", tags=\"{}\"", escape_dot(&node.tags.join(" ")))
+            };
+            dot.push_str(&format!(
+                "  \"{}\" [label=\"{}\"{}];\n",
+                escape_dot(&node.id),
+                escape_dot(&node.title),
+                tags
+            ));
+        }
+        for link in self.graph.edge_weights() {
+            dot.push_str(&format!(
+                "  \"{}\" -> \"{}\";\n",
+                escape_dot(&link.source),
+                escape_dot(&link.target)
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialises the graph as a directed GraphML document. Nodes carry `title` and
+    /// space-separated `tags` data, and edges their `anchor` when the link has one.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+             <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n  \
+             <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n  \
+             <key id=\"anchor\" for=\"edge\" attr.name=\"anchor\" attr.type=\"string\"/>\n  \
+             <graph id=\"roam\" edgedefault=\"directed\">\n",
+        );
+        for node in self.graph.node_weights() {
+            xml.push_str(&format!(
+                "    <node id=\"{}\"><data key=\"title\">{}</data>",
+                escape_xml(&node.id),
+                escape_xml(&node.title)
+            ));
+            if !node.tags.is_empty() {
+                xml.push_str(&format!(
+                    "<data key=\"tags\">{}</data>",
+                    escape_xml(&node.tags.join(" "))
+                ));
+            }
+            xml.push_str("</node>\n");
+        }
+        for link in self.graph.edge_weights() {
+            let source = escape_xml(&link.source);
+            let target = escape_xml(&link.target);
+            match &link.anchor {
+                Some(anchor) => xml.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\"><data key=\"anchor\">{}</data></edge>\n",
+                    source,
+                    target,
+                    escape_xml(anchor)
+                )),
+                None => xml.push_str(&format!(
+                    "    <edge source=\"{}\" target=\"{}\"/>\n",
+                    source, target
+                )),
+            }
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Nodes with no links in either direction.
+    pub fn orphan_nodes(&self) -> Vec<&RoamNode> {
+        self.graph
+            .node_indices()
+            .filter(|&idx| self.graph.neighbors_undirected(idx).next().is_none())
+            .filter_map(|idx| self.graph.node_weight(idx))
+            .collect()
+    }
 }
 
 #[instrument(skip(service))]
 pub fn build_roam_graph(service: &OrgService) -> Result<OrgRoamGraph> {
     let mut graph = OrgRoamGraph::default();
-    let mut link_buffer: Vec<(String, String)> = Vec::new();
+    let mut link_buffer: Vec<(String, String, Option<String>)> = Vec::new();
     let mut alias_to_node_id: HashMap<String, String> = HashMap::new();
 
     for path in service.list_documents() {
         let Ok(doc) = service.get_document(&path) else {
             continue;
         };
-        if !is_roam_file(&path) {
+        if !service.is_document_path(&path) {
             continue;
         }
 
@@ -77,6 +187,7 @@ pub fn build_roam_graph(service: &OrgService) -> Result<OrgRoamGraph> {
         let node_index = graph.graph.add_node(RoamNode {
             id: metadata.id.clone(),
             title: metadata.title,
+            subtitle: metadata.subtitle,
             path: path.clone(),
             tags: metadata.tags,
         });
@@ -89,7 +200,7 @@ pub fn build_roam_graph(service: &OrgService) -> Result<OrgRoamGraph> {
     }
 
     let mut seen_edges: HashSet<(String, String)> = HashSet::new();
-    for (source, target_alias) in link_buffer {
+    for (source, target_alias, anchor) in link_buffer {
         let target = alias_to_node_id
             .get(&target_alias)
             .cloned()
@@ -103,39 +214,79 @@ pub fn build_roam_graph(service: &OrgService) -> Result<OrgRoamGraph> {
         ) else {
             continue;
         };
-        graph
-            .graph
-            .add_edge(source_idx, target_idx, RoamLink { source, target });
+        graph.graph.add_edge(
+            source_idx,
+            target_idx,
+            RoamLink {
+                source,
+                target,
+                anchor,
+            },
+        );
     }
 
     Ok(graph)
 }
 
-fn document_metadata(path: &PathBuf, doc: &OrgDocument) -> RoamDocumentMetadata {
+fn escape_dot(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn document_metadata(path: &Path, doc: &OrgDocument) -> RoamDocumentMetadata {
     let fallback_id = compute_node_id(path);
     let org_id = extract_org_id(doc.raw());
-    let id = org_id.clone().unwrap_or_else(|| fallback_id.clone());
+    let id = org_id.unwrap_or_else(|| fallback_id.clone());
     let mut aliases = vec![fallback_id.clone(), id.clone()];
-    if let Some(org_id) = org_id {
-        aliases.push(org_id);
-    }
+    aliases.extend(extract_headline_ids(doc.raw()));
     aliases.sort();
     aliases.dedup();
 
     RoamDocumentMetadata {
         id,
         aliases,
-        title: extract_title(doc.raw()).unwrap_or(fallback_id),
+        title: doc.title().unwrap_or(fallback_id),
+        subtitle: doc.subtitle(),
         tags: extract_tags(doc.raw()),
     }
 }
 
-fn extract_links(node_id: String, doc: &OrgDocument) -> Vec<(String, String)> {
+fn extract_links(node_id: String, doc: &OrgDocument) -> Vec<(String, String, Option<String>)> {
     doc.raw()
         .lines()
         .flat_map(parse_roam_links)
-        .filter_map(normalize_link_target)
-        .map(|target| (node_id.clone(), target))
+        .filter_map(|link| {
+            let anchor = link
+                .split_once("::")
+                .map(|(_, anchor)| anchor.trim().to_string())
+                .filter(|anchor| !anchor.is_empty());
+            let target = normalize_link_target(link)?;
+            Some((node_id.clone(), target, anchor))
+        })
         .collect()
 }
 
@@ -166,11 +317,7 @@ fn parse_roam_link(line: &str) -> Option<String> {
 
 fn normalize_link_target(target: String) -> Option<String> {
     let trimmed = target.trim();
-    if trimmed.is_empty()
-        || trimmed.starts_with("http:")
-        || trimmed.starts_with("https:")
-        || trimmed.starts_with("mailto:")
-    {
+    if trimmed.is_empty() || has_external_scheme(trimmed) {
         return None;
     }
     let without_scheme = trimmed
@@ -199,38 +346,57 @@ fn normalize_link_target(target: String) -> Option<String> {
     }
 }
 
-fn extract_title(raw: &str) -> Option<String> {
-    raw.lines().find_map(|line| {
-        let trimmed = line.trim();
-        trimmed
-            .strip_prefix("#+TITLE:")
-            .or_else(|| trimmed.strip_prefix("#+title:"))
-            .map(str::trim)
-            .filter(|title| !title.is_empty())
-            .map(ToOwned::to_owned)
-    })
+/// True for links such as `https:`, `mailto:`, or `elisp:` that never point at a roam node.
+fn has_external_scheme(target: &str) -> bool {
+    let Some((scheme, _)) = target.split_once(':') else {
+        return false;
+    };
+    !scheme.is_empty()
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !scheme.eq_ignore_ascii_case("id")
+        && !scheme.eq_ignore_ascii_case("file")
 }
 
+/// Returns the value of an in-buffer `#+KEYWORD:` line, matching the keyword case-insensitively.
+fn keyword_value<'a>(line: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = line.trim().strip_prefix("#+")?;
+    let (name, value) = rest.split_once(':')?;
+    name.eq_ignore_ascii_case(keyword).then(|| value.trim())
+}
+
+/// Reads the file-level `:ID:` property, i.e. one that appears before the first headline.
 fn extract_org_id(raw: &str) -> Option<String> {
-    raw.lines().find_map(|line| {
-        let trimmed = line.trim();
-        trimmed
-            .strip_prefix(":ID:")
-            .or_else(|| trimmed.strip_prefix(":id:"))
-            .map(str::trim)
-            .filter(|id| !id.is_empty())
-            .map(ToOwned::to_owned)
-    })
+    raw.lines()
+        .take_while(|line| !line.starts_with('*'))
+        .find_map(parse_id_property)
+}
+
+/// Collects `:ID:` properties declared under headlines so links to headline nodes resolve to
+/// the file node that contains them.
+fn extract_headline_ids(raw: &str) -> Vec<String> {
+    raw.lines()
+        .skip_while(|line| !line.starts_with('*'))
+        .filter_map(parse_id_property)
+        .collect()
+}
+
+fn parse_id_property(line: &str) -> Option<String> {
+    let trimmed = line.trim();
+    trimmed
+        .strip_prefix(":ID:")
+        .or_else(|| trimmed.strip_prefix(":id:"))
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(ToOwned::to_owned)
 }
 
 fn extract_tags(raw: &str) -> Vec<String> {
     let mut tags = HashSet::new();
     for line in raw.lines() {
         let trimmed = line.trim();
-        if let Some(filetags) = trimmed
-            .strip_prefix("#+FILETAGS:")
-            .or_else(|| trimmed.strip_prefix("#+filetags:"))
-        {
+        if let Some(filetags) = keyword_value(trimmed, "FILETAGS") {
             for tag in filetags.split(|ch: char| ch == ':' || ch.is_whitespace()) {
                 let tag = tag.trim();
                 if !tag.is_empty() {
@@ -261,14 +427,7 @@ fn heading_tag_block(line: &str) -> Option<&str> {
     }
 }
 
-fn is_roam_file(path: &PathBuf) -> bool {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.ends_with(".org"))
-        .unwrap_or(false)
-}
-
-fn compute_node_id(path: &PathBuf) -> String {
+fn compute_node_id(path: &Path) -> String {
     path.file_stem()
         .and_then(|stem| stem.to_str())
         .map(|s| s.to_string())
@@ -280,6 +439,123 @@ mod tests {
     use super::*;
     use org_domain::document::OrgDocument;
 
+    fn graph_from(ids: &[&str], edges: &[(&str, &str)]) -> OrgRoamGraph {
+        let mut graph = OrgRoamGraph::default();
+        for id in ids {
+            let idx = graph.graph.add_node(RoamNode {
+                id: id.to_string(),
+                title: id.to_string(),
+                subtitle: None,
+                path: PathBuf::from(format!("/tmp/{id}.org")),
+                tags: Vec::new(),
+            });
+            graph.index_by_id.insert(id.to_string(), idx);
+        }
+        for (source, target) in edges {
+            graph.graph.add_edge(
+                graph.index_by_id[*source],
+                graph.index_by_id[*target],
+                RoamLink {
+                    source: source.to_string(),
+                    target: target.to_string(),
+                    anchor: None,
+                },
+            );
+        }
+        graph
+    }
+
+    #[test]
+    fn reports_forward_links_counts_and_orphans() {
+        let graph = graph_from(
+            &["hub", "alpha", "beta", "lonely"],
+            &[("hub", "alpha"), ("hub", "beta"), ("alpha", "hub")],
+        );
+        let mut forward: Vec<&str> = graph
+            .forward_links_for("hub")
+            .into_iter()
+            .map(|node| node.id.as_str())
+            .collect();
+        forward.sort();
+        assert_eq!(forward, vec!["alpha", "beta"]);
+        assert_eq!(graph.link_counts("hub"), (1, 2));
+        assert_eq!(graph.link_counts("beta"), (1, 0));
+        assert_eq!(graph.link_counts("missing"), (0, 0));
+        let orphans: Vec<&str> = graph
+            .orphan_nodes()
+            .into_iter()
+            .map(|node| node.id.as_str())
+            .collect();
+        assert_eq!(orphans, vec!["lonely"]);
+    }
+
+    #[test]
+    fn dot_export_escapes_titles() {
+        let mut graph = graph_from(&["a", "b"], &[("a", "b")]);
+        graph.graph[graph.index_by_id["a"]].title = "Say \"hi\" \\ bye".into();
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph roam {\n"));
+        assert!(dot.contains(r#""a" [label="Say \"hi\" \\ bye"];"#));
+        assert!(dot.contains(r#""a" -> "b";"#));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn dot_export_lists_every_node_with_tags_and_every_edge() {
+        let mut graph = graph_from(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "a")]);
+        graph.graph[graph.index_by_id["b"]].tags = vec!["rust".into(), "lang".into()];
+        let dot = graph.to_dot();
+        for id in ["a", "c"] {
+            assert!(dot.contains(&format!(r#""{id}" [label="{id}"];"#)));
+        }
+        assert!(dot.contains(r#""b" [label="b", tags="rust lang"];"#));
+        for (source, target) in [("a", "b"), ("b", "c"), ("c", "a")] {
+            assert!(dot.contains(&format!(r#""{source}" -> "{target}";"#)));
+        }
+    }
+
+    /// Checks that every element is closed in order, which is all the export can get wrong.
+    fn assert_balanced_xml(xml: &str) {
+        let mut open: Vec<&str> = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let end = start + rest[start..].find('>').expect("unterminated tag");
+            let tag = &rest[start + 1..end];
+            rest = &rest[end + 1..];
+            if tag.starts_with('?') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open.pop(), Some(name), "mismatched </{name}>");
+            } else if !tag.ends_with('/') {
+                open.push(tag.split_whitespace().next().expect("tag name"));
+            }
+        }
+        assert!(open.is_empty(), "unclosed elements: {open:?}");
+    }
+
+    #[test]
+    fn graphml_export_is_balanced_with_one_element_per_node_and_edge() {
+        let mut graph = graph_from(&["a", "b", "c"], &[("a", "b"), ("b", "c")]);
+        graph.graph[graph.index_by_id["a"]].title = "R&D <notes>".into();
+        graph.graph[graph.index_by_id["c"]].tags = vec!["rust".into()];
+        graph.graph.edge_weights_mut().next().unwrap().anchor = Some("*Intro".into());
+        let xml = graph.to_graphml();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml "));
+        assert_balanced_xml(&xml);
+        assert_eq!(xml.matches("<node ").count(), 3);
+        assert_eq!(xml.matches("<edge ").count(), 2);
+        assert!(xml.contains(r#"<data key="title">R&amp;D &lt;notes&gt;</data>"#));
+        assert!(xml.contains(
+            r#"<node id="c"><data key="title">c</data><data key="tags">rust</data></node>"#
+        ));
+        assert!(
+            xml.contains(r#"<edge source="a" target="b"><data key="anchor">*Intro</data></edge>"#)
+        );
+        assert!(xml.contains(r#"<edge source="b" target="c"/>"#));
+    }
+
     #[test]
     fn compute_node_id_from_path() {
         let path = PathBuf::from("/tmp/2025-01-01-daily.org");
@@ -307,6 +583,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn extract_links_drops_descriptions_schemes_and_external_urls() {
+        let doc = OrgDocument::from_string(
+            "demo",
+            "[[id:abc-123][My Note]] [[file:foo.org]] [[https://example.com][Site]]\n[[ftp://host/file]] [[elisp:(foo)]] [[FILE:../notes/bar.org::*Heading][Bar]]"
+                .into(),
+        );
+        let links = extract_links("source".into(), &doc);
+        let targets: Vec<&str> = links.iter().map(|(_, target, _)| target.as_str()).collect();
+        assert_eq!(targets, vec!["abc-123", "foo", "bar"]);
+        assert_eq!(links[2].2.as_deref(), Some("*Heading"));
+        assert_eq!(links[1].2, None);
+    }
+
     #[test]
     fn normalize_link_targets_match_node_aliases() {
         assert_eq!(
@@ -335,4 +625,45 @@ mod tests {
         assert!(metadata.tags.contains(&"daily".to_string()));
         assert!(metadata.tags.contains(&"mobile".to_string()));
     }
+
+    #[test]
+    fn metadata_reads_mixed_case_keywords_and_falls_back_to_stem() {
+        let doc = OrgDocument::from_string(
+            "demo",
+            "  #+Title: Weekly Review\n#+FileTags: :review:habit:\n".into(),
+        );
+        let metadata = document_metadata(&PathBuf::from("/tmp/20250101-review.org"), &doc);
+        assert_eq!(metadata.title, "Weekly Review");
+        assert_eq!(metadata.subtitle, None);
+        assert_eq!(metadata.tags, vec!["habit", "review"]);
+
+        let doc = OrgDocument::from_string("demo", "Just some text\n".into());
+        let metadata = document_metadata(&PathBuf::from("/tmp/20250101-review.org"), &doc);
+        assert_eq!(metadata.title, "20250101-review");
+        assert!(metadata.tags.is_empty());
+    }
+
+    #[test]
+    fn metadata_uses_file_level_id_and_aliases_headline_ids() {
+        let doc = OrgDocument::from_string(
+            "demo",
+            "* Heading node\n:PROPERTIES:\n:ID: heading-uuid\n:END:\n".into(),
+        );
+        let metadata = document_metadata(&PathBuf::from("/tmp/stem.org"), &doc);
+        assert_eq!(metadata.id, "stem", "headline ids are not the file id");
+        assert!(metadata.aliases.contains(&"heading-uuid".to_string()));
+
+        let doc = OrgDocument::from_string(
+            "demo",
+            ":PROPERTIES:\n:ID: file-uuid\n:END:\n* Heading node\n:PROPERTIES:\n:ID: heading-uuid\n:END:\n"
+                .into(),
+        );
+        let metadata = document_metadata(&PathBuf::from("/tmp/stem.org"), &doc);
+        assert_eq!(metadata.id, "file-uuid");
+        assert!(metadata.aliases.contains(&"heading-uuid".to_string()));
+        assert_eq!(
+            normalize_link_target("id:heading-uuid".into()),
+            Some("heading-uuid".into())
+        );
+    }
 }
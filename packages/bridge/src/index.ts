@@ -11,12 +11,41 @@ export interface AgendaItem {
   path: string;
   headline_line: number;
   todo_keyword?: string | null;
-  kind: "Scheduled" | "Deadline" | "Floating";
+  /** Whether `todo_keyword` is one of the file's done states (DONE, CANCELLED, ...). */
+  done?: boolean;
+  kind: "Scheduled" | "Deadline" | "Appointment" | "Floating";
   timestamp_raw?: string | null;
   repeater?: {
     amount: number;
     unit: "Day" | "Week" | "Month" | "Year";
   } | null;
+  warning?: {
+    amount: number;
+    unit: "Day" | "Week" | "Month" | "Year";
+    first_only: boolean;
+  } | null;
+  closed_at?: string | null;
+  tags?: string[];
+  /** Nearest `:CATEGORY:` property, the file's `#+CATEGORY:`, or the file stem. */
+  category?: string | null;
+  /** Minutes logged by the headline's closed CLOCK entries. */
+  clocked_minutes?: number;
+  /** The headline's full planning line, whatever this item's kind. */
+  planning?: PlanningInfo | null;
+}
+
+export interface PlannedTimestamp {
+  date: string;
+  time?: string | null;
+  raw: string;
+  repeater?: AgendaItem["repeater"];
+  warning?: AgendaItem["warning"];
+}
+
+export interface PlanningInfo {
+  scheduled?: PlannedTimestamp | null;
+  deadline?: PlannedTimestamp | null;
+  closed?: string | null;
 }
 
 export interface Habit {
@@ -30,6 +59,7 @@ export interface Habit {
   log_entries: Array<{
     date: string;
     state: string;
+    from_state?: string | null;
   }>;
   last_repeat?: string | null;
   path?: string;
@@ -38,16 +68,82 @@ export interface Habit {
   timestamp_raw?: string | null;
 }
 
+/** Completion figures over the last 28 days, counted in repeat periods. */
+export interface HabitStats {
+  current_streak: number;
+  longest_streak: number;
+  completion_rate: number;
+  expected_count: number;
+  done_count: number;
+  done_today?: boolean;
+  expected_today?: boolean;
+}
+
+export type TimestampErrorKind =
+  | "malformed"
+  | "bad_date"
+  | "bad_time"
+  | "bad_repeater"
+  | "bad_warning"
+  | "unexpected";
+
+export type TimestampValidation =
+  | { valid: true; timestamp: PlannedTimestamp }
+  | { valid: false; kind: TimestampErrorKind; error: string };
+
+export interface HabitWithStats extends Habit {
+  stats: HabitStats;
+}
+
 export interface AgendaSnapshot {
   items: AgendaItem[];
   habits: Habit[];
 }
 
+export interface DayGroup {
+  date: string;
+  items: AgendaItem[];
+  /** Items hidden by a per-day display limit. */
+  truncated?: number;
+  /** Minutes since local midnight, present only on today's group. */
+  now_minutes?: number | null;
+}
+
+export interface HeadlineRef {
+  path: string;
+  line: number;
+  title: string;
+  todo_keyword?: string | null;
+  depth?: number;
+  tags?: string[];
+  /** Lines of the subtree below the headline, end exclusive. */
+  body_range?: { start: number; end: number };
+}
+
+export interface SearchHit {
+  path: string;
+  line: number;
+  snippet: string;
+  headline?: string | null;
+}
+
+export interface AgendaFilter {
+  /** Keep items with at least one of these tags. */
+  tagsAny?: string[];
+  /** Keep items with every one of these tags. */
+  tagsAll?: string[];
+  /** Keep items whose TODO keyword is one of these. */
+  keywords?: string[];
+  excludeDone?: boolean;
+}
+
 export interface CompleteAgendaParams {
   roots: string[];
   roamRoots?: string[];
   path: string;
   headlineLine: number;
+  /** Done state to move to; the file's first done state when omitted. */
+  doneKeyword?: string;
 }
 
 export interface CaptureRequest {
@@ -55,6 +151,8 @@ export interface CaptureRequest {
   roamRoots?: string[];
   targetPath: string;
   content: string;
+  /** Title of the headline to file under; appended to the end of the file when omitted. */
+  targetHeadline?: string;
 }
 
 export interface SetAgendaStatusParams {
@@ -85,20 +183,40 @@ export interface RoamGraph {
   nodes: Array<{
     id: string;
     title: string;
+    subtitle?: string | null;
     path: string;
     tags: string[];
   }>;
   links: Array<{
     source: string;
     target: string;
+    /** `::` search option naming a headline in the target, e.g. `*Heading`. */
+    anchor?: string | null;
   }>;
 }
 
+export type RoamNode = RoamGraph["nodes"][number];
+
 export interface DocumentRef {
   path: string;
   name: string;
 }
 
+/** Listing orders; ties fall back to path order. */
+export type DocumentSort =
+  | "path"
+  | "modified_desc"
+  | "modified_asc"
+  | "title_asc";
+
+export interface TextSpan {
+  text: string;
+  bold: boolean;
+  italic: boolean;
+  code: boolean;
+  link?: string | null;
+}
+
 export type LexicalNode =
   | (BlockMetadata & {
       type: "heading";
@@ -128,22 +246,49 @@ export type LexicalNode =
       raw: string;
       collapsed: boolean;
     })
-  | (BlockMetadata & { type: "paragraph"; text: string; raw: string })
+  | (BlockMetadata & {
+      type: "paragraph";
+      text: string;
+      spans?: TextSpan[];
+      raw: string;
+    })
   | (BlockMetadata & {
       type: "list_item";
       depth: number;
       ordered: boolean;
       checked?: boolean | null;
       text: string;
+      spans?: TextSpan[];
       raw: string;
     })
   | (BlockMetadata & {
       type: "code_block";
       language?: string | null;
+      /** `language` as a Markdown fence info string, mapped through the service's aliases. */
+      fence_language?: string | null;
+      name?: string | null;
       text: string;
       raw: string;
     })
-  | (BlockMetadata & { type: "table"; rows: string[][]; raw: string })
+  | (BlockMetadata & {
+      type: "verse";
+      lines: string[];
+      raw: string;
+    })
+  | (BlockMetadata & {
+      type: "result";
+      name?: string | null;
+      text: string;
+      raw: string;
+    })
+  | (BlockMetadata & {
+      type: "table";
+      /** Rows above the first `|---` rule; empty without one. */
+      header?: string[][];
+      rows: string[][];
+      formula?: string | null;
+      raw: string;
+    })
   | (BlockMetadata & {
       type: "directive";
       keyword: string;
@@ -152,6 +297,13 @@ export type LexicalNode =
     })
   | (BlockMetadata & { type: "horizontal_rule"; raw: string });
 
+/** Header rows (when present) followed by body rows of a table node. */
+export function tableRows(
+  node: Extract<LexicalNode, { type: "table" }>,
+): string[][] {
+  return [...(node.header ?? []), ...node.rows];
+}
+
 export interface BlockMetadata {
   line_start: number;
   line_end: number;
@@ -170,12 +322,51 @@ export interface UpdateDocumentRequest {
   raw: string;
 }
 
+export interface AppendNoteRequest {
+  roots: string[];
+  roamRoots?: string[];
+  path: string;
+  headlineLine: number;
+  content: string;
+}
+
+type NativeAppendNoteParams = {
+  roots: string[];
+  roam_roots?: string[];
+  path: string;
+  headline_line: number;
+  content: string;
+};
+
+export interface RecomputeStatisticsRequest {
+  roots: string[];
+  roamRoots?: string[];
+  path: string;
+  headlineLine: number;
+  recursive?: boolean;
+}
+
+type NativeRecomputeStatisticsParams = {
+  roots: string[];
+  roam_roots?: string[];
+  path: string;
+  headline_line: number;
+  recursive?: boolean;
+};
+
 type NativeConfig = {
   roots: string[];
   roam_roots?: string[];
   roamRoots?: string[];
 };
 
+type NativeAgendaFilterParams = NativeConfig & {
+  tags_any?: string[];
+  tags_all?: string[];
+  keywords?: string[];
+  exclude_done?: boolean;
+};
+
 type NativeModule = {
   ping(): string;
   load_agenda_snapshot?: (config: NativeConfig) => AgendaSnapshot;
@@ -184,62 +375,102 @@ type NativeModule = {
     config: NativeConfig,
   ) => Promise<AgendaSnapshot>;
   loadAgendaSnapshotAsync?: (config: NativeConfig) => Promise<AgendaSnapshot>;
+  load_agenda_filtered?: (params: NativeAgendaFilterParams) => AgendaSnapshot;
+  loadAgendaFiltered?: (params: NativeAgendaFilterParams) => AgendaSnapshot;
+  load_agenda_filtered_async?: (
+    params: NativeAgendaFilterParams,
+  ) => Promise<AgendaSnapshot>;
+  loadAgendaFilteredAsync?: (
+    params: NativeAgendaFilterParams,
+  ) => Promise<AgendaSnapshot>;
+  load_habits?: (config: NativeConfig) => HabitWithStats[];
+  loadHabits?: (config: NativeConfig) => HabitWithStats[];
+  load_habits_async?: (config: NativeConfig) => Promise<HabitWithStats[]>;
+  loadHabitsAsync?: (config: NativeConfig) => Promise<HabitWithStats[]>;
   complete_agenda_item?: (params: {
     roots: string[];
     roam_roots?: string[];
     path: string;
     headline_line: number;
+    done_keyword?: string;
   }) => AgendaSnapshot;
   completeAgendaItem?: (params: {
     roots: string[];
     roam_roots?: string[];
     path: string;
     headline_line: number;
+    done_keyword?: string;
   }) => AgendaSnapshot;
   complete_agenda_item_async?: (params: {
     roots: string[];
     roam_roots?: string[];
     path: string;
     headline_line: number;
+    done_keyword?: string;
   }) => Promise<AgendaSnapshot>;
   completeAgendaItemAsync?: (params: {
     roots: string[];
     roam_roots?: string[];
     path: string;
     headline_line: number;
+    done_keyword?: string;
   }) => Promise<AgendaSnapshot>;
   append_capture_entry?: (request: {
     roots: string[];
     roam_roots?: string[];
     target_path: string;
     content: string;
+    target_headline?: string;
   }) => AgendaSnapshot;
   appendCaptureEntry?: (request: {
     roots: string[];
     roam_roots?: string[];
     target_path: string;
     content: string;
+    target_headline?: string;
   }) => AgendaSnapshot;
   append_capture_entry_async?: (request: {
     roots: string[];
     roam_roots?: string[];
     target_path: string;
     content: string;
+    target_headline?: string;
   }) => Promise<AgendaSnapshot>;
   appendCaptureEntryAsync?: (request: {
     roots: string[];
     roam_roots?: string[];
     target_path: string;
     content: string;
+    target_headline?: string;
   }) => Promise<AgendaSnapshot>;
   load_roam_graph?: (config: NativeConfig) => RoamGraph;
   loadRoamGraph?: (config: NativeConfig) => RoamGraph;
   load_roam_graph_async?: (config: NativeConfig) => Promise<RoamGraph>;
   loadRoamGraphAsync?: (config: NativeConfig) => Promise<RoamGraph>;
+  load_backlinks?: (config: NativeConfig, nodeId: string) => RoamNode[];
+  loadBacklinks?: (config: NativeConfig, nodeId: string) => RoamNode[];
+  load_backlinks_async?: (
+    config: NativeConfig,
+    nodeId: string,
+  ) => Promise<RoamNode[]>;
+  loadBacklinksAsync?: (
+    config: NativeConfig,
+    nodeId: string,
+  ) => Promise<RoamNode[]>;
   list_documents?: (config: NativeConfig) => string[];
   listDocuments?: (config: NativeConfig) => string[];
   list_documents_async?: (config: NativeConfig) => Promise<string[]>;
   listDocumentsAsync?: (config: NativeConfig) => Promise<string[]>;
+  list_documents_sorted?: (config: NativeConfig, order: DocumentSort) => string[];
+  listDocumentsSorted?: (config: NativeConfig, order: DocumentSort) => string[];
+  list_documents_sorted_async?: (
+    config: NativeConfig,
+    order: DocumentSort,
+  ) => Promise<string[]>;
+  listDocumentsSortedAsync?: (
+    config: NativeConfig,
+    order: DocumentSort,
+  ) => Promise<string[]>;
   load_document?: (config: NativeConfig, path: string) => DocumentPayload;
   loadDocument?: (config: NativeConfig, path: string) => DocumentPayload;
   load_document_async?: (
@@ -274,6 +505,28 @@ type NativeModule = {
     path: string;
     raw: string;
   }) => Promise<DocumentPayload>;
+  validate_timestamp?: (text: string) => TimestampValidation;
+  validateTimestamp?: (text: string) => TimestampValidation;
+  recompute_statistics?: (
+    params: NativeRecomputeStatisticsParams,
+  ) => DocumentPayload;
+  recomputeStatistics?: (
+    params: NativeRecomputeStatisticsParams,
+  ) => DocumentPayload;
+  recompute_statistics_async?: (
+    params: NativeRecomputeStatisticsParams,
+  ) => Promise<DocumentPayload>;
+  recomputeStatisticsAsync?: (
+    params: NativeRecomputeStatisticsParams,
+  ) => Promise<DocumentPayload>;
+  append_note?: (params: NativeAppendNoteParams) => DocumentPayload;
+  appendNote?: (params: NativeAppendNoteParams) => DocumentPayload;
+  append_note_async?: (
+    params: NativeAppendNoteParams,
+  ) => Promise<DocumentPayload>;
+  appendNoteAsync?: (
+    params: NativeAppendNoteParams,
+  ) => Promise<DocumentPayload>;
   set_roots?: (config: NativeConfig) => void;
   setRoots?: (config: NativeConfig) => void;
   set_roots_async?: (config: NativeConfig) => Promise<void>;
@@ -306,10 +559,136 @@ type NativeModule = {
     headline_line: number;
     status: string;
   }) => Promise<AgendaSnapshot>;
+  headlines_matching?: (config: NativeConfig, query: string) => HeadlineRef[];
+  headlinesMatching?: (config: NativeConfig, query: string) => HeadlineRef[];
+  headlines_matching_async?: (
+    config: NativeConfig,
+    query: string,
+  ) => Promise<HeadlineRef[]>;
+  headlinesMatchingAsync?: (
+    config: NativeConfig,
+    query: string,
+  ) => Promise<HeadlineRef[]>;
+  search_documents?: (config: NativeConfig, query: string) => SearchHit[];
+  searchDocuments?: (config: NativeConfig, query: string) => SearchHit[];
+  search_documents_async?: (
+    config: NativeConfig,
+    query: string,
+  ) => Promise<SearchHit[]>;
+  searchDocumentsAsync?: (
+    config: NativeConfig,
+    query: string,
+  ) => Promise<SearchHit[]>;
+  create_document?: (
+    config: NativeConfig,
+    relativePath: string,
+    content: string,
+  ) => string;
+  createDocument?: (
+    config: NativeConfig,
+    relativePath: string,
+    content: string,
+  ) => string;
+  create_document_async?: (
+    config: NativeConfig,
+    relativePath: string,
+    content: string,
+  ) => Promise<string>;
+  createDocumentAsync?: (
+    config: NativeConfig,
+    relativePath: string,
+    content: string,
+  ) => Promise<string>;
+  delete_document?: (config: NativeConfig, path: string) => void;
+  deleteDocument?: (config: NativeConfig, path: string) => void;
+  delete_document_async?: (config: NativeConfig, path: string) => Promise<void>;
+  deleteDocumentAsync?: (config: NativeConfig, path: string) => Promise<void>;
+  rename_document?: (config: NativeConfig, from: string, to: string) => void;
+  renameDocument?: (config: NativeConfig, from: string, to: string) => void;
+  rename_document_async?: (
+    config: NativeConfig,
+    from: string,
+    to: string,
+  ) => Promise<void>;
+  renameDocumentAsync?: (
+    config: NativeConfig,
+    from: string,
+    to: string,
+  ) => Promise<void>;
+  week_agenda?: (config: NativeConfig) => DayGroup[];
+  weekAgenda?: (config: NativeConfig) => DayGroup[];
+  week_agenda_async?: (config: NativeConfig) => Promise<DayGroup[]>;
+  weekAgendaAsync?: (config: NativeConfig) => Promise<DayGroup[]>;
 };
 
 let cachedBinding: NativeModule | null = null;
 
+export type BridgeErrorCode =
+  | "NotFound"
+  | "AlreadyExists"
+  | "PermissionDenied"
+  | "InvalidInput"
+  | "Io"
+  | "Parse"
+  | "Internal";
+
+const BRIDGE_ERROR_CODES: readonly BridgeErrorCode[] = [
+  "NotFound",
+  "AlreadyExists",
+  "PermissionDenied",
+  "InvalidInput",
+  "Io",
+  "Parse",
+  "Internal",
+];
+
+export class OrgBridgeError extends Error {
+  readonly code: BridgeErrorCode;
+
+  constructor(code: BridgeErrorCode, message: string) {
+    super(message);
+    this.name = "OrgBridgeError";
+    this.code = code;
+  }
+}
+
+// The native bridge prefixes each message with its category, e.g. "[NotFound] ...".
+export function toBridgeError(error: unknown): OrgBridgeError {
+  if (error instanceof OrgBridgeError) {
+    return error;
+  }
+  const message = error instanceof Error ? error.message : String(error);
+  const match = /^\[(\w+)\] ([\s\S]*)$/.exec(message);
+  if (match && BRIDGE_ERROR_CODES.includes(match[1] as BridgeErrorCode)) {
+    return new OrgBridgeError(match[1] as BridgeErrorCode, match[2]);
+  }
+  return new OrgBridgeError("Internal", message);
+}
+
+function withBridgeErrors(binding: NativeModule): NativeModule {
+  const wrapped: Record<string, unknown> = {};
+  for (const [name, value] of Object.entries(binding)) {
+    if (typeof value !== "function") {
+      wrapped[name] = value;
+      continue;
+    }
+    wrapped[name] = (...args: unknown[]) => {
+      let result: unknown;
+      try {
+        result = value(...args);
+      } catch (error) {
+        throw toBridgeError(error);
+      }
+      return result instanceof Promise
+        ? result.catch((error: unknown) => {
+            throw toBridgeError(error);
+          })
+        : result;
+    };
+  }
+  return wrapped as NativeModule;
+}
+
 export type BridgeEvent = "agendaChanged" | "documentsChanged" | "rootsChanged";
 
 type BridgeListener = () => void;
@@ -412,7 +791,9 @@ function resolveNativeBinding(): NativeModule {
 
   for (const candidate of candidatePaths) {
     if (existsSync(candidate)) {
-      cachedBinding = requireNative(candidate) as NativeModule;
+      cachedBinding = withBridgeErrors(
+        requireNative(candidate) as NativeModule,
+      );
       return cachedBinding;
     }
   }
@@ -451,6 +832,7 @@ function toNativeAgendaParams(params: CompleteAgendaParams): {
   roam_roots?: string[];
   path: string;
   headline_line: number;
+  done_keyword?: string;
 } {
   const normalized = normalizeOrgBridgeConfig(params);
   return {
@@ -458,6 +840,7 @@ function toNativeAgendaParams(params: CompleteAgendaParams): {
     roam_roots: normalized.roamRoots,
     path: normalizeLocalOrgPath(params.path),
     headline_line: params.headlineLine,
+    done_keyword: params.doneKeyword,
   };
 }
 
@@ -466,6 +849,7 @@ function toNativeCaptureRequest(request: CaptureRequest): {
   roam_roots?: string[];
   target_path: string;
   content: string;
+  target_headline?: string;
 } {
   const normalized = normalizeOrgBridgeConfig(request);
   return {
@@ -473,6 +857,7 @@ function toNativeCaptureRequest(request: CaptureRequest): {
     roam_roots: normalized.roamRoots,
     target_path: normalizeLocalOrgPath(request.targetPath),
     content: request.content,
+    target_headline: request.targetHeadline,
   };
 }
 
@@ -491,6 +876,32 @@ function toNativeUpdateDocumentRequest(request: UpdateDocumentRequest): {
   };
 }
 
+function toNativeAppendNoteParams(
+  request: AppendNoteRequest,
+): NativeAppendNoteParams {
+  const normalized = normalizeOrgBridgeConfig(request);
+  return {
+    roots: normalized.roots,
+    roam_roots: normalized.roamRoots,
+    path: normalizeLocalOrgPath(request.path),
+    headline_line: request.headlineLine,
+    content: request.content,
+  };
+}
+
+function toNativeRecomputeStatisticsParams(
+  request: RecomputeStatisticsRequest,
+): NativeRecomputeStatisticsParams {
+  const normalized = normalizeOrgBridgeConfig(request);
+  return {
+    roots: normalized.roots,
+    roam_roots: normalized.roamRoots,
+    path: normalizeLocalOrgPath(request.path),
+    headline_line: request.headlineLine,
+    recursive: request.recursive,
+  };
+}
+
 function toNativeSetAgendaStatusParams(params: SetAgendaStatusParams): {
   roots: string[];
   roam_roots?: string[];
@@ -541,6 +952,82 @@ export async function loadAgendaSnapshotAsync(
   return normalizeAgendaSnapshot(raw);
 }
 
+function toNativeAgendaFilterParams(
+  config: OrgBridgeConfig,
+  filter: AgendaFilter,
+): NativeAgendaFilterParams {
+  return {
+    ...toNativeConfig(config),
+    tags_any: filter.tagsAny,
+    tags_all: filter.tagsAll,
+    keywords: filter.keywords,
+    exclude_done: filter.excludeDone,
+  };
+}
+
+export function loadAgendaFiltered(
+  config: OrgBridgeConfig,
+  filter: AgendaFilter,
+): AgendaSnapshot {
+  if (config.roots.length === 0) {
+    return { items: [], habits: [] };
+  }
+  const binding = resolveNativeBinding();
+  const load = binding.load_agenda_filtered ?? binding.loadAgendaFiltered;
+  if (!load) {
+    throw new Error("Native bridge does not support agenda filters");
+  }
+  const raw = load(toNativeAgendaFilterParams(config, filter));
+  return normalizeAgendaSnapshot(raw);
+}
+
+export async function loadAgendaFilteredAsync(
+  config: OrgBridgeConfig,
+  filter: AgendaFilter,
+): Promise<AgendaSnapshot> {
+  if (config.roots.length === 0) {
+    return { items: [], habits: [] };
+  }
+  const binding = resolveNativeBinding();
+  const nativeParams = toNativeAgendaFilterParams(config, filter);
+  const loadAsync = binding.load_agenda_filtered_async ?? binding.loadAgendaFilteredAsync;
+  const load = binding.load_agenda_filtered ?? binding.loadAgendaFiltered;
+  if (!loadAsync && !load) {
+    throw new Error("Native bridge does not support agenda filters");
+  }
+  const raw = loadAsync ? await loadAsync(nativeParams) : load!(nativeParams);
+  return normalizeAgendaSnapshot(raw);
+}
+
+export function loadHabits(config: OrgBridgeConfig): HabitWithStats[] {
+  if (config.roots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const load = binding.load_habits ?? binding.loadHabits;
+  if (!load) {
+    throw new Error("Native bridge does not support loading habits");
+  }
+  return load(toNativeConfig(config)).map(normalizeHabitWithStats);
+}
+
+export async function loadHabitsAsync(
+  config: OrgBridgeConfig,
+): Promise<HabitWithStats[]> {
+  if (config.roots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const loadAsync = binding.load_habits_async ?? binding.loadHabitsAsync;
+  const load = binding.load_habits ?? binding.loadHabits;
+  if (!loadAsync && !load) {
+    throw new Error("Native bridge does not support loading habits");
+  }
+  const raw = loadAsync ? await loadAsync(nativeConfig) : load!(nativeConfig);
+  return raw.map(normalizeHabitWithStats);
+}
+
 export function completeAgendaItem(
   params: CompleteAgendaParams,
 ): AgendaSnapshot {
@@ -625,24 +1112,74 @@ export async function loadRoamGraphAsync(
     : load!(nativeConfig);
 }
 
-export function listDocuments(config: OrgBridgeConfig): DocumentRef[] {
+export function loadBacklinks(
+  config: OrgBridgeConfig,
+  nodeId: string,
+): RoamNode[] {
+  if (!config.roamRoots || config.roamRoots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const load = binding.load_backlinks ?? binding.loadBacklinks;
+  return load ? load(toNativeConfig(config), nodeId) : [];
+}
+
+export async function loadBacklinksAsync(
+  config: OrgBridgeConfig,
+  nodeId: string,
+): Promise<RoamNode[]> {
+  if (!config.roamRoots || config.roamRoots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const loadAsync = binding.load_backlinks_async ?? binding.loadBacklinksAsync;
+  const load = binding.load_backlinks ?? binding.loadBacklinks;
+  if (loadAsync) {
+    return loadAsync(nativeConfig, nodeId);
+  }
+  return load ? load(nativeConfig, nodeId) : [];
+}
+
+export function listDocuments(
+  config: OrgBridgeConfig,
+  order: DocumentSort = "path",
+): DocumentRef[] {
   if (!hasAnyRoot(config)) {
     return [];
   }
   const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const listSorted = binding.list_documents_sorted ?? binding.listDocumentsSorted;
+  if (order !== "path" && listSorted) {
+    return documentRefsFromPaths(listSorted(nativeConfig, order));
+  }
   const list = binding.list_documents ?? binding.listDocuments;
-  const entries = list!(toNativeConfig(config));
+  const entries = list!(nativeConfig);
   return documentRefsFromPaths(entries);
 }
 
 export async function listDocumentsAsync(
   config: OrgBridgeConfig,
+  order: DocumentSort = "path",
 ): Promise<DocumentRef[]> {
   if (!hasAnyRoot(config)) {
     return [];
   }
   const binding = resolveNativeBinding();
   const nativeConfig = toNativeConfig(config);
+  if (order !== "path") {
+    const listSortedAsync =
+      binding.list_documents_sorted_async ?? binding.listDocumentsSortedAsync;
+    const listSorted =
+      binding.list_documents_sorted ?? binding.listDocumentsSorted;
+    if (listSortedAsync) {
+      return documentRefsFromPaths(await listSortedAsync(nativeConfig, order));
+    }
+    if (listSorted) {
+      return documentRefsFromPaths(listSorted(nativeConfig, order));
+    }
+  }
   const listAsync = binding.list_documents_async ?? binding.listDocumentsAsync;
   const list = binding.list_documents ?? binding.listDocuments;
   const entries = listAsync
@@ -651,6 +1188,93 @@ export async function listDocumentsAsync(
   return documentRefsFromPaths(entries);
 }
 
+export function headlinesMatching(
+  config: OrgBridgeConfig,
+  query: string,
+): HeadlineRef[] {
+  if (!hasAnyRoot(config) || query.trim().length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const search = binding.headlines_matching ?? binding.headlinesMatching;
+  return search ? search(toNativeConfig(config), query) : [];
+}
+
+export async function headlinesMatchingAsync(
+  config: OrgBridgeConfig,
+  query: string,
+): Promise<HeadlineRef[]> {
+  if (!hasAnyRoot(config) || query.trim().length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const searchAsync =
+    binding.headlines_matching_async ?? binding.headlinesMatchingAsync;
+  const search = binding.headlines_matching ?? binding.headlinesMatching;
+  if (searchAsync) {
+    return searchAsync(nativeConfig, query);
+  }
+  return search ? search(nativeConfig, query) : [];
+}
+
+export function searchDocuments(
+  config: OrgBridgeConfig,
+  query: string,
+): SearchHit[] {
+  if (!hasAnyRoot(config) || query.trim().length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const search = binding.search_documents ?? binding.searchDocuments;
+  return search ? search(toNativeConfig(config), query) : [];
+}
+
+export async function searchDocumentsAsync(
+  config: OrgBridgeConfig,
+  query: string,
+): Promise<SearchHit[]> {
+  if (!hasAnyRoot(config) || query.trim().length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const searchAsync =
+    binding.search_documents_async ?? binding.searchDocumentsAsync;
+  const search = binding.search_documents ?? binding.searchDocuments;
+  if (searchAsync) {
+    return searchAsync(nativeConfig, query);
+  }
+  return search ? search(nativeConfig, query) : [];
+}
+
+export function weekAgenda(config: OrgBridgeConfig): DayGroup[] {
+  if (config.roots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const load = binding.week_agenda ?? binding.weekAgenda;
+  return normalizeDayGroups(load ? load(toNativeConfig(config)) : []);
+}
+
+export async function weekAgendaAsync(
+  config: OrgBridgeConfig,
+): Promise<DayGroup[]> {
+  if (config.roots.length === 0) {
+    return [];
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const loadAsync = binding.week_agenda_async ?? binding.weekAgendaAsync;
+  const load = binding.week_agenda ?? binding.weekAgenda;
+  const raw = loadAsync
+    ? await loadAsync(nativeConfig)
+    : load
+      ? load(nativeConfig)
+      : [];
+  return normalizeDayGroups(raw);
+}
+
 export function parseOrgDocument(raw: string, path = ""): DocumentPayload {
   return { path, raw, lexical: rawToLexical(raw) };
 }
@@ -717,6 +1341,223 @@ export async function updateDocumentAsync(
   return payload;
 }
 
+export function appendNote(request: AppendNoteRequest): DocumentPayload {
+  if (!hasAnyRoot(request)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const append = binding.append_note ?? binding.appendNote;
+  if (!append) {
+    throw new Error("Native bridge does not support appending notes");
+  }
+  const payload = append(toNativeAppendNoteParams(request));
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+  return payload;
+}
+
+export async function appendNoteAsync(
+  request: AppendNoteRequest,
+): Promise<DocumentPayload> {
+  if (!hasAnyRoot(request)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const nativeRequest = toNativeAppendNoteParams(request);
+  const appendAsync = binding.append_note_async ?? binding.appendNoteAsync;
+  const append = binding.append_note ?? binding.appendNote;
+  let payload: DocumentPayload;
+  if (appendAsync) {
+    payload = await appendAsync(nativeRequest);
+  } else if (append) {
+    payload = append(nativeRequest);
+  } else {
+    throw new Error("Native bridge does not support appending notes");
+  }
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+  return payload;
+}
+
+export function validateTimestamp(text: string): TimestampValidation {
+  const binding = resolveNativeBinding();
+  const validate = binding.validate_timestamp ?? binding.validateTimestamp;
+  if (!validate) {
+    throw new Error("Native bridge does not support validating timestamps");
+  }
+  return validate(text);
+}
+
+export function recomputeStatistics(
+  request: RecomputeStatisticsRequest,
+): DocumentPayload {
+  if (!hasAnyRoot(request)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const recompute =
+    binding.recompute_statistics ?? binding.recomputeStatistics;
+  if (!recompute) {
+    throw new Error("Native bridge does not support recomputing statistics");
+  }
+  const payload = recompute(toNativeRecomputeStatisticsParams(request));
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+  return payload;
+}
+
+export async function recomputeStatisticsAsync(
+  request: RecomputeStatisticsRequest,
+): Promise<DocumentPayload> {
+  if (!hasAnyRoot(request)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const nativeRequest = toNativeRecomputeStatisticsParams(request);
+  const recomputeAsync =
+    binding.recompute_statistics_async ?? binding.recomputeStatisticsAsync;
+  const recompute =
+    binding.recompute_statistics ?? binding.recomputeStatistics;
+  let payload: DocumentPayload;
+  if (recomputeAsync) {
+    payload = await recomputeAsync(nativeRequest);
+  } else if (recompute) {
+    payload = recompute(nativeRequest);
+  } else {
+    throw new Error("Native bridge does not support recomputing statistics");
+  }
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+  return payload;
+}
+
+export function createDocument(
+  config: OrgBridgeConfig,
+  relativePath: string,
+  content: string,
+): string {
+  if (config.roots.length === 0) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const create = binding.create_document ?? binding.createDocument;
+  if (!create) {
+    throw new Error("Native bridge does not support creating documents");
+  }
+  const path = create(toNativeConfig(config), relativePath, content);
+  emitBridgeEvent("documentsChanged");
+  return path;
+}
+
+export async function createDocumentAsync(
+  config: OrgBridgeConfig,
+  relativePath: string,
+  content: string,
+): Promise<string> {
+  if (config.roots.length === 0) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const createAsync =
+    binding.create_document_async ?? binding.createDocumentAsync;
+  const create = binding.create_document ?? binding.createDocument;
+  if (!createAsync && !create) {
+    throw new Error("Native bridge does not support creating documents");
+  }
+  const path = createAsync
+    ? await createAsync(nativeConfig, relativePath, content)
+    : create!(nativeConfig, relativePath, content);
+  emitBridgeEvent("documentsChanged");
+  return path;
+}
+
+export function deleteDocument(config: OrgBridgeConfig, path: string): void {
+  if (!hasAnyRoot(config)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const remove = binding.delete_document ?? binding.deleteDocument;
+  if (!remove) {
+    throw new Error("Native bridge does not support deleting documents");
+  }
+  remove(toNativeConfig(config), normalizeLocalOrgPath(path));
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+}
+
+export async function deleteDocumentAsync(
+  config: OrgBridgeConfig,
+  path: string,
+): Promise<void> {
+  if (!hasAnyRoot(config)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const normalizedPath = normalizeLocalOrgPath(path);
+  const removeAsync =
+    binding.delete_document_async ?? binding.deleteDocumentAsync;
+  const remove = binding.delete_document ?? binding.deleteDocument;
+  if (removeAsync) {
+    await removeAsync(nativeConfig, normalizedPath);
+  } else if (remove) {
+    remove(nativeConfig, normalizedPath);
+  } else {
+    throw new Error("Native bridge does not support deleting documents");
+  }
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+}
+
+export function renameDocument(
+  config: OrgBridgeConfig,
+  from: string,
+  to: string,
+): void {
+  if (!hasAnyRoot(config)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const rename = binding.rename_document ?? binding.renameDocument;
+  if (!rename) {
+    throw new Error("Native bridge does not support renaming documents");
+  }
+  rename(
+    toNativeConfig(config),
+    normalizeLocalOrgPath(from),
+    normalizeLocalOrgPath(to),
+  );
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+}
+
+export async function renameDocumentAsync(
+  config: OrgBridgeConfig,
+  from: string,
+  to: string,
+): Promise<void> {
+  if (!hasAnyRoot(config)) {
+    throw new Error("No Org roots configured");
+  }
+  const binding = resolveNativeBinding();
+  const nativeConfig = toNativeConfig(config);
+  const normalizedFrom = normalizeLocalOrgPath(from);
+  const normalizedTo = normalizeLocalOrgPath(to);
+  const renameAsync =
+    binding.rename_document_async ?? binding.renameDocumentAsync;
+  const rename = binding.rename_document ?? binding.renameDocument;
+  if (renameAsync) {
+    await renameAsync(nativeConfig, normalizedFrom, normalizedTo);
+  } else if (rename) {
+    rename(nativeConfig, normalizedFrom, normalizedTo);
+  } else {
+    throw new Error("Native bridge does not support renaming documents");
+  }
+  emitBridgeEvent("documentsChanged");
+  emitBridgeEvent("agendaChanged");
+}
+
 export const EMPTY_CONFIG: OrgBridgeConfig = { roots: [] };
 
 export function subscribeBridgeEvent(
@@ -887,14 +1728,30 @@ function normalizeAgendaSnapshot(snapshot: AgendaSnapshot): AgendaSnapshot {
   };
 }
 
+function normalizeDayGroups(groups: DayGroup[]): DayGroup[] {
+  return groups.map((group) => ({
+    date: group.date,
+    items: (group.items ?? []).map(normalizeAgendaItem),
+    truncated: group.truncated ?? 0,
+    now_minutes: group.now_minutes ?? null,
+  }));
+}
+
+/** Fallback for payloads without `done`: the default org done states. */
+function isDoneKeyword(keyword?: string | null): boolean {
+  return keyword === "DONE" || keyword === "CANCELLED" || keyword === "CANCELED";
+}
+
 function normalizeAgendaItem(item: AgendaItem): AgendaItem {
   return {
     ...item,
     date: item.date ?? null,
     time: item.time ?? null,
     todo_keyword: item.todo_keyword ?? null,
+    done: item.done ?? isDoneKeyword(item.todo_keyword),
     timestamp_raw: item.timestamp_raw ?? null,
     repeater: item.repeater ?? null,
+    warning: item.warning ?? null,
   };
 }
 
@@ -909,6 +1766,10 @@ function normalizeHabit(habit: Habit): Habit {
   };
 }
 
+function normalizeHabitWithStats(habit: HabitWithStats): HabitWithStats {
+  return { ...normalizeHabit(habit), stats: habit.stats };
+}
+
 const e2eDocs = new Map<string, string>();
 
 function weekdayName(year: number, month: number, day: number): string {
@@ -993,8 +1854,8 @@ echo e2e-${index}
 const e2eNativeModule: NativeModule = {
   ping: () => "postep-org-bridge-e2e",
   load_agenda_snapshot: () => buildE2EAgendaSnapshot(),
-  complete_agenda_item: ({ path, headline_line }) => {
-    setE2EHeadlineStatus(path, headline_line, "DONE");
+  complete_agenda_item: ({ path, headline_line, done_keyword }) => {
+    setE2EHeadlineStatus(path, headline_line, done_keyword ?? "DONE");
     return buildE2EAgendaSnapshot();
   },
   append_capture_entry: ({ target_path, content }) => {
@@ -1120,7 +1981,7 @@ function buildE2EAgendaSnapshot(): AgendaSnapshot {
           if (node.type === "paragraph" || node.type === "list_item")
             return [node.text];
           if (node.type === "table")
-            return [node.rows.map((row) => row.join(" · ")).join("\n")];
+            return [tableRows(node).map((row) => row.join(" · ")).join("\n")];
           return [];
         })
         .filter(Boolean)
@@ -1301,24 +2162,36 @@ function rawToLexical(raw: string): LexicalNode[] {
       const start = idx;
       const rawLines: string[] = [];
       const rows: string[][] = [];
+      let headerRows = 0;
       while (
         idx < lines.length &&
         lines[idx].trim().startsWith("|") &&
         lines[idx].trim().endsWith("|")
       ) {
         rawLines.push(lines[idx]);
-        rows.push(
-          lines[idx]
-            .trim()
-            .replace(/^\||\|$/g, "")
-            .split("|")
-            .map((cell) => cell.trim()),
-        );
+        if (lines[idx].trim().startsWith("|-")) {
+          if (headerRows === 0) {
+            headerRows = rows.length;
+          }
+        } else {
+          rows.push(
+            lines[idx]
+              .trim()
+              .replace(/^\||\|$/g, "")
+              .split("|")
+              .map((cell) => cell.trim()),
+          );
+        }
         idx += 1;
       }
+      const width = Math.max(0, ...rows.map((row) => row.length));
+      const pad = (row: string[]) =>
+        row.concat(Array(Math.max(0, width - row.length)).fill(""));
+      const padded = rows.map(pad);
       nodes.push({
         type: "table",
-        rows,
+        header: padded.slice(0, headerRows),
+        rows: padded.slice(headerRows),
         raw: rawLines.join("\n"),
         line_start: start,
         line_end: idx - 1,